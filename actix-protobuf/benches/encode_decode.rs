@@ -0,0 +1,90 @@
+use actix_protobuf::{ProtoBuf, ProtoBufMessage};
+use actix_web::{
+    http::header::CONTENT_TYPE, rt::System, test::TestRequest, HttpResponse, Responder as _,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use prost::Message;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Blob {
+    #[prost(int32, tag = "1")]
+    pub id: i32,
+    #[prost(bytes = "vec", tag = "2")]
+    pub data: Vec<u8>,
+}
+
+fn blobs() -> [(&'static str, Blob); 2] {
+    [
+        (
+            "1KB",
+            Blob {
+                id: 1,
+                data: vec![0xab; 1024],
+            },
+        ),
+        (
+            "1MB",
+            Blob {
+                id: 2,
+                data: vec![0xab; 1024 * 1024],
+            },
+        ),
+    ]
+}
+
+fn encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode");
+    let req = TestRequest::default().to_http_request();
+
+    for (name, blob) in blobs() {
+        group.throughput(Throughput::Bytes(blob.encoded_len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &blob, |b, blob| {
+            b.iter(|| ProtoBuf(blob.clone()).respond_to(&req))
+        });
+    }
+
+    group.finish();
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+    let system = System::new();
+
+    for (name, blob) in blobs() {
+        let body = blob.encode_to_vec();
+
+        group.throughput(Throughput::Bytes(body.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &body, |b, body| {
+            b.iter(|| {
+                let (req, mut pl) = TestRequest::post()
+                    .insert_header((CONTENT_TYPE, "application/protobuf"))
+                    .set_payload(body.clone())
+                    .to_http_parts();
+
+                system
+                    .block_on(ProtoBufMessage::<Blob>::new(&req, &mut pl).limit(2 * 1024 * 1024))
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn response_builder(c: &mut Criterion) {
+    use actix_protobuf::ProtoBufResponseBuilder as _;
+
+    let mut group = c.benchmark_group("response_builder");
+
+    for (name, blob) in blobs() {
+        group.throughput(Throughput::Bytes(blob.encoded_len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &blob, |b, blob| {
+            b.iter(|| HttpResponse::Ok().protobuf(blob.clone()).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, encode, decode, response_builder);
+criterion_main!(benches);
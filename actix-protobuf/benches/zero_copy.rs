@@ -0,0 +1,94 @@
+use actix_protobuf::ProtoBufMessage;
+use actix_web::{
+    dev::Payload,
+    error::PayloadError,
+    http::header::CONTENT_TYPE,
+    rt::System,
+    test::TestRequest,
+    web::{Bytes, BytesMut},
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use futures_util::stream;
+use prost::Message;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Blob {
+    #[prost(int32, tag = "1")]
+    pub id: i32,
+    #[prost(bytes = "bytes", tag = "2")]
+    pub data: Bytes,
+}
+
+fn payload() -> Bytes {
+    let blob = Blob {
+        id: 1,
+        data: Bytes::from(vec![0xab; 500 * 1024]),
+    };
+    Bytes::from(blob.encode_to_vec())
+}
+
+/// Splits `payload` into chunks of at most `size` bytes, as they would be read from the network.
+fn chunks(payload: &Bytes, size: usize) -> Vec<Bytes> {
+    (0..payload.len())
+        .step_by(size)
+        .map(|start| payload.slice(start..payload.len().min(start + size)))
+        .collect()
+}
+
+/// Creates a payload streaming `chunks`.
+fn chunked_payload(chunks: &[Bytes]) -> Payload {
+    let items: Vec<Result<Bytes, PayloadError>> = chunks.iter().cloned().map(Ok).collect();
+
+    Payload::Stream {
+        payload: Box::pin(stream::iter(items)),
+    }
+}
+
+/// Previous implementation: copies all chunks into a buffer and decodes from a mutable slice.
+fn decode_copy(chunks: &[Bytes]) -> Blob {
+    let mut body = BytesMut::with_capacity(8192);
+    for chunk in chunks {
+        body.extend_from_slice(chunk);
+    }
+    Blob::decode(&mut body).unwrap()
+}
+
+fn extractor(c: &mut Criterion) {
+    let payload = payload();
+    let system = System::new();
+    let mut group = c.benchmark_group("extract_500KB");
+    group.throughput(Throughput::Bytes(payload.len() as u64));
+
+    for (name, chunk_size) in [("single_chunk", payload.len()), ("64KB_chunks", 64 * 1024)] {
+        let chunks = chunks(&payload, chunk_size);
+
+        // baseline: the previous decoding path, measured without the extractor's overhead
+        group.bench_with_input(BenchmarkId::new("copy", name), &chunks, |b, chunks| {
+            b.iter(|| decode_copy(chunks))
+        });
+
+        group.bench_with_input(
+            BenchmarkId::new("ProtoBufMessage", name),
+            &chunks,
+            |b, chunks| {
+                b.iter(|| {
+                    let req = TestRequest::post()
+                        .insert_header((CONTENT_TYPE, "application/protobuf"))
+                        .to_http_request();
+
+                    system
+                        .block_on(
+                            ProtoBufMessage::<Blob>::new(&req, &mut chunked_payload(chunks))
+                                .limit(1024 * 1024),
+                        )
+                        .unwrap()
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, extractor);
+criterion_main!(benches);
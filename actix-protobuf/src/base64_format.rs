@@ -0,0 +1,84 @@
+//! Base64 encoding of messages.
+
+use prost::Message;
+
+use crate::{ProtoBuf, ProtoBufPayloadError};
+
+impl<T: Message> ProtoBuf<T> {
+    /// Encodes the message as URL-safe base64 without padding.
+    ///
+    /// Useful for embedding binary messages in JSON fields or URL parameters.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_protobuf::ProtoBuf;
+    ///
+    /// #[derive(Clone, PartialEq, prost::Message)]
+    /// pub struct MyObject {
+    ///     #[prost(int32, tag = "1")]
+    ///     pub number: i32,
+    /// }
+    ///
+    /// let encoded = ProtoBuf(MyObject { number: 9 }).encode_to_base64()?;
+    /// assert_eq!(encoded, "CAk");
+    ///
+    /// let decoded = ProtoBuf::<MyObject>::from_base64(&encoded)?;
+    /// assert_eq!(decoded.number, 9);
+    /// # Ok::<_, actix_protobuf::ProtoBufPayloadError>(())
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+    pub fn encode_to_base64(&self) -> Result<String, ProtoBufPayloadError> {
+        let mut buf = Vec::with_capacity(self.0.encoded_len());
+        self.0
+            .encode(&mut buf)
+            .map_err(ProtoBufPayloadError::Serialize)?;
+        Ok(base64::encode_config(buf, base64::URL_SAFE_NO_PAD))
+    }
+}
+
+impl<T: Message + Default> ProtoBuf<T> {
+    /// Decodes a message from URL-safe base64 without padding, as produced by
+    /// [`encode_to_base64`](Self::encode_to_base64).
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+    pub fn from_base64(s: &str) -> Result<ProtoBuf<T>, ProtoBufPayloadError> {
+        let buf = base64::decode_config(s, base64::URL_SAFE_NO_PAD)
+            .map_err(ProtoBufPayloadError::Base64)?;
+        Ok(ProtoBuf(T::decode(buf.as_slice())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+        #[prost(bytes = "vec", tag = "2")]
+        pub data: Vec<u8>,
+    }
+
+    #[test]
+    fn test_base64_roundtrip() {
+        let msg = MyObject {
+            number: 9,
+            data: vec![0xfb, 0xff],
+        };
+
+        let encoded = ProtoBuf(msg.clone()).encode_to_base64().unwrap();
+        assert!(!encoded.contains(&['+', '/', '='][..]));
+
+        let decoded = ProtoBuf::<MyObject>::from_base64(&encoded).unwrap();
+        assert_eq!(decoded.0, msg);
+    }
+
+    #[test]
+    fn test_from_base64_invalid() {
+        let err = ProtoBuf::<MyObject>::from_base64("not base64!").unwrap_err();
+        assert!(matches!(err, ProtoBufPayloadError::Base64(_)));
+
+        let err = ProtoBuf::<MyObject>::from_base64("CA").unwrap_err();
+        assert!(matches!(err, ProtoBufPayloadError::Deserialize(_)));
+    }
+}
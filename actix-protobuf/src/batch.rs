@@ -0,0 +1,174 @@
+//! Extractor for batches of messages wrapped in a single `repeated` field.
+
+use std::fmt;
+
+use actix_web::{dev::Payload, Error, FromRequest, HttpRequest};
+use futures_util::future::{FutureExt as _, LocalBoxFuture};
+use prost::{
+    bytes::{Buf, BufMut},
+    encoding::{self, DecodeContext, WireType},
+    DecodeError, Message,
+};
+
+use crate::{ProtoBufConfig, ProtoBufMessage};
+
+/// Protobuf extractor for a list of messages.
+///
+/// Expects the request body to be a message whose first (and only) field, with tag `1`, is a
+/// `repeated T`. This is wire-compatible with a wrapper message such as the following, without
+/// having to define one in every `.proto` file:
+///
+/// ```protobuf
+/// message MyObjectBatch {
+///     repeated MyObject items = 1;
+/// }
+/// ```
+///
+/// Unknown fields in the wrapper message are ignored.
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::ProtoBufBatch;
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// pub struct MyObject {
+///     #[prost(int32, tag = "1")]
+///     pub number: i32,
+/// }
+///
+/// async fn index(batch: ProtoBufBatch<MyObject>) -> String {
+///     format!("Received {} items", batch.items().len())
+/// }
+/// ```
+pub struct ProtoBufBatch<T: Message>(pub Vec<T>);
+
+impl<T: Message> ProtoBufBatch<T> {
+    /// Returns the decoded items.
+    pub fn items(&self) -> &[T] {
+        &self.0
+    }
+
+    /// Consumes the extractor and returns the decoded items.
+    pub fn into_items(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T: Message> fmt::Debug for ProtoBufBatch<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ProtoBufBatch: {:?}", self.0)
+    }
+}
+
+impl<T> FromRequest for ProtoBufBatch<T>
+where
+    T: Message + Default + 'static,
+{
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let limit = req
+            .app_data::<ProtoBufConfig>()
+            .map(|c| c.limit)
+            .unwrap_or(262_144);
+        ProtoBufMessage::<Batch<T>>::new(req, payload)
+            .limit(limit)
+            .map(move |res| match res {
+                Err(e) => Err(e.into()),
+                Ok(batch) => Ok(ProtoBufBatch(batch.items)),
+            })
+            .boxed_local()
+    }
+}
+
+/// Wrapper message with a single `repeated T items = 1;` field.
+#[derive(Debug)]
+struct Batch<T> {
+    items: Vec<T>,
+}
+
+impl<T> Default for Batch<T> {
+    fn default() -> Self {
+        Batch { items: Vec::new() }
+    }
+}
+
+impl<T: Message + Default> Message for Batch<T> {
+    fn encode_raw<B: BufMut>(&self, buf: &mut B) {
+        encoding::message::encode_repeated(1, &self.items, buf)
+    }
+
+    fn merge_field<B: Buf>(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut B,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        if tag == 1 {
+            encoding::message::merge_repeated(wire_type, &mut self.items, buf, ctx)
+        } else {
+            encoding::skip_field(wire_type, tag, buf, ctx)
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoding::message::encoded_len_repeated(1, &self.items)
+    }
+
+    fn clear(&mut self) {
+        self.items.clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header, test::TestRequest};
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct MyObjectBatch {
+        #[prost(message, repeated, tag = "1")]
+        pub items: Vec<MyObject>,
+    }
+
+    #[actix_web::test]
+    async fn test_batch() {
+        let items = vec![MyObject { number: 1 }, MyObject { number: 2 }];
+        let body = MyObjectBatch {
+            items: items.clone(),
+        }
+        .encode_to_vec();
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .set_payload(body)
+            .to_http_parts();
+        let batch = ProtoBufBatch::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+
+        assert_eq!(batch.items(), &items[..]);
+    }
+
+    #[actix_web::test]
+    async fn test_empty_batch() {
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .to_http_parts();
+        let batch = ProtoBufBatch::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+
+        assert!(batch.items().is_empty());
+    }
+}
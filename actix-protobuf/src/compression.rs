@@ -0,0 +1,278 @@
+//! Compression hints for Protobuf responses.
+
+use std::fmt;
+#[cfg(feature = "compression")]
+use std::rc::Rc;
+
+#[cfg(feature = "compression")]
+use actix_http::encoding::Encoder;
+use actix_web::HttpResponse;
+#[cfg(feature = "compression")]
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{AcceptEncoding, ContentEncoding, Header as _, Preference, Quality},
+    Error,
+};
+#[cfg(feature = "compression")]
+use futures_util::future::{self, LocalBoxFuture};
+use prost::Message;
+
+use crate::ProtoBuf;
+
+/// Compression algorithm preferred for a response.
+///
+/// See [`ProtoBuf::respond_with_compression_hint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompressionAlgorithm {
+    /// Gzip (`gzip`).
+    Gzip,
+
+    /// Deflate (`deflate`).
+    Deflate,
+
+    /// Brotli (`br`).
+    Brotli,
+
+    /// Zstandard (`zstd`).
+    Zstd,
+}
+
+impl CompressionAlgorithm {
+    /// Returns the content coding token for this algorithm, as used in the `Accept-Encoding` and
+    /// `Content-Encoding` headers.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompressionAlgorithm::Gzip => "gzip",
+            CompressionAlgorithm::Deflate => "deflate",
+            CompressionAlgorithm::Brotli => "br",
+            CompressionAlgorithm::Zstd => "zstd",
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+impl From<CompressionAlgorithm> for ContentEncoding {
+    fn from(algorithm: CompressionAlgorithm) -> Self {
+        match algorithm {
+            CompressionAlgorithm::Gzip => ContentEncoding::Gzip,
+            CompressionAlgorithm::Deflate => ContentEncoding::Deflate,
+            CompressionAlgorithm::Brotli => ContentEncoding::Brotli,
+            CompressionAlgorithm::Zstd => ContentEncoding::Zstd,
+        }
+    }
+}
+
+impl fmt::Display for CompressionAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<T: Message> ProtoBuf<T> {
+    /// Responds with the encoded message, attaching a hint about which compression algorithm to
+    /// prefer for it.
+    ///
+    /// The hint is stored in the response extensions and applied by the [`CompressionHint`]
+    /// middleware, which compresses the body with the hinted algorithm if the client accepts it.
+    /// Without that middleware, the hint has no effect; Actix Web's `Compress` middleware chooses
+    /// the algorithm solely from the request's `Accept-Encoding` header.
+    pub fn respond_with_compression_hint(self, algorithm: CompressionAlgorithm) -> HttpResponse {
+        let mut res = self.into_response();
+        res.extensions_mut().insert(algorithm);
+        res
+    }
+}
+
+/// Middleware compressing responses with the algorithm hinted by
+/// [`ProtoBuf::respond_with_compression_hint`].
+///
+/// Responses carrying a [`CompressionAlgorithm`] hint are compressed with that algorithm and given
+/// a matching `Content-Encoding` header, as long as the request's `Accept-Encoding` header accepts
+/// it. Other responses are passed through unchanged. Register Actix Web's `Compress` middleware
+/// *after* this one to compress them as well; it leaves responses that already have a
+/// `Content-Encoding` header alone.
+///
+/// Only the algorithms enabled through Actix Web's `compress-*` crate features can be applied;
+/// `gzip` and `deflate` are always available with the `compression` feature. Hints for other
+/// algorithms are ignored.
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::{CompressionAlgorithm, CompressionHint, ProtoBuf};
+/// use actix_web::{web, App, HttpResponse};
+///
+/// #[derive(Clone, PartialEq, Eq, prost::Message)]
+/// pub struct Report {
+///     #[prost(bytes = "vec", tag = "1")]
+///     pub data: Vec<u8>,
+/// }
+///
+/// async fn report() -> HttpResponse {
+///     ProtoBuf(Report { data: vec![0; 4096] })
+///         .respond_with_compression_hint(CompressionAlgorithm::Gzip)
+/// }
+///
+/// let app = App::new()
+///     .wrap(CompressionHint)
+///     .route("/report", web::get().to(report));
+/// ```
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompressionHint;
+
+#[cfg(feature = "compression")]
+impl<S, B> Transform<S, ServiceRequest> for CompressionHint
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<Encoder<B>, B>>;
+    type Error = Error;
+    type Transform = CompressionHintMiddleware<S>;
+    type InitError = ();
+    type Future = future::Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        future::ok(CompressionHintMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+#[cfg(feature = "compression")]
+#[doc(hidden)]
+pub struct CompressionHintMiddleware<S> {
+    service: Rc<S>,
+}
+
+#[cfg(feature = "compression")]
+impl<S, B> Service<ServiceRequest> for CompressionHintMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<Encoder<B>, B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let accept_encoding = AcceptEncoding::parse(&req).ok();
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+
+            let hint = res
+                .response()
+                .extensions()
+                .get::<CompressionAlgorithm>()
+                .copied()
+                .filter(|&algorithm| accepts(accept_encoding.as_ref(), algorithm));
+
+            Ok(match hint {
+                Some(algorithm) => res.map_body(move |head, body| {
+                    EitherBody::left(Encoder::response(algorithm.into(), head, body))
+                }),
+                None => res.map_into_right_body(),
+            })
+        })
+    }
+}
+
+/// Returns whether `accept_encoding` accepts the content coding of `algorithm`.
+#[cfg(feature = "compression")]
+fn accepts(accept_encoding: Option<&AcceptEncoding>, algorithm: CompressionAlgorithm) -> bool {
+    let accept_encoding = match accept_encoding {
+        Some(accept_encoding) => accept_encoding,
+        None => return false,
+    };
+
+    accept_encoding.0.iter().any(|item| {
+        let matches = match &item.item {
+            Preference::Any => true,
+            Preference::Specific(encoding) => encoding
+                .to_string()
+                .eq_ignore_ascii_case(algorithm.as_str()),
+        };
+
+        matches && item.quality >= Quality::MIN
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{body::MessageBody as _, http::header};
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+    }
+
+    #[test]
+    fn test_respond_with_compression_hint() {
+        let res = ProtoBuf(MyObject { number: 9 })
+            .respond_with_compression_hint(CompressionAlgorithm::Brotli);
+
+        assert_eq!(
+            res.extensions().get::<CompressionAlgorithm>(),
+            Some(&CompressionAlgorithm::Brotli)
+        );
+        assert!(!res.headers().contains_key(header::CONTENT_ENCODING));
+
+        let body = res.into_body().try_into_bytes().unwrap();
+        assert_eq!(MyObject::decode(body).unwrap(), MyObject { number: 9 });
+    }
+
+    #[cfg(feature = "compression")]
+    #[actix_web::test]
+    async fn test_compression_hint_middleware() {
+        use std::io::Read as _;
+
+        use actix_web::{test, web, App};
+
+        let srv = test::init_service(App::new().wrap(CompressionHint).route(
+            "/",
+            web::get().to(|| async {
+                ProtoBuf(MyObject { number: 9 })
+                    .respond_with_compression_hint(CompressionAlgorithm::Gzip)
+            }),
+        ))
+        .await;
+
+        let req = test::TestRequest::with_uri("/")
+            .insert_header((header::ACCEPT_ENCODING, "br, gzip;q=0.5"))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+
+        let body = test::read_body(res).await;
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(&body[..])
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(
+            MyObject::decode(&decoded[..]).unwrap(),
+            MyObject { number: 9 }
+        );
+
+        for accept_encoding in ["br", "gzip;q=0"] {
+            let req = test::TestRequest::with_uri("/")
+                .insert_header((header::ACCEPT_ENCODING, accept_encoding))
+                .to_request();
+            let res = test::call_service(&srv, req).await;
+            assert!(!res.headers().contains_key(header::CONTENT_ENCODING));
+
+            let body = test::read_body(res).await;
+            assert_eq!(MyObject::decode(body).unwrap(), MyObject { number: 9 });
+        }
+    }
+}
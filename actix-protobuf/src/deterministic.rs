@@ -0,0 +1,230 @@
+//! Deterministic encoding of messages.
+
+use prost::{
+    encoding::{encode_key, encode_varint, WireType},
+    Message,
+};
+use prost_reflect::{DynamicMessage, FieldDescriptor, Kind, ReflectMessage, Value};
+
+use crate::{ProtoBuf, ProtoBufPayloadError};
+
+impl<T: ReflectMessage> ProtoBuf<T> {
+    /// Encodes the message deterministically, e.g. for content-addressed storage or signing.
+    ///
+    /// The message is transcoded to a [`DynamicMessage`] using its descriptor, then encoded with
+    /// fields in tag order and map entries in key order, so that equal messages are encoded to the
+    /// same bytes regardless of how their map fields are generated. The output decodes to the same
+    /// message, but is not necessarily identical to that of [`Message::encode`].
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_protobuf::{ProtoBuf, ProtoBufPayloadError};
+    /// use prost_reflect::ReflectMessage;
+    ///
+    /// fn signed_bytes<T>(msg: &ProtoBuf<T>) -> Result<Vec<u8>, ProtoBufPayloadError>
+    /// where
+    ///     T: ReflectMessage,
+    /// {
+    ///     // equal messages are always signed over the same bytes
+    ///     msg.encode_deterministic()
+    /// }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "deterministic")))]
+    pub fn encode_deterministic(&self) -> Result<Vec<u8>, ProtoBufPayloadError> {
+        let msg = self.0.transcode_to_dynamic();
+        let mut buf = Vec::with_capacity(msg.encoded_len());
+        encode_canonical(&msg, &mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// Encodes `msg` with fields in tag order and map entries in key order, recursively.
+fn encode_canonical(msg: &DynamicMessage, buf: &mut Vec<u8>) -> Result<(), ProtoBufPayloadError> {
+    let desc = msg.descriptor();
+    let mut fields = desc.fields().collect::<Vec<_>>();
+    fields.sort_by_key(FieldDescriptor::number);
+
+    for field in fields.iter().filter(|field| msg.has_field(field)) {
+        let value = msg.get_field(field);
+
+        match (&*value, field.kind()) {
+            (Value::Map(map), Kind::Message(entry)) => {
+                let mut entries = map.iter().collect::<Vec<_>>();
+                entries.sort_by_key(|&(key, _)| key);
+
+                for (key, value) in entries {
+                    let mut entry_buf = Vec::new();
+                    let key_field = entry.map_entry_key_field();
+                    encode_field(&key_field, &Value::from(key.clone()), &mut entry_buf)?;
+                    encode_field(&entry.map_entry_value_field(), value, &mut entry_buf)?;
+                    encode_nested(field.number(), &entry_buf, buf);
+                }
+            }
+            (Value::List(items), Kind::Message(_)) if !field.is_group() => {
+                for item in items {
+                    encode_field(field, item, buf)?;
+                }
+            }
+            _ => encode_field(field, &value, buf)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes a single value of `field`, which must not be a map.
+fn encode_field(
+    field: &FieldDescriptor,
+    value: &Value,
+    buf: &mut Vec<u8>,
+) -> Result<(), ProtoBufPayloadError> {
+    match value {
+        Value::Message(nested) if !field.is_group() => {
+            let mut nested_buf = Vec::with_capacity(nested.encoded_len());
+            encode_canonical(nested, &mut nested_buf)?;
+            encode_nested(field.number(), &nested_buf, buf);
+            Ok(())
+        }
+
+        // scalars are deterministic, so encode them through a message holding only this field
+        _ => {
+            let mut single = DynamicMessage::new(field.parent_message().clone());
+            single.set_field(field, value.clone());
+            single.encode(buf).map_err(ProtoBufPayloadError::Serialize)
+        }
+    }
+}
+
+/// Encodes already encoded message bytes as a length-delimited field.
+fn encode_nested(number: u32, bytes: &[u8], buf: &mut Vec<u8>) {
+    encode_key(number, WireType::LengthDelimited, buf);
+    encode_varint(bytes.len() as u64, buf);
+    buf.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use prost_reflect::{DescriptorPool, MessageDescriptor};
+    use prost_types::{
+        field_descriptor_proto::{Label, Type},
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+        MessageOptions,
+    };
+
+    use super::*;
+
+    fn field(name: &str, number: i32, label: Label, ty: Type) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            number: Some(number),
+            label: Some(label as i32),
+            r#type: Some(ty as i32),
+            ..Default::default()
+        }
+    }
+
+    fn message_field(name: &str, number: i32, label: Label, ty: &str) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            type_name: Some(ty.to_owned()),
+            ..field(name, number, label, Type::Message)
+        }
+    }
+
+    /// Pool containing `example.Labels`, with a `map<string, int32>` field and a nested message.
+    fn descriptor_pool() -> DescriptorPool {
+        let entry = DescriptorProto {
+            name: Some("LabelsEntry".to_owned()),
+            field: vec![
+                field("key", 1, Label::Optional, Type::String),
+                field("value", 2, Label::Optional, Type::Int32),
+            ],
+            options: Some(MessageOptions {
+                map_entry: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let labels = DescriptorProto {
+            name: Some("Labels".to_owned()),
+            field: vec![
+                message_field("labels", 1, Label::Repeated, ".example.Labels.LabelsEntry"),
+                field("name", 2, Label::Optional, Type::String),
+                message_field("children", 3, Label::Repeated, ".example.Labels"),
+            ],
+            nested_type: vec![entry],
+            ..Default::default()
+        };
+
+        let file = FileDescriptorProto {
+            name: Some("example.proto".to_owned()),
+            package: Some("example".to_owned()),
+            message_type: vec![labels],
+            syntax: Some("proto3".to_owned()),
+            ..Default::default()
+        };
+
+        DescriptorPool::from_file_descriptor_set(FileDescriptorSet { file: vec![file] }).unwrap()
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Labels {
+        #[prost(map = "string, int32", tag = "1")]
+        pub labels: HashMap<String, i32>,
+        #[prost(string, tag = "2")]
+        pub name: String,
+        #[prost(message, repeated, tag = "3")]
+        pub children: Vec<Labels>,
+    }
+
+    impl ReflectMessage for Labels {
+        fn descriptor(&self) -> MessageDescriptor {
+            descriptor_pool()
+                .get_message_by_name("example.Labels")
+                .unwrap()
+        }
+    }
+
+    fn labels(keys: &[&str], children: Vec<Labels>) -> Labels {
+        Labels {
+            labels: keys
+                .iter()
+                .enumerate()
+                .map(|(i, key)| ((*key).to_owned(), i as i32))
+                .collect(),
+            name: "test".to_owned(),
+            children,
+        }
+    }
+
+    #[test]
+    fn test_encode_deterministic() {
+        let keys = ["a", "b", "c", "d", "e", "f", "g", "h"];
+        let msg = ProtoBuf(labels(&keys, vec![labels(&keys, vec![])]));
+        let encoded = msg.encode_deterministic().unwrap();
+
+        // equal messages whose maps were built in different orders
+        for _ in 0..8 {
+            let mut other = labels(&[], vec![labels(&[], vec![])]);
+            for (i, key) in keys.iter().enumerate().rev() {
+                other.labels.insert((*key).to_owned(), i as i32);
+                other.children[0].labels.insert((*key).to_owned(), i as i32);
+            }
+            assert_eq!(ProtoBuf(other).encode_deterministic().unwrap(), encoded);
+        }
+
+        assert_eq!(Labels::decode(&encoded[..]).unwrap(), msg.0);
+    }
+
+    #[test]
+    fn test_map_entries_sorted() {
+        let msg = ProtoBuf(labels(&["b", "a"], vec![]));
+        let encoded = msg.encode_deterministic().unwrap();
+
+        let a = encoded.windows(3).position(|w| w == b"\x0a\x01a").unwrap();
+        let b = encoded.windows(3).position(|w| w == b"\x0a\x01b").unwrap();
+        assert!(a < b);
+    }
+}
@@ -0,0 +1,98 @@
+//! Decoding of proto2 extension fields.
+
+use actix_web::web::Bytes;
+use prost::Message;
+
+use crate::{ProtoBuf, ProtoBufPayloadError};
+
+impl<T: Message + Default> ProtoBuf<T> {
+    /// Decodes a message along with its proto2 extension fields.
+    ///
+    /// `prost` does not support extensions; when decoding `T`, extension fields are treated as
+    /// unknown fields and discarded. This decodes `bytes` a second time into `E`, a message that
+    /// declares the extension fields (with the same tags) as regular fields, so both the message
+    /// and its extensions are available. Fields of `T` are in turn skipped when decoding `E`.
+    ///
+    /// `bytes` is only reference counted, not copied, between the two passes.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_protobuf::ProtoBuf;
+    /// use actix_web::web::Bytes;
+    /// use prost::Message as _;
+    ///
+    /// // message Event {
+    /// //     optional int32 id = 1;
+    /// //     extensions 100 to 199;
+    /// // }
+    /// #[derive(Clone, PartialEq, prost::Message)]
+    /// pub struct Event {
+    ///     #[prost(int32, optional, tag = "1")]
+    ///     pub id: Option<i32>,
+    /// }
+    ///
+    /// // extend Event {
+    /// //     optional string source = 100;
+    /// // }
+    /// #[derive(Clone, PartialEq, prost::Message)]
+    /// pub struct EventExtensions {
+    ///     #[prost(string, optional, tag = "100")]
+    ///     pub source: Option<String>,
+    /// }
+    /// # let mut buf = Event { id: Some(1) }.encode_to_vec();
+    /// # EventExtensions { source: Some("sensor".to_owned()) }.encode(&mut buf).unwrap();
+    /// # let bytes = Bytes::from(buf);
+    ///
+    /// let (event, ext) = ProtoBuf::<Event>::decode_with_extensions::<EventExtensions>(bytes)?;
+    /// assert_eq!(event.id, Some(1));
+    /// assert_eq!(ext.source.as_deref(), Some("sensor"));
+    /// # Ok::<_, actix_protobuf::ProtoBufPayloadError>(())
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "proto2-extensions")))]
+    pub fn decode_with_extensions<E>(bytes: Bytes) -> Result<(T, E), ProtoBufPayloadError>
+    where
+        E: Message + Default,
+    {
+        let msg = T::decode(bytes.clone())?;
+        let ext = E::decode(bytes)?;
+        Ok((msg, ext))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, optional, tag = "1")]
+        pub number: Option<i32>,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObjectExtensions {
+        #[prost(string, optional, tag = "100")]
+        pub label: Option<String>,
+    }
+
+    #[test]
+    fn test_decode_with_extensions() {
+        let mut buf = MyObject { number: Some(9) }.encode_to_vec();
+        MyObjectExtensions {
+            label: Some("test".to_owned()),
+        }
+        .encode(&mut buf)
+        .unwrap();
+
+        let (msg, ext) =
+            ProtoBuf::<MyObject>::decode_with_extensions::<MyObjectExtensions>(Bytes::from(buf))
+                .unwrap();
+        assert_eq!(msg.number, Some(9));
+        assert_eq!(ext.label.as_deref(), Some("test"));
+
+        let err = ProtoBuf::<MyObject>::decode_with_extensions::<MyObjectExtensions>(
+            Bytes::from_static(&[0xff]),
+        );
+        assert!(matches!(err, Err(ProtoBufPayloadError::Deserialize(_))));
+    }
+}
@@ -0,0 +1,105 @@
+//! Persistence of Protobuf messages in length-delimited files.
+
+use std::{convert::TryFrom, io, path::Path};
+
+use prost::Message;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt as _};
+
+use crate::{ProtoBuf, ProtoBufPayloadError};
+
+impl<T: Message> ProtoBuf<T> {
+    /// Appends the message to the file at `path` as a length-delimited frame, creating the file if
+    /// it does not exist.
+    ///
+    /// Each frame consists of the length of the encoded message as a 4-byte big-endian integer,
+    /// followed by the encoded message. This format is commonly used to store sequences of
+    /// messages, such as datasets or event logs.
+    ///
+    /// Note that frames written by concurrent calls for the same file may be interleaved.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use actix_protobuf::{ProtoBuf, ProtoBufPayloadError};
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # struct Event {
+    /// #     #[prost(int32, tag = "1")]
+    /// #     id: i32,
+    /// # }
+    /// async fn record(event: ProtoBuf<Event>) -> Result<(), ProtoBufPayloadError> {
+    ///     event.write_length_delimited_to_file("events.bin").await
+    /// }
+    /// ```
+    pub async fn write_length_delimited_to_file(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), ProtoBufPayloadError> {
+        let len = u32::try_from(self.0.encoded_len()).map_err(|_| {
+            ProtoBufPayloadError::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "message is too large for a 4-byte length prefix",
+            ))
+        })?;
+
+        let mut frame = Vec::with_capacity(4 + len as usize);
+        frame.extend_from_slice(&len.to_be_bytes());
+        self.0
+            .encode(&mut frame)
+            .map_err(ProtoBufPayloadError::Serialize)?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(ProtoBufPayloadError::Io)?;
+
+        file.write_all(&frame)
+            .await
+            .map_err(ProtoBufPayloadError::Io)?;
+        file.flush().await.map_err(ProtoBufPayloadError::Io)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::TryInto as _, env, fs};
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+    }
+
+    #[actix_web::test]
+    async fn test_write_length_delimited_to_file() {
+        let path = env::temp_dir().join(format!("actix-protobuf-{}.bin", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        ProtoBuf(MyObject { number: 1 })
+            .write_length_delimited_to_file(&path)
+            .await
+            .unwrap();
+        ProtoBuf(MyObject { number: 300 })
+            .write_length_delimited_to_file(&path)
+            .await
+            .unwrap();
+
+        let contents = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let mut rest = &contents[..];
+        let mut messages = Vec::new();
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            messages.push(MyObject::decode(&rest[4..4 + len]).unwrap());
+            rest = &rest[4 + len..];
+        }
+
+        assert_eq!(
+            messages,
+            vec![MyObject { number: 1 }, MyObject { number: 300 }]
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! gRPC status reporting for Protobuf responses.
+
+use std::fmt;
+
+use actix_web::{
+    http::header::{HeaderName, HeaderValue},
+    HttpResponse,
+};
+use prost::Message;
+
+use crate::ProtoBuf;
+
+/// Name of the header carrying the gRPC status code.
+pub const GRPC_STATUS: &str = "grpc-status";
+
+/// gRPC status code.
+///
+/// See [`ProtoBuf::respond_with_grpc_status`] and the [gRPC status codes].
+///
+/// [gRPC status codes]: https://grpc.github.io/grpc/core/md_doc_statuscodes.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GrpcStatus {
+    /// Not an error; returned on success.
+    Ok = 0,
+    /// The operation was cancelled, typically by the caller.
+    Cancelled = 1,
+    /// Unknown error, e.g. an error raised by an API that does not return enough information.
+    Unknown = 2,
+    /// The client specified an invalid argument, regardless of the state of the system.
+    InvalidArgument = 3,
+    /// The deadline expired before the operation could complete.
+    DeadlineExceeded = 4,
+    /// Some requested entity was not found.
+    NotFound = 5,
+    /// The entity that a client attempted to create already exists.
+    AlreadyExists = 6,
+    /// The caller does not have permission to execute the specified operation.
+    PermissionDenied = 7,
+    /// Some resource has been exhausted, e.g. a per-user quota.
+    ResourceExhausted = 8,
+    /// The system is not in a state required for the operation's execution.
+    FailedPrecondition = 9,
+    /// The operation was aborted, typically due to a concurrency issue.
+    Aborted = 10,
+    /// The operation was attempted past the valid range.
+    OutOfRange = 11,
+    /// The operation is not implemented or not supported.
+    Unimplemented = 12,
+    /// Internal error; some invariant expected by the underlying system has been broken.
+    Internal = 13,
+    /// The service is currently unavailable; the client may retry.
+    Unavailable = 14,
+    /// Unrecoverable data loss or corruption.
+    DataLoss = 15,
+    /// The request does not have valid authentication credentials.
+    Unauthenticated = 16,
+}
+
+impl GrpcStatus {
+    /// Returns the numeric code of this status.
+    pub fn code(&self) -> u32 {
+        *self as u32
+    }
+}
+
+impl fmt::Display for GrpcStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl<T: Message> ProtoBuf<T> {
+    /// Responds with the encoded message and a `Grpc-Status` header carrying `status`.
+    ///
+    /// gRPC sends the final status in an HTTP/2 trailer. Actix Web does not provide an API for
+    /// sending trailers, so the status is sent as a regular header instead. This is not a valid
+    /// gRPC response: it is intended for clients that read the status from the headers, and
+    /// clients that only look for the status in trailers will not see it.
+    pub fn respond_with_grpc_status(self, status: GrpcStatus) -> HttpResponse {
+        let mut res = self.into_response();
+
+        res.headers_mut().insert(
+            HeaderName::from_static(GRPC_STATUS),
+            HeaderValue::from(status.code()),
+        );
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{body::MessageBody as _, http::header::TRAILER};
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+    }
+
+    #[test]
+    fn test_respond_with_grpc_status() {
+        let res = ProtoBuf(MyObject { number: 9 }).respond_with_grpc_status(GrpcStatus::NotFound);
+
+        assert!(res.headers().get(TRAILER).is_none());
+        assert_eq!(res.headers().get(GRPC_STATUS).unwrap(), "5");
+
+        let body = res.into_body().try_into_bytes().unwrap();
+        assert_eq!(MyObject::decode(body).unwrap(), MyObject { number: 9 });
+    }
+}
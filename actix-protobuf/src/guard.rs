@@ -0,0 +1,207 @@
+//! Guards for routing requests with a Protobuf payload.
+
+use actix_web::{
+    guard::{Guard, GuardContext},
+    http::header::CONTENT_TYPE,
+};
+
+use crate::{is_accepted_content_type, ProtoBufConfig, GRPC_CONTENT_TYPES};
+
+/// Creates a guard that matches requests with a Protobuf content type.
+///
+/// See [`ProtoBufGuard`] for details.
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::guard;
+/// use actix_web::{web, App, HttpResponse};
+///
+/// let app = App::new().service(
+///     web::resource("/")
+///         .route(web::post().guard(guard::protobuf()).to(|| HttpResponse::Ok()))
+///         .route(web::post().to(|| HttpResponse::UnsupportedMediaType())),
+/// );
+/// ```
+pub fn protobuf() -> ProtoBufGuard {
+    ProtoBufGuard::new()
+}
+
+/// Guard that matches requests with a Protobuf content type.
+///
+/// Matches requests with a content type accepted by the [`ProtoBufConfig`] registered as app
+/// data, including gRPC content types if [gRPC framing](ProtoBufConfig::grpc_framing) is enabled.
+/// Without a config, requests with `Content-Type: application/protobuf` or
+/// `application/vnd.google.protobuf` match. Further content types can be accepted using
+/// [`content_type`](Self::content_type). Parameters of the content type, such as `charset`, are
+/// ignored and the comparison is case-insensitive. Requests without a content type never match,
+/// even if the config [allows them](ProtoBufConfig::allow_content_type_wildcard).
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::{ProtoBuf, ProtoBufGuard};
+/// use actix_web::{web, App, HttpResponse};
+///
+/// #[derive(Clone, PartialEq, prost::Message, serde::Deserialize)]
+/// struct MyObject {
+///     #[prost(int32, tag = "1")]
+///     number: i32,
+/// }
+///
+/// async fn protobuf_handler(msg: ProtoBuf<MyObject>) -> HttpResponse {
+///     HttpResponse::Ok().body(format!("protobuf: {}", msg.number))
+/// }
+///
+/// async fn json_handler(msg: web::Json<MyObject>) -> HttpResponse {
+///     HttpResponse::Ok().body(format!("json: {}", msg.number))
+/// }
+///
+/// let app = App::new().service(
+///     web::resource("/")
+///         .route(web::post().guard(ProtoBufGuard::default()).to(protobuf_handler))
+///         .route(web::post().to(json_handler)),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProtoBufGuard {
+    content_types: Vec<String>,
+}
+
+impl ProtoBufGuard {
+    /// Creates a guard matching the content types accepted by the registered [`ProtoBufConfig`].
+    pub fn new() -> Self {
+        ProtoBufGuard {
+            content_types: Vec::new(),
+        }
+    }
+
+    /// Additionally matches requests with the given content type, e.g. `application/x-protobuf`.
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_types.push(content_type.into());
+        self
+    }
+}
+
+impl Default for ProtoBufGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Guard for ProtoBufGuard {
+    fn check(&self, ctx: &GuardContext<'_>) -> bool {
+        let content_type = match ctx.head().headers().get(CONTENT_TYPE) {
+            Some(content_type) => content_type,
+            None => return false,
+        };
+
+        let essence = match content_type.to_str() {
+            Ok(content_type) => content_type.split(';').next().unwrap_or_default().trim(),
+            Err(_) => return false,
+        };
+
+        if self
+            .content_types
+            .iter()
+            .any(|accepted| accepted.eq_ignore_ascii_case(essence))
+        {
+            return true;
+        }
+
+        let config = ctx.app_data::<ProtoBufConfig>();
+        let grpc = config.is_some_and(|c| c.grpc_framing)
+            && GRPC_CONTENT_TYPES
+                .iter()
+                .any(|accepted| accepted.eq_ignore_ascii_case(essence));
+
+        grpc || is_accepted_content_type(config, essence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header, test::TestRequest};
+
+    use super::*;
+
+    #[test]
+    fn test_guard() {
+        let guard = ProtoBufGuard::default();
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .to_srv_request();
+        assert!(guard.check(&req.guard_ctx()));
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "Application/Protobuf; charset=utf-8"))
+            .to_srv_request();
+        assert!(guard.check(&req.guard_ctx()));
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/vnd.google.protobuf"))
+            .to_srv_request();
+        assert!(guard.check(&req.guard_ctx()));
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .to_srv_request();
+        assert!(!guard.check(&req.guard_ctx()));
+
+        let req = TestRequest::default().to_srv_request();
+        assert!(!guard.check(&req.guard_ctx()));
+    }
+
+    #[test]
+    fn test_guard_content_type() {
+        let guard = ProtoBufGuard::default().content_type("application/x-protobuf");
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/x-protobuf"))
+            .to_srv_request();
+        assert!(guard.check(&req.guard_ctx()));
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .to_srv_request();
+        assert!(guard.check(&req.guard_ctx()));
+    }
+
+    #[test]
+    fn test_guard_config() {
+        let guard = protobuf();
+
+        let mut config = ProtoBufConfig::default();
+        config
+            .accept_content_types(["application/x-protobuf"])
+            .grpc_framing(true);
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/x-protobuf"))
+            .app_data(config)
+            .to_srv_request();
+        assert!(guard.check(&req.guard_ctx()));
+
+        let mut config = ProtoBufConfig::default();
+        config.grpc_framing(true);
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/grpc"))
+            .app_data(config)
+            .to_srv_request();
+        assert!(guard.check(&req.guard_ctx()));
+
+        let mut config = ProtoBufConfig::default();
+        config.accept_content_types(["application/x-protobuf"]);
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .app_data(config)
+            .to_srv_request();
+        assert!(!guard.check(&req.guard_ctx()));
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/grpc"))
+            .to_srv_request();
+        assert!(!guard.check(&req.guard_ctx()));
+    }
+}
@@ -0,0 +1,348 @@
+//! Extractor decoding messages using a descriptor pool that can be replaced at runtime.
+
+use std::{fmt, marker::PhantomData, ops::Deref, sync::Arc};
+
+use actix_web::{
+    dev::Payload, error::ErrorInternalServerError, web::BytesMut, Error, FromRequest, HttpMessage,
+    HttpRequest,
+};
+use arc_swap::ArcSwap;
+use futures_util::{
+    future::{self, LocalBoxFuture},
+    StreamExt as _,
+};
+use prost::DecodeError;
+use prost_reflect::{DescriptorPool, DynamicMessage, ReflectMessage};
+
+use crate::{extend_within_limit, is_decodable_content_type, ProtoBufConfig, ProtoBufPayloadError};
+
+/// Protobuf extractor decoding messages with the latest version of a hot-reloadable schema.
+///
+/// The schema is read from a shared `Arc<ArcSwap<DescriptorPool>>` registered as app data. The
+/// body is decoded into a [`DynamicMessage`] using the descriptor of the message with the same
+/// full name as `T` in the current pool. Storing a new pool, e.g. with [`ArcSwap::store`], affects
+/// all subsequent requests without restarting the server; requests that are in flight keep
+/// using the pool they started with.
+///
+/// Fields added to the schema after `T` was compiled are therefore decoded as known fields and
+/// can be accessed by name. Use [`transcode_to`](Self::transcode_to) to convert the message into
+/// the compiled type.
+///
+/// Requests are rejected with `500 Internal Server Error` if no pool is registered or the pool
+/// has no message named like `T`. The accepted content types and the
+/// [payload limit](ProtoBufConfig::limit) are configured as for [`ProtoBuf`](crate::ProtoBuf).
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+///
+/// use actix_protobuf::HotReloadProtoBuf;
+/// use actix_web::{web, App};
+/// use arc_swap::ArcSwap;
+/// use prost_reflect::{DescriptorPool, MessageDescriptor, ReflectMessage};
+/// use prost_types::{
+///     field_descriptor_proto::Type, DescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+///     FileDescriptorSet,
+/// };
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// pub struct Event {
+///     #[prost(int32, tag = "1")]
+///     pub id: i32,
+/// }
+///
+/// impl ReflectMessage for Event {
+///     fn descriptor(&self) -> MessageDescriptor {
+///         load_pool(&[]).get_message_by_name("example.Event").unwrap()
+///     }
+/// }
+///
+/// /// Builds the schema of `example.Event`, with the given fields added since `id`.
+/// fn load_pool(added_fields: &[(&str, i32, Type)]) -> DescriptorPool {
+///     let mut fields = vec![("id", 1, Type::Int32)];
+///     fields.extend_from_slice(added_fields);
+///
+///     let event = DescriptorProto {
+///         name: Some("Event".to_owned()),
+///         field: fields
+///             .into_iter()
+///             .map(|(name, number, ty)| FieldDescriptorProto {
+///                 name: Some(name.to_owned()),
+///                 number: Some(number),
+///                 r#type: Some(ty as i32),
+///                 ..Default::default()
+///             })
+///             .collect(),
+///         ..Default::default()
+///     };
+///
+///     let file = FileDescriptorProto {
+///         name: Some("event.proto".to_owned()),
+///         package: Some("example".to_owned()),
+///         message_type: vec![event],
+///         syntax: Some("proto3".to_owned()),
+///         ..Default::default()
+///     };
+///
+///     DescriptorPool::from_file_descriptor_set(FileDescriptorSet { file: vec![file] }).unwrap()
+/// }
+///
+/// async fn index(event: HotReloadProtoBuf<Event>) -> String {
+///     match event.get_field_by_name("source") {
+///         Some(source) => format!("Event from {:?}", source),
+///         None => "Event from unknown source".to_owned(),
+///     }
+/// }
+///
+/// let pool = Arc::new(ArcSwap::from_pointee(load_pool(&[])));
+///
+/// let app = App::new()
+///     .app_data(Arc::clone(&pool))
+///     .route("/", web::post().to(index));
+///
+/// // later, e.g. when a new schema has been deployed
+/// pool.store(Arc::new(load_pool(&[("source", 2, Type::String)])));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "hot-reload")))]
+pub struct HotReloadProtoBuf<T> {
+    message: DynamicMessage,
+    _type: PhantomData<T>,
+}
+
+impl<T> HotReloadProtoBuf<T> {
+    /// Unwraps into the decoded message.
+    pub fn into_inner(self) -> DynamicMessage {
+        self.message
+    }
+
+    /// Converts the message into the compiled message type.
+    ///
+    /// Fields that are unknown to `T` are discarded.
+    pub fn transcode_to(&self) -> Result<T, DecodeError>
+    where
+        T: ReflectMessage + Default,
+    {
+        self.message.transcode_to()
+    }
+}
+
+impl<T> Deref for HotReloadProtoBuf<T> {
+    type Target = DynamicMessage;
+
+    fn deref(&self) -> &DynamicMessage {
+        &self.message
+    }
+}
+
+impl<T> fmt::Debug for HotReloadProtoBuf<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HotReloadProtoBuf: {:?}", self.message)
+    }
+}
+
+impl<T> FromRequest for HotReloadProtoBuf<T>
+where
+    T: ReflectMessage + Default + 'static,
+{
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let pool = match req.app_data::<Arc<ArcSwap<DescriptorPool>>>() {
+            Some(pool) => pool.load_full(),
+            None => {
+                return Box::pin(future::err(ErrorInternalServerError(
+                    "Descriptor pool is not configured",
+                )))
+            }
+        };
+
+        let name = T::default().descriptor().full_name().to_owned();
+        let descriptor = match pool.get_message_by_name(&name) {
+            Some(descriptor) => descriptor,
+            None => {
+                return Box::pin(future::err(ErrorInternalServerError(format!(
+                    "Descriptor pool has no message named {}",
+                    name
+                ))))
+            }
+        };
+
+        let config = req.app_data::<ProtoBufConfig>();
+
+        if !is_decodable_content_type(config, req.content_type()) {
+            return Box::pin(future::err(ProtoBufPayloadError::ContentType.into()));
+        }
+
+        let limit = config.map_or(262_144, |c| c.limit);
+        let mut payload = payload.take();
+
+        Box::pin(async move {
+            let mut body = BytesMut::with_capacity(8192);
+
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk.map_err(ProtoBufPayloadError::Payload)?;
+                extend_within_limit(&mut body, &chunk, limit)?;
+            }
+
+            let message = DynamicMessage::decode(descriptor, body.freeze())
+                .map_err(ProtoBufPayloadError::Deserialize)?;
+
+            Ok(HotReloadProtoBuf {
+                message,
+                _type: PhantomData,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header, test::TestRequest};
+    use prost::Message;
+    use prost_reflect::{MessageDescriptor, Value};
+    use prost_types::{
+        field_descriptor_proto::{Label, Type},
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+    };
+
+    use super::*;
+
+    fn field(name: &str, number: i32, ty: Type) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            number: Some(number),
+            label: Some(Label::Optional as i32),
+            r#type: Some(ty as i32),
+            ..Default::default()
+        }
+    }
+
+    /// Pool containing `example.MyObject`, with a `name` field added in version 2.
+    fn descriptor_pool(version: u32) -> DescriptorPool {
+        let mut fields = vec![field("number", 1, Type::Int32)];
+        if version >= 2 {
+            fields.push(field("name", 2, Type::String));
+        }
+
+        let file = FileDescriptorProto {
+            name: Some("example.proto".to_owned()),
+            package: Some("example".to_owned()),
+            message_type: vec![DescriptorProto {
+                name: Some("MyObject".to_owned()),
+                field: fields,
+                ..Default::default()
+            }],
+            syntax: Some("proto3".to_owned()),
+            ..Default::default()
+        };
+
+        DescriptorPool::from_file_descriptor_set(FileDescriptorSet { file: vec![file] }).unwrap()
+    }
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+    }
+
+    impl ReflectMessage for MyObject {
+        fn descriptor(&self) -> MessageDescriptor {
+            descriptor_pool(1)
+                .get_message_by_name("example.MyObject")
+                .unwrap()
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObjectV2 {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+        #[prost(string, tag = "2")]
+        pub name: String,
+    }
+
+    async fn extract(pool: &Arc<ArcSwap<DescriptorPool>>) -> HotReloadProtoBuf<MyObject> {
+        let body = MyObjectV2 {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .app_data(Arc::clone(pool))
+            .set_payload(body.encode_to_vec())
+            .to_http_parts();
+
+        HotReloadProtoBuf::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_hot_reload() {
+        let pool = Arc::new(ArcSwap::from_pointee(descriptor_pool(1)));
+
+        let msg = extract(&pool).await;
+        assert_eq!(
+            msg.get_field_by_name("number").unwrap().as_ref(),
+            &Value::I32(9)
+        );
+        assert!(msg.get_field_by_name("name").is_none());
+
+        pool.store(Arc::new(descriptor_pool(2)));
+
+        let msg = extract(&pool).await;
+        assert_eq!(
+            msg.get_field_by_name("name").unwrap().as_ref(),
+            &Value::String("test".to_owned())
+        );
+        assert_eq!(msg.transcode_to().unwrap(), MyObject { number: 9 });
+    }
+
+    #[actix_web::test]
+    async fn test_content_type() {
+        let pool = Arc::new(ArcSwap::from_pointee(descriptor_pool(1)));
+        let body = MyObject { number: 9 }.encode_to_vec();
+
+        let (req, mut pl) = TestRequest::post()
+            .app_data(Arc::clone(&pool))
+            .set_payload(body.clone())
+            .to_http_parts();
+        let res = HotReloadProtoBuf::<MyObject>::from_request(&req, &mut pl).await;
+        assert!(res.is_err());
+
+        let mut config = ProtoBufConfig::default();
+        config.allow_content_type_wildcard(true);
+
+        let (req, mut pl) = TestRequest::post()
+            .app_data(Arc::clone(&pool))
+            .app_data(config)
+            .set_payload(body.clone())
+            .to_http_parts();
+        let res = HotReloadProtoBuf::<MyObject>::from_request(&req, &mut pl).await;
+        assert_eq!(res.unwrap().transcode_to().unwrap(), MyObject { number: 9 });
+
+        let mut config = ProtoBufConfig::default();
+        config.accept_content_types(["application/x-protobuf"]);
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .app_data(Arc::clone(&pool))
+            .app_data(config)
+            .set_payload(body)
+            .to_http_parts();
+        let res = HotReloadProtoBuf::<MyObject>::from_request(&req, &mut pl).await;
+        assert!(res.is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_missing_pool() {
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .to_http_parts();
+
+        let res = HotReloadProtoBuf::<MyObject>::from_request(&req, &mut pl).await;
+        assert!(res.is_err());
+    }
+}
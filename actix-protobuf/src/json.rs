@@ -0,0 +1,598 @@
+//! Protobuf JSON support.
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use actix_web::{
+    body::BoxBody,
+    dev::Payload,
+    error::JsonPayloadError,
+    http::header::{self, Header as _},
+    mime,
+    web::BytesMut,
+    Error, FromRequest, HttpMessage as _, HttpRequest, HttpResponse, Responder,
+};
+use futures_util::{
+    future::{self, LocalBoxFuture},
+    StreamExt as _,
+};
+use prost::Message;
+use prost_reflect::{DeserializeOptions, DynamicMessage, ReflectMessage, SerializeOptions};
+use serde::Serialize;
+
+use crate::ProtoBuf;
+
+impl<T> ProtoBuf<T>
+where
+    T: Message + Default + Serialize,
+{
+    /// Responds with either binary Protobuf or proto-JSON, depending on the `Accept` header of
+    /// the request.
+    ///
+    /// If the client prefers `application/json` (including `application/json+protobuf`) over
+    /// Protobuf, the message is serialized using its [`Serialize`] implementation and sent with
+    /// `Content-Type: application/json`. Otherwise, this is equivalent to the binary
+    /// [`Responder`](actix_web::Responder) implementation.
+    ///
+    /// With the `json-negotiation` crate feature, handlers can return a [`NegotiatedProtoBuf`] to
+    /// respond like this method.
+    ///
+    /// To produce the canonical proto-JSON mapping, `T` should be generated with a crate that
+    /// derives it, such as `pbjson`.
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn respond_as_jsonpb(self, req: &HttpRequest) -> HttpResponse {
+        if !prefers_json(req) {
+            return self.respond_binary(req);
+        }
+
+        match serde_json::to_vec(&self.0) {
+            Ok(body) => HttpResponse::Ok()
+                .content_type(mime::APPLICATION_JSON)
+                .body(body),
+            Err(err) => HttpResponse::from_error(JsonPayloadError::Serialize(err)),
+        }
+    }
+}
+
+/// Responder for either binary Protobuf or proto-JSON, depending on the `Accept` header of the
+/// request.
+///
+/// Behaves like [`ProtoBuf::respond_as_jsonpb`], for use as the return type of handlers. Unlike
+/// the [`ProtoBuf`] responder, `T` must implement [`Serialize`].
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::NegotiatedProtoBuf;
+///
+/// #[derive(Clone, PartialEq, prost::Message, serde::Serialize)]
+/// pub struct User {
+///     #[prost(string, tag = "1")]
+///     pub name: String,
+/// }
+///
+/// async fn index() -> NegotiatedProtoBuf<User> {
+///     NegotiatedProtoBuf(User {
+///         name: "alice".to_owned(),
+///     })
+/// }
+/// ```
+#[cfg(feature = "json-negotiation")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json-negotiation")))]
+pub struct NegotiatedProtoBuf<T: Message>(pub T);
+
+#[cfg(feature = "json-negotiation")]
+impl<T: Message> NegotiatedProtoBuf<T> {
+    /// Unwraps into the inner message.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[cfg(feature = "json-negotiation")]
+impl<T: Message> From<ProtoBuf<T>> for NegotiatedProtoBuf<T> {
+    fn from(msg: ProtoBuf<T>) -> Self {
+        NegotiatedProtoBuf(msg.0)
+    }
+}
+
+#[cfg(feature = "json-negotiation")]
+impl<T> Responder for NegotiatedProtoBuf<T>
+where
+    T: Message + Default + Serialize,
+{
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        ProtoBuf(self.0).respond_as_jsonpb(req)
+    }
+}
+
+/// Extractor and responder for messages in the Protobuf JSON format.
+///
+/// Unlike [`ProtoBuf`], the body is (de)serialized using the [canonical JSON mapping] of
+/// Protobuf, based on the message descriptor of `T`: field names are written in lowerCamelCase,
+/// enums as their names, 64-bit integers as strings and bytes as base64, and well-known types use
+/// their special representations. This allows handling both Protobuf and JSON requests with the
+/// same message types, e.g. by registering routes for both extractors guarded by the content type.
+///
+/// Requests must have a JSON content type, such as `application/json`. The payload size limit,
+/// error handler and (de)serialization options can be changed by registering a
+/// [`ProtoBufJsonConfig`] as app data. By default, requests with unknown fields are rejected.
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::{ProtoBuf, ProtoBufJson};
+/// use prost_reflect::ReflectMessage;
+///
+/// /// Accepts the message as JSON and responds with it as binary Protobuf.
+/// async fn convert<T>(msg: ProtoBufJson<T>) -> ProtoBuf<T>
+/// where
+///     T: ReflectMessage + Default,
+/// {
+///     ProtoBuf(msg.into_inner())
+/// }
+/// ```
+///
+/// [canonical JSON mapping]: https://protobuf.dev/programming-guides/proto3/#json
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub struct ProtoBufJson<T: Message>(pub T);
+
+impl<T: Message> ProtoBufJson<T> {
+    /// Unwraps into the inner message.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Message> Deref for ProtoBufJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Message> DerefMut for ProtoBufJson<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Message> fmt::Debug for ProtoBufJson<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ProtoBufJson: {:?}", self.0)
+    }
+}
+
+impl<T: Message> fmt::Display for ProtoBufJson<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<T> FromRequest for ProtoBufJson<T>
+where
+    T: ReflectMessage + Default + 'static,
+{
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = req.app_data::<ProtoBufJsonConfig>();
+        let limit = config.map_or(262_144, |c| c.limit);
+        let err_handler = config.and_then(|c| c.err_handler.clone());
+        let options = config
+            .map(|c| c.deserialize_options.clone())
+            .unwrap_or_default();
+        let req = req.clone();
+
+        let is_json = matches!(req.mime_type(), Ok(Some(content_type))
+            if content_type.type_() == mime::APPLICATION
+                && (content_type.subtype() == mime::JSON
+                    || content_type.suffix() == Some(mime::JSON)));
+
+        let handle_err = move |err: JsonPayloadError, req: &HttpRequest| match &err_handler {
+            Some(err_handler) => err_handler(err, req),
+            None => err.into(),
+        };
+
+        if !is_json {
+            return Box::pin(future::err(handle_err(JsonPayloadError::ContentType, &req)));
+        }
+
+        let mut payload = payload.take();
+
+        Box::pin(async move {
+            let mut body = BytesMut::with_capacity(8192);
+
+            while let Some(chunk) = payload.next().await {
+                let chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(err) => return Err(handle_err(JsonPayloadError::Payload(err), &req)),
+                };
+
+                if body.len() + chunk.len() > limit {
+                    return Err(handle_err(JsonPayloadError::Overflow { limit }, &req));
+                }
+
+                body.extend_from_slice(&chunk);
+            }
+
+            match deserialize::<T>(&body, &options) {
+                Ok(msg) => Ok(ProtoBufJson(msg)),
+                Err(err) => Err(handle_err(JsonPayloadError::Deserialize(err), &req)),
+            }
+        })
+    }
+}
+
+/// Responds with the message in the Protobuf JSON format and `Content-Type: application/json`.
+impl<T: ReflectMessage> Responder for ProtoBufJson<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        let options = req
+            .app_data::<ProtoBufJsonConfig>()
+            .map(|c| c.serialize_options.clone())
+            .unwrap_or_default();
+
+        let mut body = Vec::with_capacity(self.0.encoded_len());
+        let mut serializer = serde_json::Serializer::new(&mut body);
+
+        match self
+            .0
+            .transcode_to_dynamic()
+            .serialize_with_options(&mut serializer, &options)
+        {
+            Ok(()) => HttpResponse::Ok()
+                .content_type(mime::APPLICATION_JSON)
+                .body(body),
+            Err(err) => HttpResponse::from_error(JsonPayloadError::Serialize(err)),
+        }
+    }
+}
+
+/// Deserializes a message of type `T` from `body` in the Protobuf JSON format.
+fn deserialize<T>(body: &[u8], options: &DeserializeOptions) -> Result<T, serde_json::Error>
+where
+    T: ReflectMessage + Default,
+{
+    let desc = T::default().descriptor();
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let msg = DynamicMessage::deserialize_with_options(desc, &mut deserializer, options)?;
+    deserializer.end()?;
+
+    msg.transcode_to().map_err(serde::de::Error::custom)
+}
+
+/// Configuration for the [`ProtoBufJson`] extractor and responder.
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::ProtoBufJsonConfig;
+/// use actix_web::App;
+/// use prost_reflect::{DeserializeOptions, SerializeOptions};
+///
+/// let mut config = ProtoBufJsonConfig::default();
+/// config
+///     .limit(4096)
+///     .serialize_options(SerializeOptions::new().use_proto_field_name(true))
+///     .deserialize_options(DeserializeOptions::new().deny_unknown_fields(false));
+///
+/// let app = App::new().app_data(config);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub struct ProtoBufJsonConfig {
+    limit: usize,
+    err_handler: Option<JsonErrorHandler>,
+    serialize_options: SerializeOptions,
+    deserialize_options: DeserializeOptions,
+}
+
+type JsonErrorHandler = Arc<dyn Fn(JsonPayloadError, &HttpRequest) -> Error + Send + Sync>;
+
+impl ProtoBufJsonConfig {
+    /// Change max size of payload. By default max size is 256Kb
+    pub fn limit(&mut self, limit: usize) -> &mut Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set a custom error handler. By default, errors are converted into responses as described
+    /// for [`JsonPayloadError`].
+    pub fn error_handler<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(JsonPayloadError, &HttpRequest) -> Error + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+
+    /// Set the options used to serialize responses. By default, the canonical JSON mapping is
+    /// followed.
+    pub fn serialize_options(&mut self, options: SerializeOptions) -> &mut Self {
+        self.serialize_options = options;
+        self
+    }
+
+    /// Set the options used to deserialize payloads. By default, the canonical JSON mapping is
+    /// followed and unknown fields are rejected.
+    pub fn deserialize_options(&mut self, options: DeserializeOptions) -> &mut Self {
+        self.deserialize_options = options;
+        self
+    }
+}
+
+impl Default for ProtoBufJsonConfig {
+    fn default() -> Self {
+        ProtoBufJsonConfig {
+            limit: 262_144,
+            err_handler: None,
+            serialize_options: SerializeOptions::default(),
+            deserialize_options: DeserializeOptions::default(),
+        }
+    }
+}
+
+/// Returns true if the highest ranked JSON or Protobuf media type in the `Accept` header of `req`
+/// is a JSON one.
+fn prefers_json(req: &HttpRequest) -> bool {
+    let accept = match header::Accept::parse(req) {
+        Ok(accept) => accept,
+        Err(_) => return false,
+    };
+
+    accept
+        .ranked()
+        .into_iter()
+        .find_map(|mime| {
+            if mime.type_() != mime::APPLICATION {
+                None
+            } else if mime.subtype() == mime::JSON {
+                Some(true)
+            } else if matches!(
+                mime.subtype().as_str(),
+                "protobuf" | "x-protobuf" | "vnd.google.protobuf"
+            ) {
+                Some(false)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{body::MessageBody as _, http::header::ACCEPT, test::TestRequest};
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message, Serialize)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+        #[prost(string, tag = "2")]
+        pub name: String,
+    }
+
+    fn msg() -> ProtoBuf<MyObject> {
+        ProtoBuf(MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        })
+    }
+
+    #[actix_web::test]
+    async fn test_respond_as_jsonpb() {
+        let req = TestRequest::default()
+            .insert_header((ACCEPT, "application/json+protobuf"))
+            .to_http_request();
+        let res = msg().respond_as_jsonpb(&req);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        let body = res.into_body().try_into_bytes().unwrap();
+        assert_eq!(body, r#"{"number":9,"name":"test"}"#);
+
+        let req = TestRequest::default()
+            .insert_header((ACCEPT, "application/protobuf, application/json; q=0.5"))
+            .to_http_request();
+        let res = msg().respond_as_jsonpb(&req);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/protobuf"
+        );
+
+        let req = TestRequest::default().to_http_request();
+        let res = msg().respond_as_jsonpb(&req);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/protobuf"
+        );
+    }
+
+    #[cfg(feature = "json-negotiation")]
+    #[actix_web::test]
+    async fn test_json_negotiation() {
+        use actix_web::{test, web, App};
+
+        let srv = test::init_service(App::new().route(
+            "/",
+            web::get().to(|| async { NegotiatedProtoBuf::from(msg()) }),
+        ))
+        .await;
+
+        let req = test::TestRequest::get()
+            .insert_header((ACCEPT, "application/protobuf"))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/protobuf"
+        );
+        let body = test::read_body(res).await;
+        assert_eq!(MyObject::decode(body).unwrap(), msg().0);
+
+        let req = test::TestRequest::get()
+            .insert_header((ACCEPT, "application/json"))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body, serde_json::json!({ "number": 9, "name": "test" }));
+    }
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct User {
+        #[prost(int64, tag = "1")]
+        pub user_id: i64,
+        #[prost(string, tag = "2")]
+        pub display_name: String,
+    }
+
+    impl ReflectMessage for User {
+        fn descriptor(&self) -> prost_reflect::MessageDescriptor {
+            use prost_reflect::DescriptorPool;
+            use prost_types::{
+                field_descriptor_proto::{Label, Type},
+                DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+            };
+
+            // json names are filled in by protoc
+            let field = |name: &str, json_name: &str, number, ty: Type| FieldDescriptorProto {
+                name: Some(name.to_owned()),
+                json_name: Some(json_name.to_owned()),
+                number: Some(number),
+                label: Some(Label::Optional as i32),
+                r#type: Some(ty as i32),
+                ..Default::default()
+            };
+
+            let file = FileDescriptorProto {
+                name: Some("user.proto".to_owned()),
+                package: Some("example".to_owned()),
+                message_type: vec![DescriptorProto {
+                    name: Some("User".to_owned()),
+                    field: vec![
+                        field("user_id", "userId", 1, Type::Int64),
+                        field("display_name", "displayName", 2, Type::String),
+                    ],
+                    ..Default::default()
+                }],
+                syntax: Some("proto3".to_owned()),
+                ..Default::default()
+            };
+
+            DescriptorPool::from_file_descriptor_set(FileDescriptorSet { file: vec![file] })
+                .unwrap()
+                .get_message_by_name("example.User")
+                .unwrap()
+        }
+    }
+
+    fn user() -> User {
+        User {
+            user_id: 9,
+            display_name: "test".to_owned(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_protobuf_json() {
+        use actix_web::{test, web, App};
+
+        let srv = test::init_service(App::new().route(
+            "/",
+            web::post().to(|user: ProtoBufJson<User>| async move { user }),
+        ))
+        .await;
+
+        // 64-bit integers are strings in the canonical mapping, but numbers are accepted
+        let req = test::TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .set_payload(r#"{"userId":9,"displayName":"test"}"#)
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert!(res.status().is_success());
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        let body = test::read_body(res).await;
+        assert_eq!(body, r#"{"userId":"9","displayName":"test"}"#);
+
+        let req = test::TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .set_payload(r#"{"user_id":"9","unknown":true}"#)
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert!(res.status().is_client_error());
+
+        let req = test::TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .set_payload(user().encode_to_vec())
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert!(res.status().is_client_error());
+    }
+
+    #[actix_web::test]
+    async fn test_protobuf_json_options() {
+        use actix_web::{test, web, App};
+
+        let mut config = ProtoBufJsonConfig::default();
+        config
+            .serialize_options(SerializeOptions::new().use_proto_field_name(true))
+            .deserialize_options(DeserializeOptions::new().deny_unknown_fields(false));
+
+        let srv = test::init_service(App::new().app_data(config).route(
+            "/",
+            web::post().to(|user: ProtoBufJson<User>| async move {
+                assert_eq!(user.0, self::user());
+                user
+            }),
+        ))
+        .await;
+
+        let req = test::TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .set_payload(r#"{"user_id":"9","display_name":"test","unknown":true}"#)
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert!(res.status().is_success());
+        let body = test::read_body(res).await;
+        assert_eq!(body, r#"{"user_id":"9","display_name":"test"}"#);
+    }
+
+    #[actix_web::test]
+    async fn test_protobuf_json_config() {
+        let mut config = ProtoBufJsonConfig::default();
+        config
+            .limit(8)
+            .error_handler(|err, _req| actix_web::error::ErrorUnprocessableEntity(err.to_string()));
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .app_data(config)
+            .set_payload(r#"{"userId":9,"displayName":"test"}"#)
+            .to_http_parts();
+        let err = ProtoBufJson::<User>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert_eq!(err.as_response_error().status_code(), 422);
+    }
+}
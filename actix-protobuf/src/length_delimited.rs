@@ -0,0 +1,226 @@
+//! Extractor for request bodies consisting of many length-delimited messages.
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use actix_web::{dev::Payload, web::BytesMut, Error, FromRequest, HttpMessage as _, HttpRequest};
+use futures_util::{
+    future::{self, LocalBoxFuture},
+    StreamExt as _,
+};
+use prost::Message;
+
+use crate::{extend_within_limit, is_accepted_content_type, ProtoBufConfig, ProtoBufPayloadError};
+
+/// Protobuf extractor for a sequence of length-delimited messages.
+///
+/// The request body is read completely and decoded as a concatenation of messages, each preceded
+/// by its length as a varint (see [`Message::encode_length_delimited`]). An empty body yields no
+/// messages.
+///
+/// To prevent resource exhaustion, the size of the body and the number of messages are limited by
+/// the [`ProtoBufLengthDelimitedConfig`] registered as app data. The accepted content types are
+/// taken from the [`ProtoBufConfig`], if registered.
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::ProtoBufLengthDelimited;
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// pub struct Event {
+///     #[prost(int32, tag = "1")]
+///     pub id: i32,
+/// }
+///
+/// async fn index(events: ProtoBufLengthDelimited<Event>) -> String {
+///     format!("Received {} events", events.len())
+/// }
+/// ```
+pub struct ProtoBufLengthDelimited<T: Message>(pub Vec<T>);
+
+impl<T: Message> ProtoBufLengthDelimited<T> {
+    /// Unwraps into the decoded messages.
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T: Message> Deref for ProtoBufLengthDelimited<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T: Message> DerefMut for ProtoBufLengthDelimited<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+impl<T: Message> fmt::Debug for ProtoBufLengthDelimited<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ProtoBufLengthDelimited: {:?}", self.0)
+    }
+}
+
+impl<T> FromRequest for ProtoBufLengthDelimited<T>
+where
+    T: Message + Default + 'static,
+{
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        if !is_accepted_content_type(req.app_data::<ProtoBufConfig>(), req.content_type()) {
+            return Box::pin(future::err(ProtoBufPayloadError::ContentType.into()));
+        }
+
+        let (total_limit, max_messages) = req
+            .app_data::<ProtoBufLengthDelimitedConfig>()
+            .map_or((262_144, 1024), |c| (c.total_limit, c.max_messages));
+        let mut payload = payload.take();
+
+        Box::pin(async move {
+            let mut body = BytesMut::with_capacity(8192);
+
+            while let Some(chunk) = payload.next().await {
+                let chunk = chunk.map_err(ProtoBufPayloadError::Payload)?;
+                extend_within_limit(&mut body, &chunk, total_limit)?;
+            }
+
+            let mut buf = body.freeze();
+            let mut messages = Vec::new();
+
+            while !buf.is_empty() {
+                if messages.len() == max_messages {
+                    return Err(ProtoBufPayloadError::TooManyMessages.into());
+                }
+
+                let msg = T::decode_length_delimited(&mut buf)
+                    .map_err(ProtoBufPayloadError::Deserialize)?;
+                messages.push(msg);
+            }
+
+            Ok(ProtoBufLengthDelimited(messages))
+        })
+    }
+}
+
+/// Configuration for the [`ProtoBufLengthDelimited`] extractor.
+pub struct ProtoBufLengthDelimitedConfig {
+    total_limit: usize,
+    max_messages: usize,
+}
+
+impl ProtoBufLengthDelimitedConfig {
+    /// Change max size of the body, including the length prefixes. By default max size is 256Kb.
+    pub fn total_limit(&mut self, limit: usize) -> &mut Self {
+        self.total_limit = limit;
+        self
+    }
+
+    /// Change max number of messages in the body. By default at most 1024 messages are decoded.
+    ///
+    /// Bodies with more messages are rejected with [`ProtoBufPayloadError::TooManyMessages`].
+    pub fn max_messages(&mut self, max: usize) -> &mut Self {
+        self.max_messages = max;
+        self
+    }
+}
+
+impl Default for ProtoBufLengthDelimitedConfig {
+    fn default() -> Self {
+        ProtoBufLengthDelimitedConfig {
+            total_limit: 262_144,
+            max_messages: 1024,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header, test::TestRequest};
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+    }
+
+    fn encode(count: i32) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for number in 0..count {
+            MyObject { number }
+                .encode_length_delimited(&mut buf)
+                .unwrap();
+        }
+        buf
+    }
+
+    async fn extract(
+        body: Vec<u8>,
+        config: ProtoBufLengthDelimitedConfig,
+    ) -> Result<ProtoBufLengthDelimited<MyObject>, Error> {
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .app_data(config)
+            .set_payload(body)
+            .to_http_parts();
+
+        ProtoBufLengthDelimited::<MyObject>::from_request(&req, &mut pl).await
+    }
+
+    #[actix_web::test]
+    async fn test_length_delimited() {
+        for count in [0, 1, 100] {
+            let msgs = extract(encode(count), ProtoBufLengthDelimitedConfig::default())
+                .await
+                .unwrap();
+
+            let expected = (0..count)
+                .map(|number| MyObject { number })
+                .collect::<Vec<_>>();
+            assert_eq!(msgs.into_inner(), expected);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_truncated() {
+        let mut body = encode(2);
+        body.pop();
+
+        let res = extract(body, ProtoBufLengthDelimitedConfig::default()).await;
+        assert!(res.is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_too_many_messages() {
+        let mut config = ProtoBufLengthDelimitedConfig::default();
+        config.max_messages(3);
+
+        let err = extract(encode(4), config).await.unwrap_err();
+        assert!(matches!(
+            err.as_error::<ProtoBufPayloadError>(),
+            Some(ProtoBufPayloadError::TooManyMessages)
+        ));
+    }
+
+    #[actix_web::test]
+    async fn test_total_limit() {
+        let mut config = ProtoBufLengthDelimitedConfig::default();
+        config.total_limit(4);
+
+        let err = extract(encode(4), config).await.unwrap_err();
+        assert!(matches!(
+            err.as_error::<ProtoBufPayloadError>(),
+            Some(ProtoBufPayloadError::Overflow)
+        ));
+    }
+}
@@ -3,32 +3,123 @@
 #![forbid(unsafe_code)]
 #![deny(rust_2018_idioms, nonstandard_style)]
 #![warn(future_incompatible)]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+#[cfg(feature = "base64")]
+mod base64_format;
+mod batch;
+mod compression;
+#[cfg(feature = "deterministic")]
+mod deterministic;
+#[cfg(feature = "proto2-extensions")]
+mod extensions;
+mod file;
+mod grpc;
+pub mod guard;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
+#[cfg(feature = "json")]
+mod json;
+mod length_delimited;
+mod message_stream;
+#[cfg(feature = "json")]
+mod metadata;
+mod metrics;
+#[cfg(feature = "multipart")]
+mod multipart;
+mod offload;
+mod optional;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod sensitive;
+mod sink;
+#[cfg(feature = "test-utils")]
+mod test_utils;
+mod unknown_fields;
+mod versioned;
 
 use std::{
+    any::Any,
+    cell::RefCell,
+    collections::VecDeque,
+    convert::TryFrom,
     fmt,
     future::Future,
+    io, mem,
     ops::{Deref, DerefMut},
     pin::Pin,
+    rc::Rc,
+    sync::Arc,
     task::{self, Poll},
+    time::{Duration, Instant},
 };
 
 use actix_web::{
-    body::BoxBody,
+    body::{BodyStream, BoxBody, MessageBody},
     dev::Payload,
-    error::PayloadError,
-    http::header::{CONTENT_LENGTH, CONTENT_TYPE},
-    web::BytesMut,
+    error::{BlockingError, PayloadError},
+    http::{
+        header::{
+            HeaderName, HeaderValue, ACCEPT, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, LINK,
+            VARY,
+        },
+        StatusCode,
+    },
+    mime::Mime,
+    web::{Bytes, BytesMut},
     Error, FromRequest, HttpMessage, HttpRequest, HttpResponse, HttpResponseBuilder, Responder,
     ResponseError,
 };
 use derive_more::Display;
 use futures_util::{
     future::{FutureExt as _, LocalBoxFuture},
-    stream::StreamExt as _,
+    ready,
+    stream::{self, StreamExt as _},
 };
 use prost::{DecodeError as ProtoBufDecodeError, EncodeError as ProtoBufEncodeError, Message};
 
+pub use self::batch::ProtoBufBatch;
+pub use self::compression::CompressionAlgorithm;
+#[cfg(feature = "compression")]
+pub use self::compression::CompressionHint;
+pub use self::grpc::{GrpcStatus, GRPC_STATUS};
+pub use self::guard::ProtoBufGuard;
+#[cfg(feature = "hot-reload")]
+pub use self::hot_reload::HotReloadProtoBuf;
+#[cfg(feature = "json-negotiation")]
+pub use self::json::NegotiatedProtoBuf;
+#[cfg(feature = "json")]
+pub use self::json::{ProtoBufJson, ProtoBufJsonConfig};
+pub use self::length_delimited::{ProtoBufLengthDelimited, ProtoBufLengthDelimitedConfig};
+pub use self::message_stream::ProtoBufStream;
+#[cfg(feature = "json")]
+pub use self::metadata::WithMetadata;
+pub use self::metrics::{MetricsSnapshot, ProtoBufMetrics};
+pub use self::offload::ProtoBufOffloaded;
+pub use self::optional::ProtoBufOptional;
+pub use self::sensitive::{ProtoBufSensitive, Redacted, SensitiveDebug};
+#[cfg(feature = "test-utils")]
+pub use self::test_utils::TestRequestProtoBufExt;
+pub use self::unknown_fields::UnknownField;
+pub use self::versioned::{ProtoBufVersioned, X_PROTO_VERSION};
+
+/// Name of the response header containing the schema version set by
+/// [`ProtoBufConfig::schema_version`].
+pub const X_PROTOBUF_SCHEMA_VERSION: &str = "x-protobuf-schema-version";
+
+/// Content types accepted for gRPC-framed payloads, see [`ProtoBufConfig::grpc_framing`].
+pub(crate) const GRPC_CONTENT_TYPES: &[&str] = &["application/grpc", "application/grpc+proto"];
+
+/// Maximum number of bytes pre-allocated for reading a payload based on its `Content-Length`, so
+/// that a client cannot make the server reserve memory for a body it never sends.
+const MAX_PREALLOCATION: usize = 64 * 1024;
+
+/// Content types accepted for Protobuf payloads by default.
+pub(crate) const CONTENT_TYPES: &[&str] =
+    &["application/protobuf", "application/vnd.google.protobuf"];
+
 #[derive(Debug, Display)]
+#[non_exhaustive]
 pub enum ProtoBufPayloadError {
     /// Payload size is bigger than 256k
     #[display(fmt = "Payload size is bigger than 256k")]
@@ -49,15 +140,107 @@ pub enum ProtoBufPayloadError {
     /// Payload error
     #[display(fmt = "Error that occur during reading payload: {}", _0)]
     Payload(PayloadError),
+
+    /// Offloaded decoding could not be run on the blocking thread pool
+    #[display(fmt = "ProtoBuf offloaded decoding failed: {}", _0)]
+    Blocking(BlockingError),
+
+    /// I/O error
+    #[display(fmt = "ProtoBuf I/O error: {}", _0)]
+    Io(io::Error),
+
+    /// gRPC message is compressed
+    #[display(fmt = "Compressed gRPC messages are not supported")]
+    UnsupportedCompression,
+
+    /// gRPC message header is malformed or does not match the payload length
+    #[display(fmt = "Malformed gRPC message header")]
+    GrpcFrame,
+
+    /// Body contains more messages than allowed
+    #[display(fmt = "Body contains too many messages")]
+    TooManyMessages,
+
+    /// Sink rejected the message
+    #[display(fmt = "Error that occur during sending to sink: {}", _0)]
+    Sink(Box<dyn std::error::Error + Send + Sync>),
+
+    /// Response link contains characters that would break out of the `Link` header
+    #[display(fmt = "Invalid link: {:?}", _0)]
+    InvalidLink(String),
+
+    /// Compressed payload could not be decompressed
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[display(fmt = "ProtoBuf decompression error: {}", _0)]
+    DecompressError(io::Error),
+
+    /// Base64 decode error
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+    #[display(fmt = "ProtoBuf base64 decode error: {}", _0)]
+    Base64(base64::DecodeError),
+
+    /// Multipart field error
+    #[cfg(feature = "multipart")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+    #[display(fmt = "Error that occur during reading multipart field: {}", _0)]
+    Multipart(actix_multipart::MultipartError),
+}
+
+impl std::error::Error for ProtoBufPayloadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProtoBufPayloadError::Serialize(err) => Some(err),
+            ProtoBufPayloadError::Deserialize(err) => Some(err),
+            ProtoBufPayloadError::Payload(err) => Some(err),
+            ProtoBufPayloadError::Blocking(err) => Some(err),
+            ProtoBufPayloadError::Io(err) => Some(err),
+            ProtoBufPayloadError::Sink(err) => Some(err.as_ref()),
+            #[cfg(feature = "compression")]
+            ProtoBufPayloadError::DecompressError(err) => Some(err),
+            #[cfg(feature = "base64")]
+            ProtoBufPayloadError::Base64(err) => Some(err),
+            #[cfg(feature = "multipart")]
+            ProtoBufPayloadError::Multipart(err) => Some(err),
+            ProtoBufPayloadError::Overflow
+            | ProtoBufPayloadError::ContentType
+            | ProtoBufPayloadError::UnsupportedCompression
+            | ProtoBufPayloadError::GrpcFrame
+            | ProtoBufPayloadError::TooManyMessages
+            | ProtoBufPayloadError::InvalidLink(_) => None,
+        }
+    }
 }
 
 impl ResponseError for ProtoBufPayloadError {
-    fn error_response(&self) -> HttpResponse {
+    fn status_code(&self) -> StatusCode {
         match *self {
-            ProtoBufPayloadError::Overflow => HttpResponse::PayloadTooLarge().into(),
-            _ => HttpResponse::BadRequest().into(),
+            ProtoBufPayloadError::Overflow | ProtoBufPayloadError::TooManyMessages => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            ProtoBufPayloadError::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ProtoBufPayloadError::Serialize(_)
+            | ProtoBufPayloadError::Blocking(_)
+            | ProtoBufPayloadError::Io(_)
+            | ProtoBufPayloadError::Sink(_)
+            | ProtoBufPayloadError::InvalidLink(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ProtoBufPayloadError::Deserialize(_)
+            | ProtoBufPayloadError::Payload(_)
+            | ProtoBufPayloadError::UnsupportedCompression
+            | ProtoBufPayloadError::GrpcFrame => StatusCode::BAD_REQUEST,
+            #[cfg(feature = "compression")]
+            ProtoBufPayloadError::DecompressError(_) => StatusCode::BAD_REQUEST,
+            #[cfg(feature = "base64")]
+            ProtoBufPayloadError::Base64(_) => StatusCode::BAD_REQUEST,
+            #[cfg(feature = "multipart")]
+            ProtoBufPayloadError::Multipart(_) => StatusCode::BAD_REQUEST,
         }
     }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::new(self.status_code())
+    }
 }
 
 impl From<PayloadError> for ProtoBufPayloadError {
@@ -88,6 +271,31 @@ impl<T: Message> DerefMut for ProtoBuf<T> {
     }
 }
 
+/// Serializes the inner message using its own [`Serialize`](serde::Serialize) implementation.
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<T: Message + serde::Serialize> serde::Serialize for ProtoBuf<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Deserializes the inner message using its own [`Deserialize`](serde::Deserialize)
+/// implementation.
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de, T: Message + Default + serde::Deserialize<'de>> serde::Deserialize<'de> for ProtoBuf<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(ProtoBuf)
+    }
+}
+
 impl<T: Message> fmt::Debug for ProtoBuf<T>
 where
     T: fmt::Debug,
@@ -106,21 +314,441 @@ where
     }
 }
 
+impl<T: Message> ProtoBuf<T> {
+    /// Returns the canonical content type of Protobuf messages, `application/protobuf`.
+    ///
+    /// Useful for custom middleware and response builders that need to set or compare the content
+    /// type without hard-coding it.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_protobuf::ProtoBuf;
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # struct MyObject {
+    /// #     #[prost(int32, tag = "1")]
+    /// #     number: i32,
+    /// # }
+    /// use actix_web::{http::header::CONTENT_TYPE, HttpResponse};
+    ///
+    /// let res = HttpResponse::Ok()
+    ///     .insert_header((CONTENT_TYPE, ProtoBuf::<MyObject>::content_type_header()))
+    ///     .finish();
+    /// ```
+    pub fn content_type_header() -> HeaderValue {
+        HeaderValue::from_static("application/protobuf")
+    }
+
+    /// Transforms the inner message, e.g., to turn a decoded request into a response.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_protobuf::ProtoBuf;
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # struct Request {
+    /// #     #[prost(int32, tag = "1")]
+    /// #     id: i32,
+    /// # }
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # struct Response {
+    /// #     #[prost(string, tag = "1")]
+    /// #     greeting: String,
+    /// # }
+    /// async fn index(msg: ProtoBuf<Request>) -> ProtoBuf<Response> {
+    ///     msg.map_into(|req| Response {
+    ///         greeting: format!("Hello, user {}!", req.id),
+    ///     })
+    /// }
+    /// ```
+    pub fn map_into<U, F>(self, f: F) -> ProtoBuf<U>
+    where
+        U: Message,
+        F: FnOnce(T) -> U,
+    {
+        ProtoBuf(f(self.0))
+    }
+
+    /// Merges `patch` into the inner message, e.g. to implement `PATCH` endpoints.
+    ///
+    /// Follows the Protobuf merge semantics: scalar fields set in `patch` overwrite those of the
+    /// message, repeated fields are appended and nested messages are merged recursively. Note that
+    /// proto3 scalar fields set to their default value are indistinguishable from unset ones, so
+    /// they cannot be reset using a patch.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_protobuf::ProtoBuf;
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # struct User {
+    /// #     #[prost(string, tag = "1")]
+    /// #     name: String,
+    /// # }
+    /// # fn load_user() -> User { User::default() }
+    /// async fn update_user(patch: ProtoBuf<User>) -> ProtoBuf<User> {
+    ///     ProtoBuf(load_user()).apply_patch(patch)
+    /// }
+    /// ```
+    pub fn apply_patch(mut self, patch: ProtoBuf<T>) -> ProtoBuf<T> {
+        let patch = patch.0.encode_to_vec();
+        self.0
+            .merge(patch.as_slice())
+            .expect("encoded message should be decodable");
+        self
+    }
+
+    /// Converts the inner message into a domain type using its [`TryFrom`] implementation.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::convert::TryFrom;
+    /// # use actix_protobuf::ProtoBuf;
+    /// # use actix_web::{error::ErrorBadRequest, Error, HttpResponse};
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # struct CreateUser {
+    /// #     #[prost(string, tag = "1")]
+    /// #     email: String,
+    /// # }
+    /// struct User {
+    ///     email: String,
+    /// }
+    ///
+    /// impl TryFrom<CreateUser> for User {
+    ///     type Error = &'static str;
+    ///
+    ///     fn try_from(msg: CreateUser) -> Result<Self, Self::Error> {
+    ///         if msg.email.contains('@') {
+    ///             Ok(User { email: msg.email })
+    ///         } else {
+    ///             Err("invalid email address")
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// async fn index(msg: ProtoBuf<CreateUser>) -> Result<HttpResponse, Error> {
+    ///     let user: User = msg.try_into_domain().map_err(ErrorBadRequest)?;
+    ///     Ok(HttpResponse::Ok().body(user.email))
+    /// }
+    /// ```
+    pub fn try_into_domain<V>(self) -> Result<V, V::Error>
+    where
+        V: TryFrom<T>,
+    {
+        V::try_from(self.0)
+    }
+
+    /// Converts the inner message into a [`Json`](actix_web::web::Json) responder.
+    ///
+    /// Useful for handlers that switch between Protobuf and JSON output, e.g., based on client
+    /// capabilities. The message is first converted into `V` using its [`From`] implementation.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    pub fn into_json<V>(self) -> actix_web::web::Json<V>
+    where
+        V: serde::Serialize + From<T>,
+    {
+        actix_web::web::Json(V::from(self.0))
+    }
+
+    /// Appends the message to the body of an in-progress streaming response.
+    ///
+    /// The message is encoded as a length-delimited frame (see
+    /// [`Message::encode_length_delimited`]) and chained onto the end of the existing body, which is
+    /// turned into a [`BodyStream`]. This allows handlers that assemble a response incrementally,
+    /// such as long-polling or event stream endpoints, to push messages as they become available.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_protobuf::ProtoBuf;
+    /// # use actix_web::HttpResponse;
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # struct Event {
+    /// #     #[prost(int32, tag = "1")]
+    /// #     id: i32,
+    /// # }
+    /// let mut res = HttpResponse::Ok()
+    ///     .content_type("application/protobuf")
+    ///     .finish();
+    ///
+    /// ProtoBuf(Event { id: 1 }).send_chunked(&mut res);
+    /// ProtoBuf(Event { id: 2 }).send_chunked(&mut res);
+    /// ```
+    pub fn send_chunked(self, res: &mut HttpResponse) {
+        let frame = Bytes::from(self.0.encode_length_delimited_to_vec());
+
+        // the body is only wrapped on the first call, later frames are queued behind it
+        if let Some(frames) = res.extensions().get::<ChunkedFrames>() {
+            frames.0.borrow_mut().push_back(frame);
+            return;
+        }
+
+        let frames = ChunkedFrames::default();
+        frames.0.borrow_mut().push_back(frame);
+
+        let placeholder = HttpResponse::new(res.status());
+        let prev = mem::replace(res, placeholder);
+
+        *res = prev.map_body({
+            let frames = frames.clone();
+
+            move |_, body| {
+                let mut body = body;
+                let existing = stream::poll_fn(move |cx| Pin::new(&mut body).poll_next(cx));
+                let queued = stream::poll_fn(move |_| {
+                    Poll::Ready(frames.0.borrow_mut().pop_front().map(Ok))
+                });
+
+                BodyStream::new(existing.chain(queued)).boxed()
+            }
+        });
+
+        res.extensions_mut().insert(frames);
+    }
+}
+
+/// Frames appended to a response by [`ProtoBuf::send_chunked`], shared with its body.
+#[derive(Clone, Default)]
+struct ChunkedFrames(Rc<RefCell<VecDeque<Bytes>>>);
+
 pub struct ProtoBufConfig {
     limit: usize,
+    content_types: Option<Vec<String>>,
+    allow_content_type_wildcard: bool,
+    grpc_framing: bool,
+    #[cfg(feature = "compression")]
+    allow_content_encoding: bool,
+    max_attempts: usize,
+    metrics: Option<Arc<ProtoBufMetrics>>,
+    payload_size_observer: Option<PayloadSizeObserver>,
+    response_content_type: Option<Mime>,
+    schema_version: Option<HeaderValue>,
+    err_handler: Option<ErrorHandler>,
+    validators: Vec<Validator>,
 }
 
+type ErrorHandler = Arc<dyn Fn(ProtoBufPayloadError, &HttpRequest) -> Error + Send + Sync>;
+
+type Validator = Arc<dyn Fn(&dyn Any, &HttpRequest) -> Result<(), Error> + Send + Sync>;
+
+type PayloadSizeObserver = Arc<dyn Fn(usize) + Send + Sync>;
+
 impl ProtoBufConfig {
     /// Change max size of payload. By default max size is 256Kb
     pub fn limit(&mut self, limit: usize) -> &mut Self {
         self.limit = limit;
         self
     }
+
+    /// Set a custom error handler, e.g. to respond with a machine-readable description of the
+    /// error. By default, errors result in responses with an empty body.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_protobuf::{ProtoBufConfig, ProtoBufPayloadError};
+    /// use actix_web::{error::InternalError, web, App, HttpResponse};
+    ///
+    /// let mut config = ProtoBufConfig::default();
+    /// config.error_handler(|err, _req| {
+    ///     let res = HttpResponse::BadRequest().body(format!("Invalid payload: {}", err));
+    ///     InternalError::from_response(err, res).into()
+    /// });
+    ///
+    /// let app = App::new().app_data(config);
+    /// ```
+    pub fn error_handler<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(ProtoBufPayloadError, &HttpRequest) -> Error + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+
+    /// Validate decoded messages of type `T` before they are passed to the handler, e.g. to enforce
+    /// constraints that cannot be expressed in the `.proto` schema.
+    ///
+    /// If `f` returns an error, the request is rejected with it and the handler is not called. The
+    /// error is returned as is, without being passed to the [error handler](Self::error_handler).
+    /// Validators only apply to the [`ProtoBuf`] extractor, and only to messages of type `T`; they
+    /// can be registered for several message types, and are called in the order they were
+    /// registered.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_protobuf::ProtoBufConfig;
+    /// use actix_web::{error, App};
+    ///
+    /// #[derive(Clone, PartialEq, prost::Message)]
+    /// pub struct User {
+    ///     #[prost(string, tag = "1")]
+    ///     pub name: String,
+    /// }
+    ///
+    /// let mut config = ProtoBufConfig::default();
+    /// config.validator(|user: &User, _req| {
+    ///     if user.name.is_empty() {
+    ///         return Err(error::ErrorUnprocessableEntity("name must not be empty"));
+    ///     }
+    ///     Ok(())
+    /// });
+    ///
+    /// let app = App::new().app_data(config);
+    /// ```
+    pub fn validator<F, T>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&T, &HttpRequest) -> Result<(), Error> + Send + Sync + 'static,
+        T: 'static,
+    {
+        let validator = move |msg: &dyn Any, req: &HttpRequest| match msg.downcast_ref::<T>() {
+            Some(msg) => f(msg, req),
+            None => Ok(()),
+        };
+
+        self.validators.push(Arc::new(validator));
+        self
+    }
+
+    /// Accept requests with one of the given content types, e.g. `application/x-protobuf`, instead
+    /// of the default `application/protobuf` and `application/vnd.google.protobuf`.
+    ///
+    /// Content types are compared case-insensitively, ignoring parameters such as `charset`.
+    pub fn accept_content_types<I>(&mut self, content_types: I) -> &mut Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.content_types = Some(content_types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Accept requests with a wildcard (`*/*`) or missing content type and attempt to decode them
+    /// as Protobuf anyway. Disabled by default.
+    ///
+    /// Note that enabling this lowers the security posture of the endpoint: the content type is no
+    /// longer a guarantee that the client intended to send a Protobuf payload, and arbitrary bodies
+    /// will be handed to the decoder.
+    pub fn allow_content_type_wildcard(&mut self, allow: bool) -> &mut Self {
+        self.allow_content_type_wildcard = allow;
+        self
+    }
+
+    /// Expect payloads to be framed as gRPC messages. Disabled by default.
+    ///
+    /// gRPC prefixes each message with a 5-byte header: a compression flag followed by the length
+    /// of the message as a 4-byte big-endian integer. When enabled, requests with
+    /// `Content-Type: application/grpc` or `application/grpc+proto` are accepted as well, and the
+    /// header of their payload is validated and stripped before decoding. Payloads of other
+    /// content types are decoded as is. Compressed messages are rejected with
+    /// [`ProtoBufPayloadError::UnsupportedCompression`].
+    pub fn grpc_framing(&mut self, enabled: bool) -> &mut Self {
+        self.grpc_framing = enabled;
+        self
+    }
+
+    /// Accept payloads compressed with `Content-Encoding: gzip` or `deflate` and decompress them
+    /// before decoding. Disabled by default.
+    ///
+    /// The [payload limit](Self::limit) applies to both the compressed and the decompressed
+    /// payload. Payloads that cannot be decompressed are rejected with
+    /// [`ProtoBufPayloadError::DecompressError`]. Payloads with other content codings, or any
+    /// content coding if this is disabled, are rejected with
+    /// [`ProtoBufPayloadError::ContentType`].
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub fn allow_content_encoding(&mut self, allow: bool) -> &mut Self {
+        self.allow_content_encoding = allow;
+        self
+    }
+
+    /// Read the payload up to `max_attempts` times when it ends prematurely with
+    /// [`PayloadError::Incomplete`], e.g. due to transient network issues. By default, the payload
+    /// is read only once.
+    ///
+    /// Each retry discards the data read so far and continues reading from the payload stream.
+    /// This only makes sense when the stream is truly restartable, i.e. it starts yielding the
+    /// whole body again after an incomplete read; otherwise retrying can only lead to decoding
+    /// errors.
+    pub fn retry_on_incomplete(&mut self, max_attempts: usize) -> &mut Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Record metrics about extracted payloads in `metrics`. Disabled by default.
+    pub fn observe(&mut self, metrics: Arc<ProtoBufMetrics>) -> &mut Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Call `observer` with the number of bytes read once the payload has been read completely,
+    /// e.g. to feed a histogram of payload sizes. Disabled by default.
+    ///
+    /// The size is observed before the payload is decompressed or decoded, so it is also reported
+    /// for payloads that fail to decode. Payloads that are rejected before being read, e.g. for
+    /// their content type or for exceeding the [limit](Self::limit), are not observed.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::{
+    ///     atomic::{AtomicUsize, Ordering},
+    ///     Arc,
+    /// };
+    ///
+    /// use actix_protobuf::ProtoBufConfig;
+    ///
+    /// let bytes_read = Arc::new(AtomicUsize::new(0));
+    ///
+    /// let mut config = ProtoBufConfig::default();
+    /// config.observe_payload_size({
+    ///     let bytes_read = Arc::clone(&bytes_read);
+    ///     Arc::new(move |size| {
+    ///         bytes_read.fetch_add(size, Ordering::Relaxed);
+    ///     })
+    /// });
+    /// ```
+    pub fn observe_payload_size(
+        &mut self,
+        observer: Arc<dyn Fn(usize) + Send + Sync>,
+    ) -> &mut Self {
+        self.payload_size_observer = Some(observer);
+        self
+    }
+
+    /// Change the content type of responses, e.g. to `application/vnd.google.protobuf`. By
+    /// default, responses are sent with `Content-Type: application/protobuf`.
+    ///
+    /// Applies to the [`ProtoBuf`] responder and
+    /// [`ProtoBufResponseBuilder::protobuf_with_config`].
+    pub fn response_content_type(&mut self, content_type: Mime) -> &mut Self {
+        self.response_content_type = Some(content_type);
+        self
+    }
+
+    /// Include the version of the `.proto` schema used to encode responses of the [`ProtoBuf`]
+    /// responder in the `X-Protobuf-Schema-Version` header. Disabled by default.
+    ///
+    /// # Panics
+    /// Panics if `version` is not a valid header value.
+    pub fn schema_version(&mut self, version: impl Into<String>) -> &mut Self {
+        let version = HeaderValue::try_from(version.into()).expect("invalid schema version");
+        self.schema_version = Some(version);
+        self
+    }
 }
 
 impl Default for ProtoBufConfig {
     fn default() -> Self {
-        ProtoBufConfig { limit: 262_144 }
+        ProtoBufConfig {
+            limit: 262_144,
+            content_types: None,
+            allow_content_type_wildcard: false,
+            grpc_framing: false,
+            #[cfg(feature = "compression")]
+            allow_content_encoding: false,
+            max_attempts: 1,
+            metrics: None,
+            payload_size_observer: None,
+            response_content_type: None,
+            schema_version: None,
+            err_handler: None,
+            validators: Vec::new(),
+        }
     }
 }
 
@@ -133,36 +761,243 @@ where
 
     #[inline]
     fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
-        let limit = req
-            .app_data::<ProtoBufConfig>()
-            .map(|c| c.limit)
-            .unwrap_or(262_144);
-        ProtoBufMessage::new(req, payload)
+        let config = req.app_data::<ProtoBufConfig>();
+        let limit = config.map_or(262_144, |c| c.limit);
+        let err_handler = config.and_then(|c| c.err_handler.clone());
+        let validators = config.map(|c| c.validators.clone()).unwrap_or_default();
+        let req = req.clone();
+
+        ProtoBufMessage::new(&req, payload)
             .limit(limit)
             .map(move |res| match res {
-                Err(e) => Err(e.into()),
-                Ok(item) => Ok(ProtoBuf(item)),
+                Err(e) => match err_handler {
+                    Some(err_handler) => Err(err_handler(e, &req)),
+                    None => Err(e.into()),
+                },
+                Ok(item) => {
+                    for validator in &validators {
+                        validator(&item, &req)?;
+                    }
+
+                    Ok(ProtoBuf(item))
+                }
             })
             .boxed_local()
     }
 }
 
+/// Responds with the encoded message and `Content-Type: application/protobuf`, or the content type
+/// set by [`ProtoBufConfig::response_content_type`]. If a [schema
+/// version](ProtoBufConfig::schema_version) is configured, it is sent in the
+/// `X-Protobuf-Schema-Version` header.
+///
+/// The message is encoded into a buffer of exactly [`encoded_len()`](Message::encoded_len) bytes,
+/// so the response is sent with a `Content-Length` header instead of using chunked encoding. No
+/// `Content-Encoding` is set, so the response can be compressed by the `Compress` middleware when
+/// it is registered.
+///
+/// To respond with JSON when the client prefers it, use `ProtoBuf::respond_as_jsonpb()` or the
+/// `NegotiatedProtoBuf` responder instead.
 impl<T: Message + Default> Responder for ProtoBuf<T> {
     type Body = BoxBody;
 
-    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
-        let mut buf = Vec::new();
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        self.respond_binary(req)
+    }
+}
+
+impl<T: Message> ProtoBuf<T> {
+    /// Responds with the encoded message, honoring the content type and schema version set in the
+    /// [`ProtoBufConfig`] of `req`.
+    pub(crate) fn respond_binary(self, req: &HttpRequest) -> HttpResponse {
+        let config = req.app_data::<ProtoBufConfig>();
+
+        let mut res = match config.and_then(|c| c.response_content_type.as_ref()) {
+            Some(content_type) => {
+                encode_response(&mut HttpResponse::Ok(), self.0, content_type.as_ref())
+                    .unwrap_or_else(HttpResponse::from_error)
+            }
+            None => self.into_response(),
+        };
+
+        if let Some(version) = config.and_then(|c| c.schema_version.clone()) {
+            if res.status().is_success() {
+                res.headers_mut()
+                    .insert(HeaderName::from_static(X_PROTOBUF_SCHEMA_VERSION), version);
+            }
+        }
+
+        res
+    }
+
+    fn into_response(self) -> HttpResponse {
+        let mut buf = Vec::with_capacity(self.0.encoded_len());
         match self.0.encode(&mut buf) {
-            Ok(()) => HttpResponse::Ok()
-                .content_type("application/protobuf")
-                .body(buf),
-            Err(err) => HttpResponse::from_error(Error::from(ProtoBufPayloadError::Serialize(err))),
+            Ok(()) => {
+                #[cfg(feature = "tracing")]
+                tracing::trace!(bytes = buf.len(), "encoded Protobuf response");
+
+                HttpResponse::Ok()
+                    .content_type(Self::content_type_header())
+                    .body(buf)
+            }
+            Err(err) => {
+                let err = ProtoBufPayloadError::Serialize(err);
+
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %err, "failed to encode Protobuf response");
+
+                HttpResponse::from_error(Error::from(err))
+            }
+        }
+    }
+
+    /// Responds with the encoded message and a `Vary` header listing `vary_headers`.
+    ///
+    /// Lets caches such as CDNs store the Protobuf and JSON variants of a resource separately.
+    /// When `vary_headers` is empty, `Vary: Accept, Content-Type` is used. The header is appended
+    /// to any existing `Vary` header of the response.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_protobuf::ProtoBuf;
+    /// # use actix_web::{http::header, HttpResponse};
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # struct MyObject {
+    /// #     #[prost(int32, tag = "1")]
+    /// #     number: i32,
+    /// # }
+    /// async fn index() -> HttpResponse {
+    ///     ProtoBuf(MyObject { number: 9 })
+    ///         .into_web_response_with_vary(&[header::ACCEPT, header::ACCEPT_ENCODING])
+    /// }
+    /// ```
+    pub fn into_web_response_with_vary(self, vary_headers: &[HeaderName]) -> HttpResponse {
+        const DEFAULT_VARY: &[HeaderName] = &[ACCEPT, CONTENT_TYPE];
+
+        let vary_headers = if vary_headers.is_empty() {
+            DEFAULT_VARY
+        } else {
+            vary_headers
+        };
+
+        let vary = vary_headers
+            .iter()
+            .map(HeaderName::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut res = self.into_response();
+        res.headers_mut().append(
+            VARY,
+            HeaderValue::from_str(&vary).expect("header names are valid header values"),
+        );
+        res
+    }
+
+    /// Responds with the encoded message and a `Link` header for each `(url, rel)` pair in
+    /// `links`.
+    ///
+    /// Each link is formatted as `<url>; rel="rel"`, allowing hypermedia APIs to advertise related
+    /// resources alongside Protobuf responses.
+    ///
+    /// # Errors
+    /// Returns [`ProtoBufPayloadError::InvalidLink`] if a URL contains `>`, a relation type
+    /// contains `"` or `\`, or either contains control characters such as CR and LF, since these
+    /// would allow the link to inject attributes or headers. URLs should be percent-encoded.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_protobuf::{ProtoBuf, ProtoBufPayloadError};
+    /// # use actix_web::HttpResponse;
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # struct Page {
+    /// #     #[prost(int32, tag = "1")]
+    /// #     number: i32,
+    /// # }
+    /// async fn index() -> Result<HttpResponse, ProtoBufPayloadError> {
+    ///     ProtoBuf(Page { number: 2 }).respond_with_links(vec![
+    ///         ("/items?page=1".to_owned(), "prev".to_owned()),
+    ///         ("/items?page=3".to_owned(), "next".to_owned()),
+    ///     ])
+    /// }
+    /// ```
+    pub fn respond_with_links(
+        self,
+        links: Vec<(String, String)>,
+    ) -> Result<HttpResponse, ProtoBufPayloadError> {
+        let mut values = Vec::with_capacity(links.len());
+
+        for (url, rel) in links {
+            let valid = !url.contains('>')
+                && !rel.contains(['"', '\\'])
+                && !url.chars().chain(rel.chars()).any(char::is_control);
+
+            let link = format!("<{}>; rel=\"{}\"", url, rel);
+
+            match HeaderValue::from_str(&link) {
+                Ok(value) if valid => values.push(value),
+                _ => return Err(ProtoBufPayloadError::InvalidLink(link)),
+            }
+        }
+
+        let mut res = self.into_response();
+        for value in values {
+            res.headers_mut().append(LINK, value);
+        }
+
+        Ok(res)
+    }
+
+    /// Responds with the encoded message and the status code returned by `f`.
+    ///
+    /// `f` is called with the message before it is encoded, letting handlers choose the status
+    /// based on the response content, e.g. `201 Created` for a newly created resource and `200 OK`
+    /// for an updated one. If encoding fails, the error response is returned instead.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_protobuf::ProtoBuf;
+    /// # use actix_web::{http::StatusCode, HttpResponse};
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # struct Upserted {
+    /// #     #[prost(bool, tag = "1")]
+    /// #     created: bool,
+    /// # }
+    /// async fn index() -> HttpResponse {
+    ///     ProtoBuf(Upserted { created: true }).respond_with_computed_status(|msg| {
+    ///         if msg.created {
+    ///             StatusCode::CREATED
+    ///         } else {
+    ///             StatusCode::OK
+    ///         }
+    ///     })
+    /// }
+    /// ```
+    pub fn respond_with_computed_status<F>(self, f: F) -> HttpResponse
+    where
+        F: FnOnce(&T) -> StatusCode,
+    {
+        let status = f(&self.0);
+        let mut res = self.into_response();
+
+        if res.status().is_success() {
+            *res.status_mut() = status;
         }
+
+        res
     }
 }
 
 pub struct ProtoBufMessage<T: Message + Default> {
     limit: usize,
+    max_attempts: usize,
+    metrics: Option<Arc<ProtoBufMetrics>>,
+    payload_size_observer: Option<PayloadSizeObserver>,
+    decode_timing: Option<Arc<dyn Fn(Duration) + Send + Sync>>,
+    offload: bool,
+    grpc_framing: bool,
+    encoding: Option<CompressionAlgorithm>,
     length: Option<usize>,
     stream: Option<Payload>,
     err: Option<ProtoBufPayloadError>,
@@ -172,15 +1007,45 @@ pub struct ProtoBufMessage<T: Message + Default> {
 impl<T: Message + Default> ProtoBufMessage<T> {
     /// Create `ProtoBufMessage` for request.
     pub fn new(req: &HttpRequest, payload: &mut Payload) -> Self {
-        if req.content_type() != "application/protobuf" {
-            return ProtoBufMessage {
-                limit: 262_144,
-                length: None,
-                stream: None,
-                fut: None,
-                err: Some(ProtoBufPayloadError::ContentType),
-            };
-        }
+        let config = req.app_data::<ProtoBufConfig>();
+        let max_attempts = config.map_or(1, |c| c.max_attempts);
+        let metrics = config.and_then(|c| c.metrics.clone());
+        let payload_size_observer = config.and_then(|c| c.payload_size_observer.clone());
+        let grpc_framing = config.is_some_and(|c| c.grpc_framing);
+
+        let content_type = req.content_type();
+        let grpc = grpc_framing
+            && GRPC_CONTENT_TYPES
+                .iter()
+                .any(|accepted| accepted.eq_ignore_ascii_case(content_type));
+
+        let accepted = is_decodable_content_type(config, content_type) || grpc;
+
+        let encoding = if !accepted {
+            Err(ProtoBufPayloadError::ContentType)
+        } else {
+            payload_encoding(req, config)
+        };
+
+        let encoding = match encoding {
+            Ok(encoding) => encoding,
+            Err(err) => {
+                return ProtoBufMessage {
+                    limit: 262_144,
+                    max_attempts,
+                    metrics,
+                    payload_size_observer,
+                    decode_timing: None,
+                    offload: false,
+                    grpc_framing: grpc,
+                    encoding: None,
+                    length: None,
+                    stream: None,
+                    fut: None,
+                    err: Some(err),
+                }
+            }
+        };
 
         let mut len = None;
         if let Some(l) = req.headers().get(CONTENT_LENGTH) {
@@ -193,6 +1058,13 @@ impl<T: Message + Default> ProtoBufMessage<T> {
 
         ProtoBufMessage {
             limit: 262_144,
+            max_attempts,
+            metrics,
+            payload_size_observer,
+            decode_timing: None,
+            offload: false,
+            grpc_framing: grpc,
+            encoding,
             length: len,
             stream: Some(payload.take()),
             fut: None,
@@ -205,12 +1077,79 @@ impl<T: Message + Default> ProtoBufMessage<T> {
         self.limit = limit;
         self
     }
+
+    /// Read the payload up to `max_attempts` times when it ends prematurely. See
+    /// [`ProtoBufConfig::retry_on_incomplete`].
+    pub fn retry_on_incomplete(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Record metrics about the payload in `metrics`. See [`ProtoBufConfig::observe`].
+    pub fn observe(mut self, metrics: Arc<ProtoBufMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Call `observer` with the number of bytes read from the payload. See
+    /// [`ProtoBufConfig::observe_payload_size`].
+    pub fn observe_payload_size(mut self, observer: Arc<dyn Fn(usize) + Send + Sync>) -> Self {
+        self.payload_size_observer = Some(observer);
+        self
+    }
+
+    /// Measure the time spent decoding the payload and report it to `sink`.
+    ///
+    /// Only the call to [`Message::decode`] is timed; reading the payload is not included.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use actix_protobuf::ProtoBufMessage;
+    /// # use actix_web::{dev::Payload, HttpRequest};
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # struct MyObject {}
+    /// # fn extract(req: &HttpRequest, payload: &mut Payload) {
+    /// let msg = ProtoBufMessage::<MyObject>::new(req, payload)
+    ///     .with_decode_timing(Arc::new(|elapsed| eprintln!("decoded in {:?}", elapsed)));
+    /// # }
+    /// ```
+    pub fn with_decode_timing(mut self, sink: Arc<dyn Fn(Duration) + Send + Sync>) -> Self {
+        self.decode_timing = Some(sink);
+        self
+    }
+
+    /// Decode the payload on the blocking thread pool instead of the async executor.
+    pub(crate) fn offload(mut self) -> Self {
+        self.offload = true;
+        self
+    }
 }
 
 impl<T: Message + Default + 'static> Future for ProtoBufMessage<T> {
     type Output = Result<T, ProtoBufPayloadError>;
 
     fn poll(mut self: Pin<&mut Self>, task: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let res = ready!(self.poll_decode(task));
+
+        #[cfg(feature = "tracing")]
+        if let Err(ref err) = res {
+            tracing::warn!(error = %err, "failed to decode Protobuf payload");
+        }
+
+        if let Some(ref metrics) = self.metrics {
+            metrics.record(&res);
+        }
+
+        Poll::Ready(res)
+    }
+}
+
+impl<T: Message + Default + 'static> ProtoBufMessage<T> {
+    fn poll_decode(
+        &mut self,
+        task: &mut task::Context<'_>,
+    ) -> Poll<Result<T, ProtoBufPayloadError>> {
         if let Some(ref mut fut) = self.fut {
             return Pin::new(fut).poll(task);
         }
@@ -220,54 +1159,344 @@ impl<T: Message + Default + 'static> Future for ProtoBufMessage<T> {
         }
 
         let limit = self.limit;
-        if let Some(len) = self.length.take() {
+        let max_attempts = self.max_attempts;
+        let metrics = self.metrics.clone();
+        let payload_size_observer = self.payload_size_observer.clone();
+        let decode_timing = self.decode_timing.clone();
+        let offload = self.offload;
+        let grpc_framing = self.grpc_framing;
+        let encoding = self.encoding;
+        let length = self.length.take();
+        if let Some(len) = length {
             if len > limit {
                 return Poll::Ready(Err(ProtoBufPayloadError::Overflow));
             }
         }
+        let capacity = length.map_or(8192, |len| len.min(MAX_PREALLOCATION));
 
         let mut stream = self
             .stream
             .take()
             .expect("ProtoBufMessage could not be used second time");
 
-        self.fut = Some(
-            async move {
-                let mut body = BytesMut::with_capacity(8192);
+        let fut = async move {
+            let mut body = Body::default();
+            let mut attempt = 1;
 
-                while let Some(item) = stream.next().await {
-                    let chunk = item?;
-                    if (body.len() + chunk.len()) > limit {
-                        return Err(ProtoBufPayloadError::Overflow);
-                    } else {
-                        body.extend_from_slice(&chunk);
+            while let Some(item) = stream.next().await {
+                let chunk = match item {
+                    Ok(chunk) => chunk,
+                    Err(PayloadError::Incomplete(_)) if attempt < max_attempts => {
+                        attempt += 1;
+                        body = Body::default();
+                        continue;
                     }
+                    Err(err) => return Err(err.into()),
+                };
+
+                if body.len() + chunk.len() > limit {
+                    return Err(ProtoBufPayloadError::Overflow);
                 }
+                body.push(chunk, capacity);
+            }
+
+            if let Some(observer) = payload_size_observer {
+                observer(body.len());
+            }
+
+            let mut body = body.freeze();
 
-                Ok(<T>::decode(&mut body)?)
+            if let Some(encoding) = encoding {
+                body = decompress(&body, encoding, limit)?;
             }
-            .boxed_local(),
+
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("bytes", body.len());
+
+            if let Some(metrics) = metrics {
+                metrics.record_payload_size(body.len());
+            }
+
+            if grpc_framing {
+                strip_grpc_header(&mut body)?;
+            }
+
+            if offload {
+                return actix_web::web::block(move || {
+                    timed(decode_timing.as_deref(), || <T>::decode(body))
+                })
+                .await
+                .map_err(ProtoBufPayloadError::Blocking)?
+                .map_err(Into::into);
+            }
+
+            Ok(timed(decode_timing.as_deref(), || <T>::decode(body))?)
+        };
+
+        #[cfg(feature = "tracing")]
+        let fut = tracing::Instrument::instrument(
+            fut,
+            tracing::debug_span!(
+                "protobuf_decode",
+                message.type = std::any::type_name::<T>(),
+                bytes = tracing::field::Empty,
+            ),
         );
-        self.poll(task)
+
+        self.fut = Some(fut.boxed_local());
+        self.poll_decode(task)
+    }
+}
+
+/// Appends `chunk` to `body`, failing with [`ProtoBufPayloadError::Overflow`] if the body would
+/// exceed `limit` bytes.
+pub(crate) fn extend_within_limit(
+    body: &mut BytesMut,
+    chunk: &[u8],
+    limit: usize,
+) -> Result<(), ProtoBufPayloadError> {
+    if body.len() + chunk.len() > limit {
+        return Err(ProtoBufPayloadError::Overflow);
+    }
+
+    body.extend_from_slice(chunk);
+    Ok(())
+}
+
+/// Body of a payload being read.
+///
+/// A body received in a single chunk, which is the common case for small messages, is kept
+/// without copying. Later chunks are copied into a buffer that is pre-allocated with the given
+/// capacity.
+#[derive(Default)]
+enum Body {
+    #[default]
+    Empty,
+    Single(Bytes),
+    Buffered(BytesMut),
+}
+
+impl Body {
+    fn len(&self) -> usize {
+        match self {
+            Body::Empty => 0,
+            Body::Single(chunk) => chunk.len(),
+            Body::Buffered(buf) => buf.len(),
+        }
+    }
+
+    fn push(&mut self, chunk: Bytes, capacity: usize) {
+        *self = match std::mem::take(self) {
+            Body::Empty => Body::Single(chunk),
+            Body::Single(first) => {
+                let mut buf = BytesMut::with_capacity(capacity.max(first.len() + chunk.len()));
+                buf.extend_from_slice(&first);
+                buf.extend_from_slice(&chunk);
+                Body::Buffered(buf)
+            }
+            Body::Buffered(mut buf) => {
+                buf.extend_from_slice(&chunk);
+                Body::Buffered(buf)
+            }
+        };
+    }
+
+    fn freeze(self) -> Bytes {
+        match self {
+            Body::Empty => Bytes::new(),
+            Body::Single(chunk) => chunk,
+            Body::Buffered(buf) => buf.freeze(),
+        }
+    }
+}
+
+/// Returns the algorithm the payload of `req` is compressed with, if any.
+///
+/// Fails with [`ProtoBufPayloadError::ContentType`] if the content coding is not supported, or
+/// compressed payloads are not allowed by `config`.
+#[cfg_attr(not(feature = "compression"), allow(unused_variables))]
+fn payload_encoding(
+    req: &HttpRequest,
+    config: Option<&ProtoBufConfig>,
+) -> Result<Option<CompressionAlgorithm>, ProtoBufPayloadError> {
+    let encoding = match req.headers().get(CONTENT_ENCODING) {
+        Some(encoding) => encoding
+            .to_str()
+            .map_err(|_| ProtoBufPayloadError::ContentType)?
+            .trim(),
+        None => return Ok(None),
+    };
+
+    if encoding.eq_ignore_ascii_case("identity") {
+        return Ok(None);
+    }
+
+    #[cfg(feature = "compression")]
+    if config.is_some_and(|c| c.allow_content_encoding) {
+        if encoding.eq_ignore_ascii_case("gzip") {
+            return Ok(Some(CompressionAlgorithm::Gzip));
+        } else if encoding.eq_ignore_ascii_case("deflate") {
+            return Ok(Some(CompressionAlgorithm::Deflate));
+        }
+    }
+
+    Err(ProtoBufPayloadError::ContentType)
+}
+
+/// Decompresses `body`, failing with [`ProtoBufPayloadError::Overflow`] if the result would
+/// exceed `limit` bytes.
+#[cfg(feature = "compression")]
+fn decompress(
+    body: &[u8],
+    algorithm: CompressionAlgorithm,
+    limit: usize,
+) -> Result<Bytes, ProtoBufPayloadError> {
+    use std::io::Read as _;
+
+    use flate2::read::{GzDecoder, ZlibDecoder};
+
+    // HTTP's `deflate` coding is the zlib format, see RFC 9110 §8.4.1.2
+    let decoder: Box<dyn io::Read + '_> = match algorithm {
+        CompressionAlgorithm::Gzip => Box::new(GzDecoder::new(body)),
+        CompressionAlgorithm::Deflate => Box::new(ZlibDecoder::new(body)),
+        _ => return Err(ProtoBufPayloadError::ContentType),
+    };
+
+    let mut buf = Vec::new();
+    decoder
+        .take(limit as u64 + 1)
+        .read_to_end(&mut buf)
+        .map_err(ProtoBufPayloadError::DecompressError)?;
+
+    if buf.len() > limit {
+        return Err(ProtoBufPayloadError::Overflow);
     }
+
+    Ok(Bytes::from(buf))
+}
+
+/// Compressed payloads are rejected by [`payload_encoding`] without the `compression` feature.
+#[cfg(not(feature = "compression"))]
+fn decompress(
+    _body: &[u8],
+    _algorithm: CompressionAlgorithm,
+    _limit: usize,
+) -> Result<Bytes, ProtoBufPayloadError> {
+    Err(ProtoBufPayloadError::ContentType)
+}
+
+/// Validates and removes the 5-byte header of a gRPC message from `body`.
+fn strip_grpc_header(body: &mut Bytes) -> Result<(), ProtoBufPayloadError> {
+    if body.len() < 5 {
+        return Err(ProtoBufPayloadError::GrpcFrame);
+    }
+
+    match body[0] {
+        0 => {}
+        1 => return Err(ProtoBufPayloadError::UnsupportedCompression),
+        _ => return Err(ProtoBufPayloadError::GrpcFrame),
+    }
+
+    let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+    if len != body.len() - 5 {
+        return Err(ProtoBufPayloadError::GrpcFrame);
+    }
+
+    let _ = body.split_to(5);
+    Ok(())
+}
+
+/// Returns whether `content_type` is accepted by `config`, or is one of the default content types.
+pub(crate) fn is_accepted_content_type(
+    config: Option<&ProtoBufConfig>,
+    content_type: &str,
+) -> bool {
+    match config.and_then(|c| c.content_types.as_ref()) {
+        Some(content_types) => content_types
+            .iter()
+            .any(|accepted| accepted.eq_ignore_ascii_case(content_type)),
+        None => CONTENT_TYPES
+            .iter()
+            .any(|accepted| accepted.eq_ignore_ascii_case(content_type)),
+    }
+}
+
+/// Returns whether a payload with `content_type` is decoded by the extractors: it is accepted by
+/// `config`, or it is a wildcard or missing content type and `config` allows those.
+pub(crate) fn is_decodable_content_type(
+    config: Option<&ProtoBufConfig>,
+    content_type: &str,
+) -> bool {
+    let wildcard = content_type.is_empty() || content_type == "*/*";
+
+    is_accepted_content_type(config, content_type)
+        || (wildcard && config.is_some_and(|c| c.allow_content_type_wildcard))
+}
+
+/// Runs `decode`, reporting the time it took to `sink` (if any).
+fn timed<R>(sink: Option<&(dyn Fn(Duration) + Send + Sync)>, decode: impl FnOnce() -> R) -> R {
+    let start = Instant::now();
+    let res = decode();
+
+    if let Some(sink) = sink {
+        sink(start.elapsed());
+    }
+
+    res
 }
 
 pub trait ProtoBufResponseBuilder {
     fn protobuf<T: Message>(&mut self, value: T) -> Result<HttpResponse, Error>;
+
+    /// Like [`protobuf`](Self::protobuf), but uses the response content type set by
+    /// [`ProtoBufConfig::response_content_type`].
+    fn protobuf_with_config<T: Message>(
+        &mut self,
+        value: T,
+        config: &ProtoBufConfig,
+    ) -> Result<HttpResponse, Error>;
 }
 
 impl ProtoBufResponseBuilder for HttpResponseBuilder {
     fn protobuf<T: Message>(&mut self, value: T) -> Result<HttpResponse, Error> {
-        self.insert_header((CONTENT_TYPE, "application/protobuf"));
+        encode_response(self, value, "application/protobuf")
+    }
 
-        let mut body = Vec::new();
-        value
-            .encode(&mut body)
-            .map_err(ProtoBufPayloadError::Serialize)?;
-        Ok(self.body(body))
+    fn protobuf_with_config<T: Message>(
+        &mut self,
+        value: T,
+        config: &ProtoBufConfig,
+    ) -> Result<HttpResponse, Error> {
+        match &config.response_content_type {
+            Some(content_type) => encode_response(self, value, content_type.as_ref()),
+            None => self.protobuf(value),
+        }
     }
 }
 
+fn encode_response<T: Message>(
+    builder: &mut HttpResponseBuilder,
+    value: T,
+    content_type: &str,
+) -> Result<HttpResponse, Error> {
+    builder.insert_header((CONTENT_TYPE, content_type));
+
+    let mut body = Vec::with_capacity(value.encoded_len());
+    value.encode(&mut body).map_err(|err| {
+        let err = ProtoBufPayloadError::Serialize(err);
+
+        #[cfg(feature = "tracing")]
+        tracing::warn!(error = %err, "failed to encode Protobuf response");
+
+        err
+    })?;
+
+    #[cfg(feature = "tracing")]
+    tracing::trace!(bytes = body.len(), "encoded Protobuf response");
+
+    Ok(builder.body(body))
+}
+
 #[cfg(test)]
 mod tests {
     use actix_web::http::header;
@@ -297,6 +1526,78 @@ mod tests {
         pub name: String,
     }
 
+    #[test]
+    fn test_error_source() {
+        use std::error::Error as _;
+
+        let mut buf = [0u8; 0];
+        let encode_err = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        }
+        .encode(&mut &mut buf[..])
+        .unwrap_err();
+        let err = ProtoBufPayloadError::Serialize(encode_err);
+        assert!(err.source().unwrap().is::<ProtoBufEncodeError>());
+
+        let decode_err = MyObject::decode(&[0x08][..]).unwrap_err();
+        let err = ProtoBufPayloadError::Deserialize(decode_err);
+        assert!(err.source().unwrap().is::<ProtoBufDecodeError>());
+
+        let err = ProtoBufPayloadError::Payload(PayloadError::Overflow);
+        assert!(err.source().unwrap().is::<PayloadError>());
+
+        let err = ProtoBufPayloadError::Io(io::Error::new(io::ErrorKind::Other, "test"));
+        assert!(err.source().unwrap().is::<io::Error>());
+
+        assert!(ProtoBufPayloadError::Overflow.source().is_none());
+        assert!(ProtoBufPayloadError::ContentType.source().is_none());
+    }
+
+    #[test]
+    fn test_error_status_code() {
+        let mut buf = [0u8; 0];
+        let encode_err = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        }
+        .encode(&mut &mut buf[..])
+        .unwrap_err();
+        let decode_err = MyObject::decode(&[0x08][..]).unwrap_err();
+
+        let cases = [
+            (
+                ProtoBufPayloadError::Overflow,
+                StatusCode::PAYLOAD_TOO_LARGE,
+            ),
+            (
+                ProtoBufPayloadError::ContentType,
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ),
+            (
+                ProtoBufPayloadError::Serialize(encode_err),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                ProtoBufPayloadError::Deserialize(decode_err),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                ProtoBufPayloadError::Payload(PayloadError::Overflow),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                ProtoBufPayloadError::Io(io::Error::new(io::ErrorKind::Other, "test")),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ];
+
+        for (err, status) in cases {
+            assert_eq!(err.status_code(), status, "{}", err);
+            assert_eq!(err.error_response().status(), status, "{}", err);
+        }
+    }
+
     #[actix_web::test]
     async fn test_protobuf() {
         let protobuf = ProtoBuf(MyObject {
@@ -309,6 +1610,54 @@ mod tests {
         assert_eq!(ct, "application/protobuf");
     }
 
+    #[actix_web::test]
+    async fn test_protobuf_content_length() {
+        use actix_web::body::BodySize;
+
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+        let size = BodySize::Sized(msg.encoded_len() as u64);
+
+        let req = TestRequest::default().to_http_request();
+        let res = ProtoBuf(msg.clone()).respond_to(&req);
+        assert_eq!(res.body().size(), size);
+
+        let mut config = ProtoBufConfig::default();
+        config.response_content_type("application/vnd.google.protobuf".parse().unwrap());
+        let req = TestRequest::default().app_data(config).to_http_request();
+        let res = ProtoBuf(msg.clone()).respond_to(&req);
+        assert_eq!(res.body().size(), size);
+
+        let res = HttpResponse::Ok().protobuf(msg).unwrap();
+        assert_eq!(res.body().size(), size);
+    }
+
+    #[test]
+    fn test_body_chunks() {
+        let mut body = Body::default();
+        body.push(Bytes::from_static(b"ab"), 16);
+        assert!(matches!(body, Body::Single(_)));
+
+        body.push(Bytes::from_static(b"cd"), 16);
+        body.push(Bytes::from_static(b"ef"), 16);
+        match &body {
+            Body::Buffered(buf) => assert!(buf.capacity() >= 16),
+            _ => panic!("expected buffered body"),
+        }
+        assert_eq!(body.len(), 6);
+        assert_eq!(body.freeze(), "abcdef");
+    }
+
+    #[test]
+    fn test_content_type_header() {
+        assert_eq!(
+            ProtoBuf::<MyObject>::content_type_header(),
+            "application/protobuf"
+        );
+    }
+
     #[actix_web::test]
     async fn test_protobuf_message() {
         let (req, mut pl) = TestRequest::default().to_http_parts();
@@ -330,4 +1679,576 @@ mod tests {
             .await;
         assert_eq!(protobuf.err().unwrap(), ProtoBufPayloadError::Overflow);
     }
+
+    #[actix_web::test]
+    async fn test_send_chunked() {
+        let mut res = HttpResponse::Ok().finish();
+
+        ProtoBuf(MyObject {
+            number: 1,
+            name: "first".to_owned(),
+        })
+        .send_chunked(&mut res);
+        ProtoBuf(MyObject {
+            number: 2,
+            name: "second".to_owned(),
+        })
+        .send_chunked(&mut res);
+
+        let mut body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        let first = MyObject::decode_length_delimited(&mut body).unwrap();
+        let second = MyObject::decode_length_delimited(&mut body).unwrap();
+
+        assert_eq!(first.number, 1);
+        assert_eq!(first.name, "first");
+        assert_eq!(second.number, 2);
+        assert_eq!(second.name, "second");
+        assert!(body.is_empty());
+
+        let mut res = HttpResponse::Ok().finish();
+        for number in 0..1000 {
+            ProtoBuf(MyObject {
+                number,
+                name: String::new(),
+            })
+            .send_chunked(&mut res);
+        }
+
+        let mut body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        for number in 0..1000 {
+            let msg = MyObject::decode_length_delimited(&mut body).unwrap();
+            assert_eq!(msg.number, number);
+        }
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn test_into_web_response_with_vary() {
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let res = ProtoBuf(msg.clone()).into_web_response_with_vary(&[]);
+        assert_eq!(
+            res.headers().get(header::VARY).unwrap(),
+            "accept, content-type"
+        );
+
+        let res = ProtoBuf(msg).into_web_response_with_vary(&[header::ACCEPT_ENCODING]);
+        assert_eq!(res.headers().get(header::VARY).unwrap(), "accept-encoding");
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/protobuf"
+        );
+    }
+
+    #[test]
+    fn test_respond_with_links() {
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let res = ProtoBuf(msg.clone())
+            .respond_with_links(vec![
+                ("/items?page=1".to_owned(), "prev".to_owned()),
+                ("/items?page=3".to_owned(), "next".to_owned()),
+            ])
+            .unwrap();
+
+        let links = res.headers().get_all(header::LINK).collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            [
+                r#"</items?page=1>; rel="prev""#,
+                r#"</items?page=3>; rel="next""#
+            ]
+        );
+
+        let invalid = [
+            ("/invalid\n".to_owned(), "self".to_owned()),
+            ("/invalid\r".to_owned(), "self".to_owned()),
+            ("/a>; rel=\"admin".to_owned(), "self".to_owned()),
+            ("/items".to_owned(), "self\"; title=\"injected".to_owned()),
+        ];
+
+        for link in invalid {
+            let err = ProtoBuf(msg.clone())
+                .respond_with_links(vec![link])
+                .unwrap_err();
+            assert!(matches!(err, ProtoBufPayloadError::InvalidLink(_)));
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_respond_with_computed_status() {
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let res = ProtoBuf(msg.clone()).respond_with_computed_status(|msg| {
+            if msg.number > 0 {
+                StatusCode::CREATED
+            } else {
+                StatusCode::OK
+            }
+        });
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/protobuf"
+        );
+
+        let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(MyObject::decode(body).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_try_into_domain() {
+        struct Positive(i32);
+
+        impl TryFrom<MyObject> for Positive {
+            type Error = i32;
+
+            fn try_from(obj: MyObject) -> Result<Self, Self::Error> {
+                if obj.number > 0 {
+                    Ok(Positive(obj.number))
+                } else {
+                    Err(obj.number)
+                }
+            }
+        }
+
+        let msg = ProtoBuf(MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        });
+        assert_eq!(msg.try_into_domain::<Positive>().ok().map(|p| p.0), Some(9));
+
+        let msg = ProtoBuf(MyObject {
+            number: -1,
+            name: "test".to_owned(),
+        });
+        assert_eq!(msg.try_into_domain::<Positive>().err(), Some(-1));
+    }
+
+    #[test]
+    fn test_map_into() {
+        let msg = ProtoBuf(MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        });
+
+        let mapped = msg.map_into(|obj| MyObject {
+            number: obj.number * 2,
+            name: obj.name.to_uppercase(),
+        });
+
+        assert_eq!(mapped.number, 18);
+        assert_eq!(mapped.name, "TEST");
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        #[derive(Clone, PartialEq, Eq, Message)]
+        pub struct Tagged {
+            #[prost(int32, tag = "1")]
+            pub number: i32,
+            #[prost(string, tag = "2")]
+            pub name: String,
+            #[prost(string, repeated, tag = "3")]
+            pub tags: Vec<String>,
+        }
+
+        let msg = ProtoBuf(Tagged {
+            number: 9,
+            name: "test".to_owned(),
+            tags: vec!["a".to_owned()],
+        });
+
+        let patched = msg.apply_patch(ProtoBuf(Tagged {
+            number: 10,
+            name: String::new(),
+            tags: vec!["b".to_owned()],
+        }));
+
+        assert_eq!(patched.number, 10);
+        assert_eq!(patched.name, "test");
+        assert_eq!(patched.tags, ["a", "b"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_passthrough() {
+        #[derive(Clone, PartialEq, Eq, Message, serde::Serialize, serde::Deserialize)]
+        pub struct MyMessage {
+            #[prost(int32, tag = "1")]
+            pub number: i32,
+            #[prost(string, tag = "2")]
+            pub name: String,
+        }
+
+        let msg = ProtoBuf(MyMessage {
+            number: 9,
+            name: "test".to_owned(),
+        });
+
+        let json = serde_json::to_string(&msg).unwrap();
+        assert_eq!(json, r#"{"number":9,"name":"test"}"#);
+
+        let decoded: ProtoBuf<MyMessage> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.0, msg.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_into_json() {
+        #[derive(serde::Serialize)]
+        struct Summary {
+            label: String,
+        }
+
+        impl From<MyObject> for Summary {
+            fn from(obj: MyObject) -> Self {
+                Summary {
+                    label: format!("{}: {}", obj.number, obj.name),
+                }
+            }
+        }
+
+        let msg = ProtoBuf(MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        });
+
+        let json = msg.into_json::<Summary>();
+        assert_eq!(json.label, "9: test");
+    }
+
+    #[actix_web::test]
+    async fn test_content_type_wildcard() {
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "*/*"))
+            .set_payload(msg.encode_to_vec())
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl).await;
+        assert_eq!(protobuf.err().unwrap(), ProtoBufPayloadError::ContentType);
+
+        let mut config = ProtoBufConfig::default();
+        config.allow_content_type_wildcard(true);
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "*/*"))
+            .app_data(config)
+            .set_payload(msg.encode_to_vec())
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl).await;
+        assert_eq!(protobuf.unwrap(), msg);
+
+        let mut config = ProtoBufConfig::default();
+        config.allow_content_type_wildcard(true);
+        let (req, mut pl) = TestRequest::post()
+            .app_data(config)
+            .set_payload(msg.encode_to_vec())
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl).await;
+        assert_eq!(protobuf.unwrap(), msg);
+
+        let mut config = ProtoBufConfig::default();
+        config.allow_content_type_wildcard(true);
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .app_data(config)
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl).await;
+        assert_eq!(protobuf.err().unwrap(), ProtoBufPayloadError::ContentType);
+    }
+
+    #[actix_web::test]
+    async fn test_error_handler() {
+        use actix_web::{error::InternalError, test, web, App};
+
+        let mut config = ProtoBufConfig::default();
+        config.limit(4).error_handler(|err, _req| {
+            let code = match err {
+                ProtoBufPayloadError::Overflow => "overflow",
+                _ => "invalid",
+            };
+            let res = HttpResponse::BadRequest().body(format!(r#"{{"code":"{}"}}"#, code));
+            InternalError::from_response(err, res).into()
+        });
+
+        let srv = test::init_service(App::new().app_data(config).route(
+            "/",
+            web::post().to(|msg: ProtoBuf<MyObject>| async move { msg }),
+        ))
+        .await;
+
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+        let req = test::TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .set_payload(msg.encode_to_vec())
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let body = test::read_body(res).await;
+        assert_eq!(body, r#"{"code":"overflow"}"#);
+    }
+
+    #[actix_web::test]
+    async fn test_accept_content_types() {
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let config = || {
+            let mut config = ProtoBufConfig::default();
+            config.accept_content_types(["application/x-protobuf", "application/octet-stream"]);
+            config
+        };
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/x-protobuf"))
+            .app_data(config())
+            .set_payload(msg.encode_to_vec())
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl).await;
+        assert_eq!(protobuf.unwrap(), msg);
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((
+                header::CONTENT_TYPE,
+                "Application/Octet-Stream; charset=utf-8",
+            ))
+            .app_data(config())
+            .set_payload(msg.encode_to_vec())
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl).await;
+        assert_eq!(protobuf.unwrap(), msg);
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .app_data(config())
+            .set_payload(msg.encode_to_vec())
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl).await;
+        assert_eq!(protobuf.err().unwrap(), ProtoBufPayloadError::ContentType);
+    }
+
+    #[actix_web::test]
+    async fn test_vnd_google_protobuf() {
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/vnd.google.protobuf"))
+            .set_payload(msg.encode_to_vec())
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl).await;
+        assert_eq!(protobuf.unwrap(), msg);
+
+        let mut config = ProtoBufConfig::default();
+        config.response_content_type("application/vnd.google.protobuf".parse().unwrap());
+
+        let res = HttpResponse::Ok()
+            .protobuf_with_config(msg.clone(), &config)
+            .unwrap();
+        let ct = res.headers().get(header::CONTENT_TYPE).unwrap();
+        assert_eq!(ct, "application/vnd.google.protobuf");
+
+        let req = TestRequest::default().app_data(config).to_http_request();
+        let res = ProtoBuf(msg).respond_to(&req);
+        let ct = res.headers().get(header::CONTENT_TYPE).unwrap();
+        assert_eq!(ct, "application/vnd.google.protobuf");
+    }
+
+    #[actix_web::test]
+    async fn test_schema_version() {
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let req = TestRequest::default().to_http_request();
+        let res = ProtoBuf(msg.clone()).respond_to(&req);
+        assert!(!res.headers().contains_key(X_PROTOBUF_SCHEMA_VERSION));
+
+        let mut config = ProtoBufConfig::default();
+        config.schema_version("2.1.0");
+        let req = TestRequest::default().app_data(config).to_http_request();
+        let res = ProtoBuf(msg).respond_to(&req);
+        assert_eq!(
+            res.headers().get(X_PROTOBUF_SCHEMA_VERSION).unwrap(),
+            "2.1.0"
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_observe() {
+        let metrics = Arc::new(ProtoBufMetrics::default());
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .set_payload(msg.encode_to_vec())
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl)
+            .observe(Arc::clone(&metrics))
+            .await;
+        assert_eq!(protobuf.unwrap(), msg);
+
+        let mut config = ProtoBufConfig::default();
+        config.observe(Arc::clone(&metrics));
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "text/plain"))
+            .app_data(config)
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl).await;
+        assert_eq!(protobuf.err().unwrap(), ProtoBufPayloadError::ContentType);
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .set_payload(vec![0xff; 4])
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl)
+            .observe(Arc::clone(&metrics))
+            .await;
+        assert!(protobuf.is_err());
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.requests_total, 3);
+        assert_eq!(snapshot.content_type_errors_total, 1);
+        assert_eq!(snapshot.decode_errors_total, 1);
+        assert_eq!(snapshot.payload_size_count, 2);
+    }
+
+    #[actix_web::test]
+    async fn test_observe_payload_size() {
+        use std::sync::Mutex;
+
+        let sizes = Arc::new(Mutex::new(Vec::new()));
+        let observer: Arc<dyn Fn(usize) + Send + Sync> = {
+            let sizes = Arc::clone(&sizes);
+            Arc::new(move |size| sizes.lock().unwrap().push(size))
+        };
+        let config = || {
+            let mut config = ProtoBufConfig::default();
+            config.observe_payload_size(Arc::clone(&observer));
+            config
+        };
+
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .app_data(config())
+            .set_payload(msg.encode_to_vec())
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl).await;
+        assert_eq!(protobuf.unwrap(), msg);
+
+        // rejected for its content type before being read
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "text/plain"))
+            .app_data(config())
+            .set_payload(msg.encode_to_vec())
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl).await;
+        assert!(protobuf.is_err());
+
+        assert_eq!(*sizes.lock().unwrap(), vec![msg.encoded_len()]);
+    }
+
+    #[actix_web::test]
+    async fn test_with_decode_timing() {
+        use std::sync::Mutex;
+
+        let timings = Arc::new(Mutex::new(Vec::new()));
+        let sink: Arc<dyn Fn(Duration) + Send + Sync> = {
+            let timings = Arc::clone(&timings);
+            Arc::new(move |elapsed| timings.lock().unwrap().push(elapsed))
+        };
+
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .set_payload(msg.encode_to_vec())
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl)
+            .with_decode_timing(Arc::clone(&sink))
+            .await;
+        assert_eq!(protobuf.unwrap(), msg);
+        assert_eq!(timings.lock().unwrap().len(), 1);
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "text/plain"))
+            .to_http_parts();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut pl)
+            .with_decode_timing(sink)
+            .await;
+        assert!(protobuf.is_err());
+        assert_eq!(timings.lock().unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_retry_on_incomplete() {
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+        let encoded = Bytes::from(msg.encode_to_vec());
+
+        let payload = |encoded: &Bytes| -> Payload {
+            let (head, tail) = encoded.split_at(3);
+            let items = vec![
+                Ok(Bytes::copy_from_slice(head)),
+                Err(PayloadError::Incomplete(None)),
+                Ok(Bytes::copy_from_slice(head)),
+                Ok(Bytes::copy_from_slice(tail)),
+            ];
+            Payload::Stream {
+                payload: Box::pin(stream::iter(items)),
+            }
+        };
+
+        let req = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .to_http_request();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut payload(&encoded)).await;
+        assert!(matches!(
+            protobuf.unwrap_err(),
+            ProtoBufPayloadError::Payload(PayloadError::Incomplete(_))
+        ));
+
+        let mut config = ProtoBufConfig::default();
+        config.retry_on_incomplete(2);
+        let req = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .app_data(config)
+            .to_http_request();
+        let protobuf = ProtoBufMessage::<MyObject>::new(&req, &mut payload(&encoded)).await;
+        assert_eq!(protobuf.unwrap(), msg);
+    }
 }
@@ -0,0 +1,93 @@
+//! Streaming responses of length-prefixed messages.
+
+use std::{error::Error as StdError, fmt};
+
+use actix_web::{
+    body::{BodyStream, BoxBody},
+    http::header::CONTENT_TYPE,
+    web::{BufMut as _, Bytes, BytesMut},
+    HttpRequest, HttpResponse, Responder,
+};
+use futures_util::{Stream, StreamExt as _};
+use prost::Message;
+
+use crate::ProtoBuf;
+
+/// Responder streaming a sequence of messages.
+///
+/// Each message is sent as a frame consisting of its encoded length, as a 4-byte big-endian
+/// integer, followed by the encoded message. Unlike with
+/// [gRPC framing](crate::ProtoBufConfig::grpc_framing), frames carry no compression flag.
+///
+/// The response has `Content-Type: application/protobuf` and no known size, so it is sent with
+/// `Transfer-Encoding: chunked` over HTTP/1.1. If the stream yields an error, the response is
+/// aborted.
+///
+/// # Examples
+/// ```
+/// use std::convert::Infallible;
+///
+/// use actix_protobuf::ProtoBufStream;
+/// use futures_util::{stream, Stream};
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// pub struct Event {
+///     #[prost(int32, tag = "1")]
+///     pub id: i32,
+/// }
+///
+/// async fn events() -> ProtoBufStream<impl Stream<Item = Result<Event, Infallible>>> {
+///     ProtoBufStream::new(stream::iter((1..=3).map(|id| Ok(Event { id }))))
+/// }
+/// ```
+pub struct ProtoBufStream<S> {
+    stream: S,
+}
+
+impl<S> ProtoBufStream<S> {
+    /// Constructs a responder streaming the messages yielded by `stream`.
+    pub fn new(stream: S) -> Self {
+        ProtoBufStream { stream }
+    }
+}
+
+impl<S> From<S> for ProtoBufStream<S> {
+    fn from(stream: S) -> Self {
+        ProtoBufStream::new(stream)
+    }
+}
+
+impl<S> fmt::Debug for ProtoBufStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProtoBufStream").finish_non_exhaustive()
+    }
+}
+
+impl<S, T, E> Responder for ProtoBufStream<S>
+where
+    S: Stream<Item = Result<T, E>> + 'static,
+    T: Message,
+    E: Into<Box<dyn StdError>> + 'static,
+{
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse {
+        let frames = self.stream.map(|item| item.map(|msg| encode_frame(&msg)));
+
+        HttpResponse::Ok()
+            .insert_header((CONTENT_TYPE, ProtoBuf::<T>::content_type_header()))
+            .body(BodyStream::new(frames))
+    }
+}
+
+/// Encodes `msg` with a 4-byte big-endian length prefix.
+fn encode_frame<T: Message>(msg: &T) -> Bytes {
+    let len = msg.encoded_len();
+
+    let mut buf = BytesMut::with_capacity(4 + len);
+    buf.put_u32(len as u32);
+    msg.encode(&mut buf)
+        .expect("buffer has enough capacity for the message");
+
+    buf.freeze()
+}
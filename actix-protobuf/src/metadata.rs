@@ -0,0 +1,205 @@
+//! Responses carrying a message along with JSON metadata.
+
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, error::JsonPayloadError, http::header::HeaderValue, mime, HttpRequest,
+    HttpResponse, Responder,
+};
+use prost::Message;
+use serde::Serialize;
+
+use crate::{ProtoBuf, ProtoBufConfig, ProtoBufPayloadError};
+
+/// Base of the boundary separating the parts of the response.
+const BOUNDARY: &str = "actix-protobuf-metadata";
+
+/// Responder for a message along with metadata, such as a pagination token or the cost of a query.
+///
+/// Responds with a `multipart/mixed` body of two parts: the encoded message with
+/// `Content-Type: application/protobuf` (or the content type set by
+/// [`ProtoBufConfig::response_content_type`]), followed by the metadata serialized as JSON with
+/// `Content-Type: application/json`.
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::WithMetadata;
+/// use serde::Serialize;
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// pub struct Page {
+///     #[prost(string, repeated, tag = "1")]
+///     pub items: Vec<String>,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Metadata {
+///     next_page_token: Option<String>,
+/// }
+///
+/// async fn index() -> WithMetadata<Page, Metadata> {
+///     let page = Page {
+///         items: vec!["first".to_owned()],
+///     };
+///
+///     WithMetadata::new(page, Metadata { next_page_token: None })
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub struct WithMetadata<T: Message, M: Serialize> {
+    /// Message sent in the first part.
+    pub data: ProtoBuf<T>,
+
+    /// Metadata sent as JSON in the second part.
+    pub metadata: M,
+}
+
+impl<T: Message, M: Serialize> WithMetadata<T, M> {
+    /// Creates a response of `data` along with `metadata`.
+    pub fn new(data: T, metadata: M) -> Self {
+        WithMetadata {
+            data: ProtoBuf(data),
+            metadata,
+        }
+    }
+}
+
+impl<T, M> fmt::Debug for WithMetadata<T, M>
+where
+    T: Message + fmt::Debug,
+    M: Serialize + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithMetadata")
+            .field("data", &self.data.0)
+            .field("metadata", &self.metadata)
+            .finish()
+    }
+}
+
+impl<T: Message, M: Serialize> Responder for WithMetadata<T, M> {
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        let content_type = req
+            .app_data::<ProtoBufConfig>()
+            .and_then(|c| c.response_content_type.as_ref())
+            .map_or_else(ProtoBuf::<T>::content_type_header, |content_type| {
+                HeaderValue::from_str(content_type.as_ref())
+                    .unwrap_or_else(|_| ProtoBuf::<T>::content_type_header())
+            });
+
+        let mut data = Vec::with_capacity(self.data.encoded_len());
+        if let Err(err) = self.data.encode(&mut data) {
+            return HttpResponse::from_error(ProtoBufPayloadError::Serialize(err));
+        }
+
+        let metadata = match serde_json::to_vec(&self.metadata) {
+            Ok(metadata) => metadata,
+            Err(err) => return HttpResponse::from_error(JsonPayloadError::Serialize(err)),
+        };
+
+        let parts = [
+            (content_type.as_bytes(), data.as_slice()),
+            (
+                mime::APPLICATION_JSON.as_ref().as_bytes(),
+                metadata.as_slice(),
+            ),
+        ];
+        let boundary = boundary(&[&data, &metadata]);
+
+        let mut body = Vec::with_capacity(data.len() + metadata.len() + 192);
+        for (content_type, content) in parts {
+            body.extend_from_slice(b"--");
+            body.extend_from_slice(boundary.as_bytes());
+            body.extend_from_slice(b"\r\nContent-Type: ");
+            body.extend_from_slice(content_type);
+            body.extend_from_slice(b"\r\n\r\n");
+            body.extend_from_slice(content);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(b"--");
+        body.extend_from_slice(boundary.as_bytes());
+        body.extend_from_slice(b"--\r\n");
+
+        HttpResponse::Ok()
+            .content_type(format!("multipart/mixed; boundary={}", boundary))
+            .body(body)
+    }
+}
+
+/// Returns a boundary that does not occur in any of `parts`.
+fn boundary(parts: &[&[u8]]) -> String {
+    let mut boundary = BOUNDARY.to_owned();
+    let mut n = 0;
+
+    while parts.iter().any(|part| {
+        part.windows(boundary.len())
+            .any(|w| w == boundary.as_bytes())
+    }) {
+        n += 1;
+        boundary = format!("{}-{}", BOUNDARY, n);
+    }
+
+    boundary
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{body, http::header, test::TestRequest};
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+        #[prost(string, tag = "2")]
+        pub name: String,
+    }
+
+    #[derive(Serialize)]
+    struct Metadata {
+        next_page_token: &'static str,
+    }
+
+    #[actix_web::test]
+    async fn test_with_metadata() {
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+        let metadata = Metadata {
+            next_page_token: "abc",
+        };
+
+        let req = TestRequest::default().to_http_request();
+        let res = WithMetadata::new(msg.clone(), metadata).respond_to(&req);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "multipart/mixed; boundary=actix-protobuf-metadata"
+        );
+
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let mut expected = b"--actix-protobuf-metadata\r\n\
+            Content-Type: application/protobuf\r\n\r\n"
+            .to_vec();
+        expected.extend_from_slice(&msg.encode_to_vec());
+        expected.extend_from_slice(
+            b"\r\n--actix-protobuf-metadata\r\n\
+            Content-Type: application/json\r\n\r\n\
+            {\"next_page_token\":\"abc\"}\r\n\
+            --actix-protobuf-metadata--\r\n",
+        );
+        assert_eq!(body, expected);
+    }
+
+    #[test]
+    fn test_boundary() {
+        assert_eq!(boundary(&[b"abc"]), "actix-protobuf-metadata");
+        assert_eq!(
+            boundary(&[b"abc", b"--actix-protobuf-metadata"]),
+            "actix-protobuf-metadata-1"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Opt-in metrics for Protobuf payload extraction.
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::ProtoBufPayloadError;
+
+/// Upper bounds, in bytes, of the payload size histogram buckets.
+const PAYLOAD_SIZE_BUCKETS: [u64; 8] = [
+    256, 1_024, 4_096, 16_384, 65_536, 262_144, 1_048_576, 4_194_304,
+];
+
+/// Counters describing the Protobuf payloads extracted by an application.
+///
+/// Metrics are collected when registered using [`ProtoBufConfig::observe`] or
+/// [`ProtoBufMessage::observe`]. All counters are updated atomically, so a single instance can be
+/// shared between workers.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+///
+/// use actix_protobuf::{ProtoBufConfig, ProtoBufMetrics};
+/// use actix_web::{web, App, HttpResponse};
+///
+/// let metrics = Arc::new(ProtoBufMetrics::default());
+///
+/// let mut config = ProtoBufConfig::default();
+/// config.observe(Arc::clone(&metrics));
+///
+/// let app = App::new()
+///     .app_data(config)
+///     .route("/metrics", web::get().to(move || {
+///         let snapshot = metrics.snapshot();
+///         async move { HttpResponse::Ok().body(snapshot.to_string()) }
+///     }));
+/// ```
+///
+/// [`ProtoBufConfig::observe`]: crate::ProtoBufConfig::observe
+/// [`ProtoBufMessage::observe`]: crate::ProtoBufMessage::observe
+#[derive(Debug, Default)]
+pub struct ProtoBufMetrics {
+    requests_total: AtomicU64,
+    decode_errors_total: AtomicU64,
+    overflow_errors_total: AtomicU64,
+    content_type_errors_total: AtomicU64,
+    payload_size_buckets: [AtomicU64; PAYLOAD_SIZE_BUCKETS.len()],
+    payload_size_sum: AtomicU64,
+    payload_size_count: AtomicU64,
+}
+
+impl ProtoBufMetrics {
+    /// Takes a point-in-time copy of the counters.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let mut cumulative = 0;
+        let buckets = PAYLOAD_SIZE_BUCKETS
+            .iter()
+            .zip(&self.payload_size_buckets)
+            .map(|(&le, count)| {
+                cumulative += count.load(Ordering::Relaxed);
+                (le, cumulative)
+            })
+            .collect();
+
+        MetricsSnapshot {
+            requests_total: self.requests_total.load(Ordering::Relaxed),
+            decode_errors_total: self.decode_errors_total.load(Ordering::Relaxed),
+            overflow_errors_total: self.overflow_errors_total.load(Ordering::Relaxed),
+            content_type_errors_total: self.content_type_errors_total.load(Ordering::Relaxed),
+            payload_size_buckets: buckets,
+            payload_size_sum: self.payload_size_sum.load(Ordering::Relaxed),
+            payload_size_count: self.payload_size_count.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Records the outcome of an extraction.
+    pub(crate) fn record<T>(&self, res: &Result<T, ProtoBufPayloadError>) {
+        self.requests_total.fetch_add(1, Ordering::Relaxed);
+
+        let counter = match res {
+            Err(ProtoBufPayloadError::Deserialize(_)) => &self.decode_errors_total,
+            Err(ProtoBufPayloadError::Overflow) => &self.overflow_errors_total,
+            Err(ProtoBufPayloadError::ContentType) => &self.content_type_errors_total,
+            _ => return,
+        };
+
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records the size of a fully read payload.
+    pub(crate) fn record_payload_size(&self, size: usize) {
+        let size = size as u64;
+
+        if let Some(idx) = PAYLOAD_SIZE_BUCKETS.iter().position(|&le| size <= le) {
+            self.payload_size_buckets[idx].fetch_add(1, Ordering::Relaxed);
+        }
+
+        self.payload_size_sum.fetch_add(size, Ordering::Relaxed);
+        self.payload_size_count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Point-in-time copy of [`ProtoBufMetrics`].
+///
+/// The [`Display`](fmt::Display) implementation renders the metrics in the Prometheus text
+/// exposition format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Number of extracted payloads, including failed extractions.
+    pub requests_total: u64,
+
+    /// Number of payloads that could not be decoded.
+    pub decode_errors_total: u64,
+
+    /// Number of payloads exceeding the size limit.
+    pub overflow_errors_total: u64,
+
+    /// Number of payloads rejected due to their content type.
+    pub content_type_errors_total: u64,
+
+    /// Cumulative payload size histogram, as pairs of bucket upper bound (in bytes) and the number
+    /// of payloads that were at most that large.
+    pub payload_size_buckets: Vec<(u64, u64)>,
+
+    /// Total size of all fully read payloads, in bytes.
+    pub payload_size_sum: u64,
+
+    /// Number of fully read payloads.
+    pub payload_size_count: u64,
+}
+
+impl fmt::Display for MetricsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counters = [
+            ("protobuf_requests_total", self.requests_total),
+            ("protobuf_decode_errors_total", self.decode_errors_total),
+            ("protobuf_overflow_errors_total", self.overflow_errors_total),
+            (
+                "protobuf_content_type_errors_total",
+                self.content_type_errors_total,
+            ),
+        ];
+
+        for (name, value) in counters {
+            writeln!(f, "# TYPE {} counter", name)?;
+            writeln!(f, "{} {}", name, value)?;
+        }
+
+        writeln!(f, "# TYPE protobuf_payload_size_bytes histogram")?;
+        for (le, count) in &self.payload_size_buckets {
+            writeln!(
+                f,
+                "protobuf_payload_size_bytes_bucket{{le=\"{}\"}} {}",
+                le, count
+            )?;
+        }
+        writeln!(
+            f,
+            "protobuf_payload_size_bytes_bucket{{le=\"+Inf\"}} {}",
+            self.payload_size_count
+        )?;
+        writeln!(
+            f,
+            "protobuf_payload_size_bytes_sum {}",
+            self.payload_size_sum
+        )?;
+        writeln!(
+            f,
+            "protobuf_payload_size_bytes_count {}",
+            self.payload_size_count
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot() {
+        let metrics = ProtoBufMetrics::default();
+        metrics.record_payload_size(100);
+        metrics.record_payload_size(2_000);
+        metrics.record(&Ok::<_, ProtoBufPayloadError>(()));
+        metrics.record::<()>(&Err(ProtoBufPayloadError::Overflow));
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.requests_total, 2);
+        assert_eq!(snapshot.overflow_errors_total, 1);
+        assert_eq!(snapshot.decode_errors_total, 0);
+        assert_eq!(snapshot.payload_size_buckets[0], (256, 1));
+        assert_eq!(snapshot.payload_size_buckets[2], (4_096, 2));
+        assert_eq!(snapshot.payload_size_sum, 2_100);
+        assert_eq!(snapshot.payload_size_count, 2);
+
+        let text = snapshot.to_string();
+        assert!(text.contains("protobuf_requests_total 2\n"));
+        assert!(text.contains("protobuf_payload_size_bytes_bucket{le=\"1024\"} 1\n"));
+        assert!(text.contains("protobuf_payload_size_bytes_bucket{le=\"+Inf\"} 2\n"));
+    }
+}
@@ -0,0 +1,115 @@
+//! Decoding of `multipart/form-data` fields.
+
+use actix_multipart::Field;
+use actix_web::web::BytesMut;
+use futures_util::StreamExt as _;
+use prost::Message;
+
+use crate::{extend_within_limit, ProtoBuf, ProtoBufPayloadError};
+
+impl<T: Message + Default> ProtoBuf<T> {
+    /// Reads a field of a `multipart/form-data` request and decodes it as a Protobuf message.
+    ///
+    /// Fields larger than `limit` bytes are rejected with [`ProtoBufPayloadError::Overflow`]. The
+    /// content type of the field is not checked.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_multipart::Multipart;
+    /// use actix_protobuf::{ProtoBuf, ProtoBufPayloadError};
+    /// use futures_util::TryStreamExt as _;
+    ///
+    /// #[derive(Clone, PartialEq, prost::Message)]
+    /// pub struct Upload {
+    ///     #[prost(string, tag = "1")]
+    ///     pub name: String,
+    /// }
+    ///
+    /// async fn index(mut payload: Multipart) -> Result<String, actix_web::Error> {
+    ///     let mut names = Vec::new();
+    ///
+    ///     while let Some(field) = payload.try_next().await? {
+    ///         let upload = ProtoBuf::<Upload>::from_multipart_field(field, 65_536).await?;
+    ///         names.push(upload.0.name);
+    ///     }
+    ///
+    ///     Ok(names.join(", "))
+    /// }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+    pub async fn from_multipart_field(
+        mut field: Field,
+        limit: usize,
+    ) -> Result<ProtoBuf<T>, ProtoBufPayloadError> {
+        let mut body = BytesMut::with_capacity(8192);
+
+        while let Some(chunk) = field.next().await {
+            let chunk = chunk.map_err(ProtoBufPayloadError::Multipart)?;
+            extend_within_limit(&mut body, &chunk, limit)?;
+        }
+
+        Ok(ProtoBuf(T::decode(body)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_multipart::Multipart;
+    use actix_web::{
+        http::header::{self, HeaderMap, HeaderValue},
+        web::Bytes,
+    };
+    use futures_util::stream;
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+        #[prost(string, tag = "2")]
+        pub name: String,
+    }
+
+    fn multipart(content: &[u8]) -> Multipart {
+        let mut body = b"--abc\r\n\
+            Content-Disposition: form-data; name=\"msg\"\r\n\
+            Content-Type: application/protobuf\r\n\r\n"
+            .to_vec();
+        body.extend_from_slice(content);
+        body.extend_from_slice(b"\r\n--abc--\r\n");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("multipart/form-data; boundary=abc"),
+        );
+
+        let payload = stream::iter(vec![Ok(Bytes::from(body))]);
+        Multipart::new(&headers, payload)
+    }
+
+    #[actix_web::test]
+    async fn test_from_multipart_field() {
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+        let encoded = msg.encode_to_vec();
+
+        // the field must be read while the multipart stream is alive
+        let mut payload = multipart(&encoded);
+        let field = payload.next().await.unwrap().unwrap();
+        let decoded = ProtoBuf::<MyObject>::from_multipart_field(field, 1024)
+            .await
+            .unwrap();
+        assert_eq!(decoded.0, msg);
+
+        let mut payload = multipart(&encoded);
+        let field = payload.next().await.unwrap().unwrap();
+        let err = ProtoBuf::<MyObject>::from_multipart_field(field, 4)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProtoBufPayloadError::Overflow));
+    }
+}
@@ -0,0 +1,129 @@
+//! Extractor decoding large messages on the blocking thread pool.
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use actix_web::{dev::Payload, Error, FromRequest, HttpRequest};
+use futures_util::future::{FutureExt as _, LocalBoxFuture};
+use prost::Message;
+
+use crate::{ProtoBuf, ProtoBufConfig, ProtoBufMessage};
+
+/// Protobuf extractor that decodes the message off the async executor.
+///
+/// The request body is read as usual, but decoding it is done on the blocking thread pool (see
+/// [`web::block`](actix_web::web::block)). Decoding very large messages (several megabytes) is
+/// CPU-intensive and would otherwise stall all other requests handled by the same worker.
+///
+/// For small messages, the overhead of moving the work to another thread outweighs the benefit;
+/// use [`ProtoBuf`] instead. Remember to raise the [payload limit](ProtoBufConfig::limit) to allow
+/// large messages.
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::{ProtoBuf, ProtoBufOffloaded};
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// pub struct Dataset {
+///     #[prost(bytes = "vec", tag = "1")]
+///     pub data: Vec<u8>,
+/// }
+///
+/// async fn index(msg: ProtoBufOffloaded<Dataset>) -> String {
+///     let msg: ProtoBuf<Dataset> = msg.into_inner();
+///     format!("Received {} bytes", msg.data.len())
+/// }
+/// ```
+pub struct ProtoBufOffloaded<T: Message>(pub ProtoBuf<T>);
+
+impl<T: Message> ProtoBufOffloaded<T> {
+    /// Unwraps into the decoded [`ProtoBuf`] message.
+    pub fn into_inner(self) -> ProtoBuf<T> {
+        self.0
+    }
+}
+
+impl<T: Message> Deref for ProtoBufOffloaded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Message> DerefMut for ProtoBufOffloaded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Message> fmt::Debug for ProtoBufOffloaded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ProtoBufOffloaded: {:?}", *self.0)
+    }
+}
+
+impl<T> FromRequest for ProtoBufOffloaded<T>
+where
+    T: Message + Default + 'static,
+{
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let limit = req
+            .app_data::<ProtoBufConfig>()
+            .map(|c| c.limit)
+            .unwrap_or(262_144);
+        ProtoBufMessage::new(req, payload)
+            .limit(limit)
+            .offload()
+            .map(move |res| match res {
+                Err(e) => Err(e.into()),
+                Ok(item) => Ok(ProtoBufOffloaded(ProtoBuf(item))),
+            })
+            .boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header, test::TestRequest};
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+        #[prost(string, tag = "2")]
+        pub name: String,
+    }
+
+    #[actix_web::test]
+    async fn test_offloaded() {
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .set_payload(msg.encode_to_vec())
+            .to_http_parts();
+        let offloaded = ProtoBufOffloaded::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(offloaded.into_inner().0, msg);
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .set_payload(vec![0xff; 4])
+            .to_http_parts();
+        let res = ProtoBufOffloaded::<MyObject>::from_request(&req, &mut pl).await;
+        assert_eq!(res.unwrap_err().as_response_error().status_code(), 400);
+    }
+}
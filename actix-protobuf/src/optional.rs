@@ -0,0 +1,166 @@
+//! Extractor for requests with an optional Protobuf body.
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use actix_web::{
+    dev::Payload,
+    http::header::{CONTENT_LENGTH, CONTENT_TYPE, TRANSFER_ENCODING},
+    Error, FromRequest, HttpRequest,
+};
+use futures_util::future::{self, FutureExt as _, LocalBoxFuture};
+use prost::Message;
+
+use crate::ProtoBuf;
+
+/// Protobuf extractor for requests that may have no body, e.g. `GET` or `DELETE` requests.
+///
+/// Extracts `None` if the request has no body, i.e. it has neither a non-zero `Content-Length`
+/// nor a `Transfer-Encoding` header, or if it has no `Content-Type` header. Otherwise, the body is
+/// extracted like [`ProtoBuf`], and requests with an unsupported content type or a malformed body
+/// are rejected.
+///
+/// Unlike `Option<ProtoBuf<T>>`, which extracts `None` for any invalid request, this only
+/// tolerates absent bodies.
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::ProtoBufOptional;
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// pub struct DeleteOptions {
+///     #[prost(bool, tag = "1")]
+///     pub purge: bool,
+/// }
+///
+/// async fn delete(opts: ProtoBufOptional<DeleteOptions>) -> String {
+///     let purge = opts.as_ref().map_or(false, |opts| opts.purge);
+///     format!("Purge: {}", purge)
+/// }
+/// ```
+pub struct ProtoBufOptional<T: Message>(pub Option<T>);
+
+impl<T: Message> ProtoBufOptional<T> {
+    /// Unwraps into the decoded message, if the request had a body.
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T: Message> Deref for ProtoBufOptional<T> {
+    type Target = Option<T>;
+
+    fn deref(&self) -> &Option<T> {
+        &self.0
+    }
+}
+
+impl<T: Message> DerefMut for ProtoBufOptional<T> {
+    fn deref_mut(&mut self) -> &mut Option<T> {
+        &mut self.0
+    }
+}
+
+impl<T: Message> fmt::Debug for ProtoBufOptional<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ProtoBufOptional: {:?}", self.0)
+    }
+}
+
+impl<T> FromRequest for ProtoBufOptional<T>
+where
+    T: Message + Default + 'static,
+{
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        if !has_body(req) || !req.headers().contains_key(CONTENT_TYPE) {
+            return Box::pin(future::ok(ProtoBufOptional(None)));
+        }
+
+        ProtoBuf::<T>::from_request(req, payload)
+            .map(|res| res.map(|msg| ProtoBufOptional(Some(msg.0))))
+            .boxed_local()
+    }
+}
+
+/// Returns whether `req` announces a non-empty body.
+fn has_body(req: &HttpRequest) -> bool {
+    let headers = req.headers();
+
+    match headers.get(CONTENT_LENGTH) {
+        Some(len) => len
+            .to_str()
+            .ok()
+            .and_then(|len| len.parse::<usize>().ok())
+            .map_or(true, |len| len > 0),
+        None => headers.contains_key(TRANSFER_ENCODING),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+    }
+
+    #[actix_web::test]
+    async fn test_no_body() {
+        let (req, mut pl) = TestRequest::delete().to_http_parts();
+        let msg = ProtoBufOptional::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert!(msg.is_none());
+
+        let (req, mut pl) = TestRequest::delete()
+            .insert_header((CONTENT_TYPE, "application/protobuf"))
+            .insert_header((CONTENT_LENGTH, "0"))
+            .to_http_parts();
+        let msg = ProtoBufOptional::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert!(msg.is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_body() {
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((CONTENT_TYPE, "application/protobuf"))
+            .set_payload(MyObject { number: 9 }.encode_to_vec())
+            .to_http_parts();
+        let msg = ProtoBufOptional::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(msg.into_inner(), Some(MyObject { number: 9 }));
+    }
+
+    #[actix_web::test]
+    async fn test_wrong_content_type() {
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((CONTENT_TYPE, "application/json"))
+            .set_payload(MyObject { number: 9 }.encode_to_vec())
+            .to_http_parts();
+        let res = ProtoBufOptional::<MyObject>::from_request(&req, &mut pl).await;
+        assert!(res.is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_malformed_body() {
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((CONTENT_TYPE, "application/protobuf"))
+            .set_payload(vec![0x08])
+            .to_http_parts();
+        let res = ProtoBufOptional::<MyObject>::from_request(&req, &mut pl).await;
+        assert!(res.is_err());
+    }
+}
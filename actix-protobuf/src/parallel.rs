@@ -0,0 +1,73 @@
+//! Parallel decoding of Protobuf messages.
+
+use actix_web::web::Bytes;
+use prost::Message;
+use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
+
+use crate::{ProtoBuf, ProtoBufPayloadError};
+
+impl<T> ProtoBuf<T>
+where
+    T: Message + Default + Send,
+{
+    /// Decodes a batch of encoded messages in parallel, using the global [rayon] thread pool.
+    ///
+    /// Results are returned in the same order as `messages`. This is a blocking operation; when
+    /// called from within a request handler, consider running it using
+    /// [`web::block`](actix_web::web::block).
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_protobuf::ProtoBuf;
+    /// use actix_web::web::Bytes;
+    /// use prost::Message;
+    ///
+    /// #[derive(Clone, PartialEq, Message)]
+    /// struct Event {
+    ///     #[prost(int32, tag = "1")]
+    ///     id: i32,
+    /// }
+    ///
+    /// let messages = vec![Bytes::from_static(&[8, 1]), Bytes::from_static(&[8, 2])];
+    /// let events = ProtoBuf::<Event>::decode_batch(messages);
+    /// assert_eq!(events[1].as_ref().unwrap().id, 2);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    pub fn decode_batch(messages: Vec<Bytes>) -> Vec<Result<T, ProtoBufPayloadError>> {
+        messages
+            .into_par_iter()
+            .map(|msg| T::decode(msg).map_err(ProtoBufPayloadError::Deserialize))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+    }
+
+    #[test]
+    fn test_decode_batch() {
+        let messages = (0..64)
+            .map(|number| Bytes::from(MyObject { number }.encode_to_vec()))
+            .chain(Some(Bytes::from_static(&[0xff])))
+            .collect();
+
+        let decoded = ProtoBuf::<MyObject>::decode_batch(messages);
+        assert_eq!(decoded.len(), 65);
+
+        for (number, res) in (0..64).zip(&decoded) {
+            assert_eq!(res.as_ref().unwrap().number, number);
+        }
+
+        assert!(matches!(
+            decoded[64],
+            Err(ProtoBufPayloadError::Deserialize(_))
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+//! Debug redaction for messages containing sensitive data.
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use prost::Message;
+
+use crate::ProtoBuf;
+
+/// Wrapper around a Protobuf message that hides its contents from [`Debug`](fmt::Debug) output.
+///
+/// Useful for messages containing passwords, tokens or other secrets that must not end up in logs.
+/// The message is still accessible through [`Deref`] and [`into_inner`](Self::into_inner).
+///
+/// To redact only some of the fields of a message, use [`sensitive_proto!`](crate::sensitive_proto)
+/// and [`SensitiveDebug::redacted`] instead.
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::{ProtoBuf, ProtoBufSensitive};
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// pub struct Login {
+///     #[prost(string, tag = "1")]
+///     pub password: String,
+/// }
+///
+/// async fn index(msg: ProtoBuf<Login>) -> &'static str {
+///     let msg = ProtoBufSensitive::from(msg);
+///     assert_eq!(format!("{:?}", msg), "ProtoBuf { <redacted> }");
+///     "ok"
+/// }
+/// ```
+pub struct ProtoBufSensitive<T: Message>(pub T);
+
+impl<T: Message> ProtoBufSensitive<T> {
+    /// Unwraps into the inner message.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Message> Deref for ProtoBufSensitive<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Message> DerefMut for ProtoBufSensitive<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Message> From<ProtoBuf<T>> for ProtoBufSensitive<T> {
+    fn from(msg: ProtoBuf<T>) -> Self {
+        ProtoBufSensitive(msg.0)
+    }
+}
+
+impl<T: Message> fmt::Debug for ProtoBufSensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProtoBuf { <redacted> }")
+    }
+}
+
+/// Message whose [`Debug`](fmt::Debug) output can be redacted, implemented using
+/// [`sensitive_proto!`](crate::sensitive_proto).
+///
+/// `prost`'s `Message` derive implements `Debug` itself, printing every field. Use
+/// [`redacted`](Self::redacted) to print the message with its sensitive fields hidden instead.
+pub trait SensitiveDebug {
+    /// Formats the message, printing sensitive fields as `<redacted>`.
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// Returns a view of the message whose `Debug` implementation redacts sensitive fields.
+    fn redacted(&self) -> Redacted<'_, Self> {
+        Redacted(self)
+    }
+}
+
+/// View of a message whose [`Debug`](fmt::Debug) output hides its sensitive fields, returned by
+/// [`SensitiveDebug::redacted`].
+pub struct Redacted<'a, T: ?Sized>(&'a T);
+
+impl<T: SensitiveDebug + ?Sized> fmt::Debug for Redacted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_redacted(f)
+    }
+}
+
+/// Implements [`SensitiveDebug`] for a message, redacting the fields marked `#[redact]`.
+///
+/// All fields of the message must be listed. Redacted fields are printed as `<redacted>`.
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::{sensitive_proto, SensitiveDebug as _};
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// pub struct Login {
+///     #[prost(string, tag = "1")]
+///     pub username: String,
+///     #[prost(string, tag = "2")]
+///     pub password: String,
+/// }
+///
+/// sensitive_proto! {
+///     Login {
+///         username,
+///         #[redact]
+///         password,
+///     }
+/// }
+///
+/// let login = Login {
+///     username: "alice".to_owned(),
+///     password: "hunter2".to_owned(),
+/// };
+///
+/// assert_eq!(
+///     format!("{:?}", login.redacted()),
+///     r#"Login { username: "alice", password: <redacted> }"#,
+/// );
+/// ```
+#[macro_export]
+macro_rules! sensitive_proto {
+    ($ty:ident { $( $(#[$attr:ident])? $field:ident ),* $(,)? }) => {
+        impl $crate::SensitiveDebug for $ty {
+            fn fmt_redacted(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let mut dbg = f.debug_struct(stringify!($ty));
+                $( $crate::sensitive_proto!(@field dbg, self, $field $(, $attr)?); )*
+                dbg.finish()
+            }
+        }
+    };
+
+    (@field $dbg:ident, $this:ident, $field:ident) => {
+        $dbg.field(stringify!($field), &$this.$field);
+    };
+
+    (@field $dbg:ident, $this:ident, $field:ident, redact) => {
+        $dbg.field(stringify!($field), &format_args!("<redacted>"));
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Credentials {
+        #[prost(string, tag = "1")]
+        pub token: String,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Account {
+        #[prost(int32, tag = "1")]
+        pub id: i32,
+        #[prost(string, tag = "2")]
+        pub name: String,
+        #[prost(string, tag = "3")]
+        pub secret: String,
+    }
+
+    sensitive_proto! {
+        Account {
+            id,
+            name,
+            #[redact]
+            secret,
+        }
+    }
+
+    #[test]
+    fn test_sensitive_wrapper() {
+        let msg = ProtoBufSensitive::from(ProtoBuf(Credentials {
+            token: "secret".to_owned(),
+        }));
+
+        assert_eq!(format!("{:?}", msg), "ProtoBuf { <redacted> }");
+        assert_eq!(msg.token, "secret");
+    }
+
+    #[test]
+    fn test_sensitive_proto_macro() {
+        let account = Account {
+            id: 1,
+            name: "alice".to_owned(),
+            secret: "hunter2".to_owned(),
+        };
+
+        assert_eq!(
+            format!("{:?}", account.redacted()),
+            r#"Account { id: 1, name: "alice", secret: <redacted> }"#
+        );
+        assert!(format!("{:?}", account).contains("hunter2"));
+    }
+}
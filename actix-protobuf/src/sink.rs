@@ -0,0 +1,96 @@
+//! Forwarding of Protobuf messages to sinks.
+
+use std::error::Error as StdError;
+
+use actix_web::web::Bytes;
+use futures_util::{pin_mut, Sink, SinkExt as _};
+use prost::Message;
+
+use crate::{ProtoBuf, ProtoBufPayloadError};
+
+impl<T: Message> ProtoBuf<T> {
+    /// Encodes the message and sends it into `sink`, e.g. a message queue producer.
+    ///
+    /// The returned future waits until the sink is ready to accept the message and has flushed it,
+    /// so producers that apply backpressure slow down the caller instead of buffering messages
+    /// without bound. Errors reported by the sink are returned as [`ProtoBufPayloadError::Sink`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_protobuf::{ProtoBuf, ProtoBufPayloadError};
+    /// # use actix_web::web::Bytes;
+    /// # use futures_util::Sink;
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # struct Event {
+    /// #     #[prost(int32, tag = "1")]
+    /// #     id: i32,
+    /// # }
+    /// async fn publish<S>(event: ProtoBuf<Event>, producer: S) -> Result<(), ProtoBufPayloadError>
+    /// where
+    ///     S: Sink<Bytes, Error = std::io::Error>,
+    /// {
+    ///     event.pipe_to_sink(producer).await
+    /// }
+    /// ```
+    pub async fn pipe_to_sink<S, E>(self, sink: S) -> Result<(), ProtoBufPayloadError>
+    where
+        S: Sink<Bytes, Error = E>,
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        let msg = Bytes::from(self.0.encode_to_vec());
+
+        pin_mut!(sink);
+        sink.send(msg)
+            .await
+            .map_err(|err| ProtoBufPayloadError::Sink(err.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, convert::Infallible, io};
+
+    use futures_util::{future, sink};
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+    }
+
+    #[actix_web::test]
+    async fn test_pipe_to_sink() {
+        let received = RefCell::new(Vec::new());
+        let sink = sink::unfold((), |(), msg: Bytes| {
+            received.borrow_mut().push(msg);
+            future::ok::<_, Infallible>(())
+        });
+
+        ProtoBuf(MyObject { number: 9 })
+            .pipe_to_sink(sink)
+            .await
+            .unwrap();
+
+        let received = received.into_inner();
+        assert_eq!(received.len(), 1);
+        assert_eq!(
+            MyObject::decode(received[0].clone()).unwrap(),
+            MyObject { number: 9 }
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_pipe_to_sink_error() {
+        let sink = sink::unfold((), |(), _msg: Bytes| {
+            future::err::<(), _>(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+        });
+
+        let err = ProtoBuf(MyObject { number: 9 })
+            .pipe_to_sink(sink)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProtoBufPayloadError::Sink(_)));
+    }
+}
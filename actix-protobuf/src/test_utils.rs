@@ -0,0 +1,84 @@
+//! Helpers for testing handlers that extract Protobuf payloads.
+
+use actix_web::{
+    http::header::{CONTENT_LENGTH, CONTENT_TYPE},
+    test::TestRequest,
+};
+use prost::Message;
+
+/// Extension trait for building test requests with Protobuf payloads.
+#[cfg_attr(docsrs, doc(cfg(feature = "test-utils")))]
+pub trait TestRequestProtoBufExt {
+    /// Encodes `msg` and sets it as the payload, along with the `Content-Type: application/protobuf`
+    /// and `Content-Length` headers.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_protobuf::{ProtoBuf, TestRequestProtoBufExt as _};
+    /// use actix_web::{test::TestRequest, FromRequest as _};
+    ///
+    /// #[derive(Clone, PartialEq, prost::Message)]
+    /// pub struct MyObject {
+    ///     #[prost(int32, tag = "1")]
+    ///     pub number: i32,
+    /// }
+    ///
+    /// # actix_web::rt::System::new().block_on(async {
+    /// let (req, mut payload) = TestRequest::post()
+    ///     .set_protobuf_payload(MyObject { number: 9 })
+    ///     .to_http_parts();
+    ///
+    /// let msg = ProtoBuf::<MyObject>::from_request(&req, &mut payload).await.unwrap();
+    /// assert_eq!(msg.number, 9);
+    /// # });
+    /// ```
+    fn set_protobuf_payload<T: Message>(self, msg: T) -> TestRequest;
+}
+
+impl TestRequestProtoBufExt for TestRequest {
+    fn set_protobuf_payload<T: Message>(self, msg: T) -> TestRequest {
+        let body = msg.encode_to_vec();
+
+        self.insert_header((CONTENT_TYPE, "application/protobuf"))
+            .insert_header((CONTENT_LENGTH, body.len()))
+            .set_payload(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{FromRequest as _, HttpMessage as _};
+
+    use super::*;
+    use crate::ProtoBuf;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+        #[prost(string, tag = "2")]
+        pub name: String,
+    }
+
+    #[actix_web::test]
+    async fn test_set_protobuf_payload() {
+        let msg = MyObject {
+            number: 9,
+            name: "test".to_owned(),
+        };
+
+        let (req, mut pl) = TestRequest::post()
+            .set_protobuf_payload(msg.clone())
+            .to_http_parts();
+        assert_eq!(req.content_type(), "application/protobuf");
+        assert_eq!(
+            req.headers().get(CONTENT_LENGTH).unwrap(),
+            msg.encoded_len().to_string().as_str()
+        );
+
+        let decoded = ProtoBuf::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(decoded.0, msg);
+    }
+}
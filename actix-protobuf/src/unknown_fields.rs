@@ -0,0 +1,177 @@
+//! Decoding of messages while retaining fields unknown to the message type.
+
+use std::collections::HashMap;
+
+use actix_web::web::Bytes;
+use prost::{
+    bytes::Buf as _,
+    encoding::{self, DecodeContext, WireType},
+    Message,
+};
+
+use crate::{ProtoBuf, ProtoBufPayloadError};
+
+/// A field that was present on the wire but is not declared by the decoded message type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField {
+    /// Field number.
+    pub tag: u32,
+
+    /// Encoded field, including its key, exactly as received.
+    pub bytes: Bytes,
+}
+
+impl<T: Message + Default> ProtoBuf<T> {
+    /// Decodes a message and returns the fields that `T` does not declare.
+    ///
+    /// `prost` silently discards unknown fields when decoding. They usually indicate that the
+    /// sender uses a newer (or a different) schema, so this allows logging a warning instead of
+    /// either ignoring them or rejecting the message. Unknown fields are returned in the order they
+    /// were received; a repeated unknown field is returned once per occurrence.
+    ///
+    /// Since `T` does not describe its fields at runtime, whether a field is declared is determined
+    /// by decoding an empty group with its tag, which declared fields reject because of the
+    /// mismatching wire type. Declared proto2 group fields accept it, so they are returned as
+    /// unknown fields.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_protobuf::ProtoBuf;
+    /// use actix_web::web::Bytes;
+    /// use prost::Message as _;
+    ///
+    /// #[derive(Clone, PartialEq, prost::Message)]
+    /// pub struct Event {
+    ///     #[prost(int32, tag = "1")]
+    ///     pub id: i32,
+    /// }
+    /// # #[derive(Clone, PartialEq, prost::Message)]
+    /// # pub struct EventV2 {
+    /// #     #[prost(int32, tag = "1")]
+    /// #     pub id: i32,
+    /// #     #[prost(string, tag = "2")]
+    /// #     pub source: String,
+    /// # }
+    /// # let bytes = Bytes::from(EventV2 { id: 1, source: "sensor".to_owned() }.encode_to_vec());
+    ///
+    /// let (event, unknown) = ProtoBuf::<Event>::decode_with_unknown_fields(bytes)?;
+    /// assert_eq!(event.id, 1);
+    ///
+    /// // the sender uses a newer schema, with a `source` field
+    /// let tags = unknown.iter().map(|field| field.tag).collect::<Vec<_>>();
+    /// assert_eq!(tags, [2]);
+    /// # Ok::<_, actix_protobuf::ProtoBufPayloadError>(())
+    /// ```
+    pub fn decode_with_unknown_fields(
+        bytes: Bytes,
+    ) -> Result<(T, Vec<UnknownField>), ProtoBufPayloadError> {
+        let msg = T::decode(bytes.clone())?;
+
+        let mut unknown = Vec::new();
+        let mut known_tags = HashMap::new();
+        let mut buf = bytes.clone();
+
+        while buf.has_remaining() {
+            let start = bytes.len() - buf.remaining();
+            let (tag, wire_type) = encoding::decode_key(&mut buf)?;
+            encoding::skip_field(wire_type, tag, &mut buf, DecodeContext::default())?;
+            let end = bytes.len() - buf.remaining();
+
+            let known = *known_tags
+                .entry(tag)
+                .or_insert_with(|| is_known_field::<T>(tag));
+
+            if !known {
+                unknown.push(UnknownField {
+                    tag,
+                    bytes: bytes.slice(start..end),
+                });
+            }
+        }
+
+        Ok((msg, unknown))
+    }
+}
+
+/// Returns whether `T` declares a field with the given tag.
+///
+/// Decodes an empty group with that tag: unknown fields are skipped regardless of their wire type,
+/// while declared fields reject it because it does not match their type. Declared group fields
+/// accept it, so they are considered unknown.
+fn is_known_field<T: Message + Default>(tag: u32) -> bool {
+    let mut probe = Vec::new();
+    encoding::encode_key(tag, WireType::StartGroup, &mut probe);
+    encoding::encode_key(tag, WireType::EndGroup, &mut probe);
+
+    T::decode(probe.as_slice()).is_err()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObject {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+        #[prost(string, repeated, tag = "3")]
+        pub tags: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct MyObjectV2 {
+        #[prost(int32, tag = "1")]
+        pub number: i32,
+        #[prost(string, tag = "2")]
+        pub name: String,
+        #[prost(string, repeated, tag = "3")]
+        pub tags: Vec<String>,
+        #[prost(fixed64, tag = "4")]
+        pub id: u64,
+    }
+
+    #[test]
+    fn test_decode_with_unknown_fields() {
+        let v2 = MyObjectV2 {
+            number: 9,
+            name: "test".to_owned(),
+            tags: vec!["a".to_owned(), "b".to_owned()],
+            id: 42,
+        };
+        let bytes = Bytes::from(v2.encode_to_vec());
+
+        let (msg, unknown) = ProtoBuf::<MyObject>::decode_with_unknown_fields(bytes).unwrap();
+        assert_eq!(
+            msg,
+            MyObject {
+                number: 9,
+                tags: vec!["a".to_owned(), "b".to_owned()],
+            }
+        );
+
+        let tags = unknown.iter().map(|field| field.tag).collect::<Vec<_>>();
+        assert_eq!(tags, [2, 4]);
+
+        let name = MyObjectV2 {
+            name: "test".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(unknown[0].bytes, name.encode_to_vec());
+    }
+
+    #[test]
+    fn test_decode_without_unknown_fields() {
+        // explicitly encoded default value of a declared field
+        let bytes = Bytes::from_static(&[0x08, 0x00]);
+
+        let (msg, unknown) = ProtoBuf::<MyObject>::decode_with_unknown_fields(bytes).unwrap();
+        assert_eq!(msg, MyObject::default());
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn test_decode_with_unknown_fields_invalid() {
+        let bytes = Bytes::from_static(&[0x08]);
+        assert!(ProtoBuf::<MyObject>::decode_with_unknown_fields(bytes).is_err());
+    }
+}
@@ -0,0 +1,197 @@
+//! Extractor and responder for negotiating between two versions of a message schema.
+
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody,
+    dev::Payload,
+    error::ErrorBadRequest,
+    http::header::{HeaderName, HeaderValue},
+    Error, FromRequest, HttpRequest, HttpResponse, Responder,
+};
+use futures_util::future::{self, FutureExt as _, LocalBoxFuture};
+use prost::Message;
+
+use crate::ProtoBuf;
+
+/// Name of the header used to negotiate the schema version.
+pub const X_PROTO_VERSION: &str = "x-proto-version";
+
+/// Protobuf extractor and responder for endpoints supporting two versions of a message schema.
+///
+/// When extracting, the `X-Proto-Version` header selects the message type the body is decoded
+/// into: `1` (or a missing header) decodes `V1` and `2` decodes `V2`. Any other value is rejected
+/// with `400 Bad Request`.
+///
+/// When responding, the message is encoded and the `X-Proto-Version` header is set to the version
+/// that was used.
+///
+/// # Examples
+/// ```
+/// use actix_protobuf::ProtoBufVersioned;
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// pub struct UserV1 {
+///     #[prost(string, tag = "1")]
+///     pub name: String,
+/// }
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// pub struct UserV2 {
+///     #[prost(string, tag = "1")]
+///     pub first_name: String,
+///     #[prost(string, tag = "2")]
+///     pub last_name: String,
+/// }
+///
+/// async fn index(
+///     user: ProtoBufVersioned<UserV1, UserV2>,
+/// ) -> ProtoBufVersioned<UserV1, UserV2> {
+///     // respond using the same schema version as the client
+///     user
+/// }
+/// ```
+pub enum ProtoBufVersioned<V1: Message, V2: Message> {
+    /// Message encoded using version 1 of the schema.
+    V1(V1),
+
+    /// Message encoded using version 2 of the schema.
+    V2(V2),
+}
+
+impl<V1: Message, V2: Message> ProtoBufVersioned<V1, V2> {
+    /// Returns the schema version of the message.
+    pub fn version(&self) -> u32 {
+        match self {
+            ProtoBufVersioned::V1(_) => 1,
+            ProtoBufVersioned::V2(_) => 2,
+        }
+    }
+}
+
+impl<V1: Message, V2: Message> fmt::Debug for ProtoBufVersioned<V1, V2> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtoBufVersioned::V1(msg) => write!(f, "ProtoBufVersioned::V1: {:?}", msg),
+            ProtoBufVersioned::V2(msg) => write!(f, "ProtoBufVersioned::V2: {:?}", msg),
+        }
+    }
+}
+
+impl<V1, V2> FromRequest for ProtoBufVersioned<V1, V2>
+where
+    V1: Message + Default + 'static,
+    V2: Message + Default + 'static,
+{
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let version = req
+            .headers()
+            .get(X_PROTO_VERSION)
+            .map(|val| val.to_str().map(str::trim));
+
+        match version {
+            None | Some(Ok("1")) => ProtoBuf::<V1>::from_request(req, payload)
+                .map(|res| res.map(|ProtoBuf(msg)| ProtoBufVersioned::V1(msg)))
+                .boxed_local(),
+
+            Some(Ok("2")) => ProtoBuf::<V2>::from_request(req, payload)
+                .map(|res| res.map(|ProtoBuf(msg)| ProtoBufVersioned::V2(msg)))
+                .boxed_local(),
+
+            _ => future::err(ErrorBadRequest("Unsupported X-Proto-Version")).boxed_local(),
+        }
+    }
+}
+
+impl<V1, V2> Responder for ProtoBufVersioned<V1, V2>
+where
+    V1: Message + Default,
+    V2: Message + Default,
+{
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        let (mut res, version) = match self {
+            ProtoBufVersioned::V1(msg) => (ProtoBuf(msg).respond_binary(req), "1"),
+            ProtoBufVersioned::V2(msg) => (ProtoBuf(msg).respond_binary(req), "2"),
+        };
+
+        res.headers_mut().insert(
+            HeaderName::from_static(X_PROTO_VERSION),
+            HeaderValue::from_static(version),
+        );
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header, test::TestRequest};
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct UserV1 {
+        #[prost(string, tag = "1")]
+        pub name: String,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Message)]
+    pub struct UserV2 {
+        #[prost(string, tag = "1")]
+        pub first_name: String,
+        #[prost(string, tag = "2")]
+        pub last_name: String,
+    }
+
+    type User = ProtoBufVersioned<UserV1, UserV2>;
+
+    #[actix_web::test]
+    async fn test_versioned() {
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .set_payload(
+                UserV1 {
+                    name: "Ferris".to_owned(),
+                }
+                .encode_to_vec(),
+            )
+            .to_http_parts();
+        let user = User::from_request(&req, &mut pl).await.unwrap();
+        assert!(matches!(user, ProtoBufVersioned::V1(ref user) if user.name == "Ferris"));
+
+        let res = user.respond_to(&req);
+        assert_eq!(res.headers().get(X_PROTO_VERSION).unwrap(), "1");
+
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .insert_header((X_PROTO_VERSION, "2"))
+            .set_payload(
+                UserV2 {
+                    first_name: "Ferris".to_owned(),
+                    last_name: "Crab".to_owned(),
+                }
+                .encode_to_vec(),
+            )
+            .to_http_parts();
+        let user = User::from_request(&req, &mut pl).await.unwrap();
+        assert!(matches!(user, ProtoBufVersioned::V2(ref user) if user.last_name == "Crab"));
+
+        let res = user.respond_to(&req);
+        assert_eq!(res.headers().get(X_PROTO_VERSION).unwrap(), "2");
+    }
+
+    #[actix_web::test]
+    async fn test_unsupported_version() {
+        let (req, mut pl) = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .insert_header((X_PROTO_VERSION, "3"))
+            .to_http_parts();
+        let err = User::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(err.as_response_error().status_code(), 400);
+    }
+}
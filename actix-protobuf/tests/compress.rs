@@ -0,0 +1,71 @@
+use std::io::Read as _;
+
+use actix_protobuf::ProtoBuf;
+use actix_web::{
+    http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE},
+    middleware::Compress,
+    test, web, App,
+};
+use flate2::read::GzDecoder;
+use prost::Message;
+
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct MyObject {
+    #[prost(int32, tag = "1")]
+    pub number: i32,
+    #[prost(string, tag = "2")]
+    pub name: String,
+}
+
+fn msg() -> MyObject {
+    MyObject {
+        number: 9,
+        name: "test".repeat(100),
+    }
+}
+
+#[actix_web::test]
+async fn test_compress_gzip() {
+    let srv = test::init_service(
+        App::new()
+            .wrap(Compress::default())
+            .route("/", web::get().to(|| async { ProtoBuf(msg()) })),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .insert_header((ACCEPT_ENCODING, "gzip"))
+        .to_request();
+    let res = test::call_service(&srv, req).await;
+
+    assert!(res.status().is_success());
+    assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+    assert_eq!(
+        res.headers().get(CONTENT_TYPE).unwrap(),
+        "application/protobuf"
+    );
+
+    let body = test::read_body(res).await;
+    let mut decoded = Vec::new();
+    GzDecoder::new(&body[..]).read_to_end(&mut decoded).unwrap();
+
+    assert_eq!(MyObject::decode(&decoded[..]).unwrap(), msg());
+}
+
+#[actix_web::test]
+async fn test_compress_identity() {
+    let srv = test::init_service(
+        App::new()
+            .wrap(Compress::default())
+            .route("/", web::get().to(|| async { ProtoBuf(msg()) })),
+    )
+    .await;
+
+    let req = test::TestRequest::get().to_request();
+    let res = test::call_service(&srv, req).await;
+
+    assert!(res.headers().get(CONTENT_ENCODING).is_none());
+
+    let body = test::read_body(res).await;
+    assert_eq!(MyObject::decode(&body[..]).unwrap(), msg());
+}
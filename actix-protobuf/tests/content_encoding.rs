@@ -0,0 +1,164 @@
+use actix_protobuf::{ProtoBuf, ProtoBufConfig};
+use actix_web::{
+    http::{
+        header::{CONTENT_ENCODING, CONTENT_TYPE},
+        StatusCode,
+    },
+    test, web, App,
+};
+use prost::Message;
+
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct MyObject {
+    #[prost(int32, tag = "1")]
+    pub number: i32,
+    #[prost(string, tag = "2")]
+    pub name: String,
+}
+
+fn msg() -> MyObject {
+    MyObject {
+        number: 9,
+        name: "test".to_owned(),
+    }
+}
+
+async fn echo(msg: ProtoBuf<MyObject>) -> ProtoBuf<MyObject> {
+    msg
+}
+
+#[cfg(feature = "compression")]
+fn compress(encoding: &str, body: &[u8]) -> Vec<u8> {
+    use std::io::Write as _;
+
+    use flate2::{
+        write::{GzEncoder, ZlibEncoder},
+        Compression,
+    };
+
+    match encoding {
+        "gzip" => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(body).unwrap();
+            encoder.finish().unwrap()
+        }
+        "deflate" => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(body).unwrap();
+            encoder.finish().unwrap()
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(feature = "compression")]
+fn config(limit: usize) -> ProtoBufConfig {
+    let mut config = ProtoBufConfig::default();
+    config.limit(limit).allow_content_encoding(true);
+    config
+}
+
+#[cfg(feature = "compression")]
+#[actix_web::test]
+async fn test_content_encoding() {
+    let srv = test::init_service(
+        App::new()
+            .app_data(config(262_144))
+            .route("/", web::post().to(echo)),
+    )
+    .await;
+
+    for encoding in ["gzip", "deflate", "GZIP"] {
+        let req = test::TestRequest::post()
+            .insert_header((CONTENT_TYPE, "application/protobuf"))
+            .insert_header((CONTENT_ENCODING, encoding))
+            .set_payload(compress(
+                &encoding.to_ascii_lowercase(),
+                &msg().encode_to_vec(),
+            ))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let body = test::read_body(res).await;
+        assert_eq!(MyObject::decode(body).unwrap(), msg());
+    }
+}
+
+#[cfg(feature = "compression")]
+#[actix_web::test]
+async fn test_content_encoding_invalid() {
+    let srv = test::init_service(
+        App::new()
+            .app_data(config(262_144))
+            .route("/", web::post().to(echo)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .insert_header((CONTENT_TYPE, "application/protobuf"))
+        .insert_header((CONTENT_ENCODING, "gzip"))
+        .set_payload(msg().encode_to_vec())
+        .to_request();
+    let res = test::call_service(&srv, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .insert_header((CONTENT_TYPE, "application/protobuf"))
+        .insert_header((CONTENT_ENCODING, "br"))
+        .set_payload(msg().encode_to_vec())
+        .to_request();
+    let res = test::call_service(&srv, req).await;
+    assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[cfg(feature = "compression")]
+#[actix_web::test]
+async fn test_content_encoding_limit() {
+    let srv = test::init_service(
+        App::new()
+            .app_data(config(64))
+            .route("/", web::post().to(echo)),
+    )
+    .await;
+
+    // compresses to well below the limit
+    let large = MyObject {
+        number: 9,
+        name: "a".repeat(1024),
+    };
+
+    let req = test::TestRequest::post()
+        .insert_header((CONTENT_TYPE, "application/protobuf"))
+        .insert_header((CONTENT_ENCODING, "gzip"))
+        .set_payload(compress("gzip", &large.encode_to_vec()))
+        .to_request();
+    let res = test::call_service(&srv, req).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[actix_web::test]
+async fn test_content_encoding_not_allowed() {
+    let srv = test::init_service(
+        App::new()
+            .app_data(ProtoBufConfig::default())
+            .route("/", web::post().to(echo)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .insert_header((CONTENT_TYPE, "application/protobuf"))
+        .insert_header((CONTENT_ENCODING, "gzip"))
+        .set_payload(msg().encode_to_vec())
+        .to_request();
+    let res = test::call_service(&srv, req).await;
+    assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let req = test::TestRequest::post()
+        .insert_header((CONTENT_TYPE, "application/protobuf"))
+        .insert_header((CONTENT_ENCODING, "identity"))
+        .set_payload(msg().encode_to_vec())
+        .to_request();
+    let res = test::call_service(&srv, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
@@ -0,0 +1,132 @@
+use actix_protobuf::{ProtoBuf, ProtoBufConfig};
+use actix_web::{
+    http::{header::CONTENT_TYPE, StatusCode},
+    test, web, App,
+};
+use prost::Message;
+
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct MyObject {
+    #[prost(int32, tag = "1")]
+    pub number: i32,
+    #[prost(string, tag = "2")]
+    pub name: String,
+}
+
+fn msg() -> MyObject {
+    MyObject {
+        number: 9,
+        name: "test".to_owned(),
+    }
+}
+
+/// Encodes `msg` with a gRPC message header.
+fn grpc_frame(compressed: bool, msg: &MyObject) -> Vec<u8> {
+    let encoded = msg.encode_to_vec();
+
+    let mut frame = vec![compressed as u8];
+    frame.extend_from_slice(&(encoded.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&encoded);
+    frame
+}
+
+async fn echo(msg: ProtoBuf<MyObject>) -> ProtoBuf<MyObject> {
+    msg
+}
+
+fn config() -> ProtoBufConfig {
+    let mut config = ProtoBufConfig::default();
+    config.grpc_framing(true);
+    config
+}
+
+#[actix_web::test]
+async fn test_grpc_framing() {
+    let srv = test::init_service(
+        App::new()
+            .app_data(config())
+            .route("/", web::post().to(echo)),
+    )
+    .await;
+
+    for content_type in ["application/grpc+proto", "application/grpc"] {
+        let req = test::TestRequest::post()
+            .insert_header((CONTENT_TYPE, content_type))
+            .set_payload(grpc_frame(false, &msg()))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let body = test::read_body(res).await;
+        assert_eq!(MyObject::decode(body).unwrap(), msg());
+    }
+}
+
+#[actix_web::test]
+async fn test_grpc_framing_compressed() {
+    let srv = test::init_service(
+        App::new()
+            .app_data(config())
+            .route("/", web::post().to(echo)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .insert_header((CONTENT_TYPE, "application/grpc+proto"))
+        .set_payload(grpc_frame(true, &msg()))
+        .to_request();
+    let res = test::call_service(&srv, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_grpc_framing_length_mismatch() {
+    let srv = test::init_service(
+        App::new()
+            .app_data(config())
+            .route("/", web::post().to(echo)),
+    )
+    .await;
+
+    let mut frame = grpc_frame(false, &msg());
+    frame.truncate(frame.len() - 1);
+
+    let req = test::TestRequest::post()
+        .insert_header((CONTENT_TYPE, "application/grpc+proto"))
+        .set_payload(frame)
+        .to_request();
+    let res = test::call_service(&srv, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_grpc_content_type_requires_framing() {
+    let srv = test::init_service(App::new().route("/", web::post().to(echo))).await;
+
+    let req = test::TestRequest::post()
+        .insert_header((CONTENT_TYPE, "application/grpc+proto"))
+        .set_payload(grpc_frame(false, &msg()))
+        .to_request();
+    let res = test::call_service(&srv, req).await;
+    assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[actix_web::test]
+async fn test_grpc_framing_plain_content_type() {
+    let srv = test::init_service(
+        App::new()
+            .app_data(config())
+            .route("/", web::post().to(echo)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .insert_header((CONTENT_TYPE, "application/protobuf"))
+        .set_payload(msg().encode_to_vec())
+        .to_request();
+    let res = test::call_service(&srv, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body = test::read_body(res).await;
+    assert_eq!(MyObject::decode(body).unwrap(), msg());
+}
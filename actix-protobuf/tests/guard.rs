@@ -0,0 +1,47 @@
+use actix_protobuf::guard;
+use actix_web::{
+    guard::Header,
+    http::{header::CONTENT_TYPE, StatusCode},
+    test, web, App, HttpResponse,
+};
+
+#[actix_web::test]
+async fn test_guard_routing() {
+    let srv = test::init_service(
+        App::new().service(
+            web::resource("/")
+                .route(
+                    web::post()
+                        .guard(guard::protobuf())
+                        .to(|| async { HttpResponse::Ok().body("protobuf") }),
+                )
+                .route(
+                    web::post()
+                        .guard(Header("content-type", "application/json"))
+                        .to(|| async { HttpResponse::Ok().body("json") }),
+                ),
+        ),
+    )
+    .await;
+
+    for (content_type, route) in [
+        ("application/protobuf", "protobuf"),
+        ("application/vnd.google.protobuf", "protobuf"),
+        ("application/json", "json"),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header((CONTENT_TYPE, content_type))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(test::read_body(res).await, route);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/")
+        .insert_header((CONTENT_TYPE, "text/plain"))
+        .to_request();
+    let res = test::call_service(&srv, req).await;
+    assert!(!res.status().is_success());
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "tracing")]
+
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use actix_protobuf::ProtoBuf;
+use actix_web::{http::header::CONTENT_TYPE, test::TestRequest, FromRequest as _};
+use prost::Message;
+use tracing_subscriber::fmt::{format::FmtSpan, MakeWriter};
+
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct MyObject {
+    #[prost(int32, tag = "1")]
+    pub number: i32,
+    #[prost(string, tag = "2")]
+    pub name: String,
+}
+
+/// Collects the output of a subscriber.
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Buffer {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl io::Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Buffer {
+    type Writer = Buffer;
+
+    fn make_writer(&'a self) -> Buffer {
+        self.clone()
+    }
+}
+
+#[actix_web::test]
+async fn test_decode_span() {
+    let buffer = Buffer::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(buffer.clone())
+        .with_max_level(tracing::Level::TRACE)
+        .with_span_events(FmtSpan::CLOSE)
+        .without_time()
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let msg = MyObject {
+        number: 9,
+        name: "test".to_owned(),
+    };
+    let body = msg.encode_to_vec();
+
+    let (req, mut pl) = TestRequest::post()
+        .insert_header((CONTENT_TYPE, "application/protobuf"))
+        .set_payload(body.clone())
+        .to_http_parts();
+    let decoded = ProtoBuf::<MyObject>::from_request(&req, &mut pl)
+        .await
+        .unwrap();
+    assert_eq!(decoded.0, msg);
+
+    let output = buffer.contents();
+    assert!(output.contains("protobuf_decode{"), "{}", output);
+    assert!(
+        output.contains(r#"message.type="instrumentation::MyObject""#),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains(&format!("bytes={}", body.len())),
+        "{}",
+        output
+    );
+}
+
+#[actix_web::test]
+async fn test_decode_error() {
+    let buffer = Buffer::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(buffer.clone())
+        .without_time()
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let (req, mut pl) = TestRequest::post()
+        .insert_header((CONTENT_TYPE, "application/protobuf"))
+        .set_payload(vec![0x08])
+        .to_http_parts();
+    let res = ProtoBuf::<MyObject>::from_request(&req, &mut pl).await;
+    assert!(res.is_err());
+
+    let output = buffer.contents();
+    assert!(output.contains("WARN"), "{}", output);
+    assert!(output.contains("ProtoBuf deserialize error"), "{}", output);
+}
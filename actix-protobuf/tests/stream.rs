@@ -0,0 +1,57 @@
+use std::convert::{Infallible, TryInto as _};
+
+use actix_protobuf::ProtoBufStream;
+use actix_web::{
+    http::{header::CONTENT_TYPE, StatusCode},
+    test, web, App,
+};
+use futures_util::{stream, Stream};
+use prost::Message;
+
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct MyObject {
+    #[prost(int32, tag = "1")]
+    pub number: i32,
+    #[prost(string, tag = "2")]
+    pub name: String,
+}
+
+fn msgs() -> Vec<MyObject> {
+    (1..=3)
+        .map(|number| MyObject {
+            number,
+            name: "test".repeat(number as usize),
+        })
+        .collect()
+}
+
+async fn index() -> ProtoBufStream<impl Stream<Item = Result<MyObject, Infallible>>> {
+    ProtoBufStream::new(stream::iter(msgs().into_iter().map(Ok)))
+}
+
+#[actix_web::test]
+async fn test_stream() {
+    let srv = test::init_service(App::new().route("/", web::get().to(index))).await;
+
+    let req = test::TestRequest::get().to_request();
+    let res = test::call_service(&srv, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers().get(CONTENT_TYPE).unwrap(),
+        "application/protobuf"
+    );
+
+    let body = test::read_body(res).await;
+    let mut rest = &body[..];
+    let mut decoded = Vec::new();
+
+    while !rest.is_empty() {
+        let (len, tail) = rest.split_at(4);
+        let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+        let (frame, tail) = tail.split_at(len);
+        decoded.push(MyObject::decode(frame).unwrap());
+        rest = tail;
+    }
+
+    assert_eq!(decoded, msgs());
+}
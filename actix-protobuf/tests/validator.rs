@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use actix_protobuf::{ProtoBuf, ProtoBufConfig};
+use actix_web::{
+    error,
+    http::{header::CONTENT_TYPE, StatusCode},
+    test, web, App, HttpResponse,
+};
+use prost::Message;
+
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct MyObject {
+    #[prost(int32, tag = "1")]
+    pub number: i32,
+    #[prost(string, tag = "2")]
+    pub name: String,
+}
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+async fn index(msg: ProtoBuf<MyObject>) -> HttpResponse {
+    CALLS.fetch_add(1, Ordering::SeqCst);
+    HttpResponse::Ok().body(msg.name.clone())
+}
+
+fn config() -> ProtoBufConfig {
+    let mut config = ProtoBufConfig::default();
+    config.validator(|msg: &MyObject, _req| {
+        if msg.name.is_empty() {
+            return Err(error::ErrorUnprocessableEntity("name must not be empty"));
+        }
+        Ok(())
+    });
+    config
+}
+
+#[actix_web::test]
+async fn test_validator() {
+    let srv = test::init_service(
+        App::new()
+            .app_data(config())
+            .route("/", web::post().to(index)),
+    )
+    .await;
+
+    let call = |name: &str| {
+        let msg = MyObject {
+            number: 9,
+            name: name.to_owned(),
+        };
+        let req = test::TestRequest::post()
+            .insert_header((CONTENT_TYPE, "application/protobuf"))
+            .set_payload(msg.encode_to_vec())
+            .to_request();
+        test::call_service(&srv, req)
+    };
+
+    let res = call("").await;
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(test::read_body(res).await, "name must not be empty");
+    assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+
+    let res = call("test").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(test::read_body(res).await, "test");
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,280 @@
+//! Extractor for API keys sent in a header or query parameter.
+
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use actix_utils::future::{ready, Ready};
+use actix_web::{
+    dev::Payload, http::header::HeaderName, http::StatusCode, web::Query, Error, FromRequest,
+    HttpRequest, ResponseError,
+};
+
+type ErrorHandler = Arc<dyn Fn(ApiKeyError, &HttpRequest) -> Error + Send + Sync>;
+
+/// [`ApiKey`] extractor configuration.
+///
+/// By default, the key is read from the `X-Api-Key` header only.
+///
+/// # Examples
+/// ```
+/// use actix_web::{error, web, App};
+/// use actix_web_httpauth::extractors::api_key::{ApiKey, ApiKeyConfig};
+///
+/// async fn index(key: ApiKey) -> String {
+///     format!("Hello, client with key {}!", key.key())
+/// }
+///
+/// let app = App::new()
+///     .app_data(
+///         ApiKeyConfig::default()
+///             .query_param("api_key")
+///             .error_handler(|err, _req| error::ErrorForbidden(err)),
+///     )
+///     .route("/", web::get().to(index));
+/// ```
+#[derive(Clone)]
+pub struct ApiKeyConfig {
+    header_name: Option<HeaderName>,
+    query_param: Option<String>,
+    err_handler: Option<ErrorHandler>,
+}
+
+impl ApiKeyConfig {
+    /// Sets the header carrying the key, or disables reading the key from a header with `None`.
+    ///
+    /// Defaults to `X-Api-Key`.
+    pub fn header_name(mut self, name: impl Into<Option<HeaderName>>) -> Self {
+        self.header_name = name.into();
+        self
+    }
+
+    /// Sets the query parameter carrying the key. The header, if any, takes precedence.
+    ///
+    /// Disabled by default, since keys in URLs tend to end up in logs.
+    pub fn query_param(mut self, name: impl Into<String>) -> Self {
+        self.query_param = Some(name.into());
+        self
+    }
+
+    /// Sets a custom error handler, converting extraction errors into responses.
+    ///
+    /// By default, errors are returned as is, resulting in `401 Unauthorized` responses.
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(ApiKeyError, &HttpRequest) -> Error + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+
+    fn extract(&self, req: &HttpRequest) -> Result<ApiKey, ApiKeyError> {
+        if let Some(ref name) = self.header_name {
+            if let Some(value) = req.headers().get(name) {
+                let key = value.to_str().map_err(|_| ApiKeyError::InvalidHeader)?;
+                return Ok(ApiKey(key.to_owned()));
+            }
+        }
+
+        if let Some(ref name) = self.query_param {
+            let query = Query::<HashMap<String, String>>::from_query(req.query_string()).ok();
+            if let Some(key) = query.and_then(|mut query| query.remove(name)) {
+                return Ok(ApiKey(key));
+            }
+        }
+
+        Err(ApiKeyError::Missing)
+    }
+}
+
+impl Default for ApiKeyConfig {
+    fn default() -> Self {
+        ApiKeyConfig {
+            header_name: Some(HeaderName::from_static("x-api-key")),
+            query_param: None,
+            err_handler: None,
+        }
+    }
+}
+
+impl fmt::Debug for ApiKeyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKeyConfig")
+            .field("header_name", &self.header_name)
+            .field("query_param", &self.query_param)
+            .finish()
+    }
+}
+
+/// Errors that can occur when extracting an [`ApiKey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ApiKeyError {
+    /// Neither the header nor the query parameter is present.
+    Missing,
+
+    /// Header value is not a valid string.
+    InvalidHeader,
+}
+
+impl fmt::Display for ApiKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiKeyError::Missing => f.write_str("API key is missing"),
+            ApiKeyError::InvalidHeader => f.write_str("API key header is not a valid string"),
+        }
+    }
+}
+
+impl std::error::Error for ApiKeyError {}
+
+/// Responds with `401 Unauthorized`.
+impl ResponseError for ApiKeyError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::UNAUTHORIZED
+    }
+}
+
+/// Extractor for API keys sent in a header or query parameter.
+///
+/// The key is read from the header and query parameter set in the [`ApiKeyConfig`] registered as
+/// app data, or from the `X-Api-Key` header if there is none. Validating the key is left to the
+/// application.
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::extractors::api_key::ApiKey;
+///
+/// async fn index(key: ApiKey) -> String {
+///     format!("Hello, client with key {}!", key.key())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    /// Returns the key sent by the client.
+    pub fn key(&self) -> &str {
+        &self.0
+    }
+
+    /// Consumes the extractor, returning the key.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl FromRequest for ApiKey {
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Error = Error;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> <Self as FromRequest>::Future {
+        let default_config;
+        let config = match req.app_data::<ApiKeyConfig>() {
+            Some(config) => config,
+            None => {
+                default_config = ApiKeyConfig::default();
+                &default_config
+            }
+        };
+
+        ready(config.extract(req).map_err(|err| {
+            log::debug!("`ApiKey` extract error: {}", err);
+
+            match config.err_handler {
+                Some(ref err_handler) => err_handler(err, req),
+                None => err.into(),
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{error, test::TestRequest};
+
+    use super::*;
+
+    async fn extract(req: TestRequest) -> Result<ApiKey, Error> {
+        ApiKey::extract(&req.to_http_request()).await
+    }
+
+    #[actix_web::test]
+    async fn test_header() {
+        let req = TestRequest::default().insert_header(("X-Api-Key", "secret"));
+        assert_eq!(extract(req).await.unwrap().key(), "secret");
+
+        // query parameter is not read by default
+        let req = TestRequest::with_uri("/?api_key=secret");
+        let err = extract(req).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        let req = TestRequest::default()
+            .app_data(ApiKeyConfig::default().header_name(HeaderName::from_static("x-key")))
+            .insert_header(("X-Key", "secret"));
+        assert_eq!(extract(req).await.unwrap().key(), "secret");
+    }
+
+    #[actix_web::test]
+    async fn test_query() {
+        let config = || {
+            ApiKeyConfig::default()
+                .header_name(None)
+                .query_param("api_key")
+        };
+
+        let req = TestRequest::with_uri("/?page=2&api_key=secret").app_data(config());
+        assert_eq!(extract(req).await.unwrap().key(), "secret");
+
+        let req = TestRequest::default()
+            .app_data(config())
+            .insert_header(("X-Api-Key", "secret"));
+        let err = extract(req).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_header_and_query() {
+        let config = || ApiKeyConfig::default().query_param("api_key");
+
+        let req = TestRequest::with_uri("/?api_key=from-query")
+            .app_data(config())
+            .insert_header(("X-Api-Key", "from-header"));
+        assert_eq!(extract(req).await.unwrap().key(), "from-header");
+
+        let req = TestRequest::with_uri("/?api_key=from-query").app_data(config());
+        assert_eq!(extract(req).await.unwrap().key(), "from-query");
+    }
+
+    #[actix_web::test]
+    async fn test_missing() {
+        let config = ApiKeyConfig::default().query_param("api_key");
+        let req = TestRequest::with_uri("/?page=2").app_data(config.clone());
+        let err = extract(req).await.unwrap_err();
+        assert_eq!(
+            *err.as_error::<ApiKeyError>().unwrap(),
+            ApiKeyError::Missing
+        );
+
+        let req = TestRequest::default()
+            .app_data(config)
+            .insert_header(("X-Api-Key", &b"\xff"[..]));
+        let err = extract(req).await.unwrap_err();
+        assert_eq!(
+            *err.as_error::<ApiKeyError>().unwrap(),
+            ApiKeyError::InvalidHeader
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_error_handler() {
+        let config = ApiKeyConfig::default().error_handler(|err, _req| error::ErrorForbidden(err));
+        let req = TestRequest::default().app_data(config);
+        let err = extract(req).await.unwrap_err();
+        assert_eq!(err.as_response_error().status_code(), StatusCode::FORBIDDEN);
+    }
+}
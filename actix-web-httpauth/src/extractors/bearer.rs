@@ -1,17 +1,24 @@
 //! Extractor for the "Bearer" HTTP Authentication Scheme.
 
-use std::{borrow::Cow, default::Default};
+use std::borrow::Cow;
 
 use actix_utils::future::{ready, Ready};
-use actix_web::{dev::Payload, http::header::Header, FromRequest, HttpRequest};
+use actix_web::{
+    dev::Payload,
+    http::header::{Header, AUTHORIZATION},
+    FromRequest, HttpRequest,
+};
 
 use super::{config::AuthExtractorConfig, errors::AuthenticationError};
-pub use crate::headers::www_authenticate::bearer::Error;
+pub use crate::headers::www_authenticate::bearer::{BearerBuildError, Error};
 use crate::headers::{authorization, www_authenticate::bearer};
 
 /// [`BearerAuth`] extractor configuration.
 #[derive(Debug, Clone, Default)]
-pub struct Config(bearer::Bearer);
+pub struct Config {
+    challenge: bearer::Bearer,
+    pub(crate) scheme_name: Option<Cow<'static, str>>,
+}
 
 impl Config {
     /// Set challenge `scope` attribute.
@@ -20,7 +27,7 @@ impl Config {
     /// scope values indicating the required scope of the access token for
     /// accessing the requested resource.
     pub fn scope<T: Into<Cow<'static, str>>>(mut self, value: T) -> Config {
-        self.0.scope = Some(value.into());
+        self.challenge.scope = Some(value.into());
         self
     }
 
@@ -29,14 +36,41 @@ impl Config {
     /// The "realm" attribute indicates the scope of protection in the manner
     /// described in HTTP/1.1 [RFC 2617](https://tools.ietf.org/html/rfc2617#section-1.2).
     pub fn realm<T: Into<Cow<'static, str>>>(mut self, value: T) -> Config {
-        self.0.realm = Some(value.into());
+        self.challenge.realm = Some(value.into());
+        self
+    }
+
+    /// Provides a localized `error_description` attribute for the language `lang`.
+    ///
+    /// The description is only sent after an error has been attached to the challenge and it has
+    /// been localized for the request's `Accept-Language` header, see
+    /// [`Bearer::localize`](bearer::Bearer::localize). Requests lacking credentials receive the
+    /// challenge without any error information.
+    pub fn error_description_locale<L, T>(mut self, lang: L, value: T) -> Config
+    where
+        L: Into<Cow<'static, str>>,
+        T: Into<Cow<'static, str>>,
+    {
+        self.challenge
+            .error_description_locales
+            .push((lang.into(), value.into()));
+        self
+    }
+
+    /// Set the authentication scheme name expected in the `Authorization` header.
+    ///
+    /// Defaults to `Bearer`. Some services use custom scheme names, such as
+    /// `Authorization: AccessToken <token>`; setting this allows [`BearerAuth`] to extract
+    /// those tokens as well. Like `Bearer`, the scheme name is matched case-insensitively.
+    pub fn scheme_name<T: Into<Cow<'static, str>>>(mut self, value: T) -> Config {
+        self.scheme_name = Some(value.into());
         self
     }
 }
 
 impl AsRef<bearer::Bearer> for Config {
     fn as_ref(&self) -> &bearer::Bearer {
-        &self.0
+        &self.challenge
     }
 }
 
@@ -44,7 +78,7 @@ impl AuthExtractorConfig for Config {
     type Inner = bearer::Bearer;
 
     fn into_inner(self) -> Self::Inner {
-        self.0
+        self.challenge
     }
 }
 
@@ -95,18 +129,26 @@ impl FromRequest for BearerAuth {
     type Error = AuthenticationError<bearer::Bearer>;
 
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> <Self as FromRequest>::Future {
-        ready(
-            authorization::Authorization::<authorization::Bearer>::parse(req)
-                .map(|auth| BearerAuth(auth.into_scheme()))
-                .map_err(|_| {
-                    let bearer = req
-                        .app_data::<Config>()
-                        .map(|config| config.0.clone())
-                        .unwrap_or_else(Default::default);
-
-                    AuthenticationError::new(bearer)
-                }),
-        )
+        let config = req.app_data::<Config>();
+
+        let credentials = match config.and_then(|config| config.scheme_name.as_deref()) {
+            Some(scheme) => req
+                .headers()
+                .get(AUTHORIZATION)
+                .and_then(|header| authorization::Bearer::parse_with_scheme(header, scheme).ok()),
+
+            None => authorization::Authorization::<authorization::Bearer>::parse(req)
+                .map(|auth| auth.into_scheme())
+                .ok(),
+        };
+
+        ready(credentials.map(BearerAuth).ok_or_else(|| {
+            let bearer = config
+                .map(|config| config.challenge.clone())
+                .unwrap_or_default();
+
+            AuthenticationError::new(bearer)
+        }))
     }
 }
 
@@ -123,22 +165,93 @@ impl AuthenticationError<bearer::Bearer> {
     }
 
     /// Attach error description to the current Authentication error.
+    ///
+    /// Replaces any localized descriptions, so that the challenge keeps this description when it
+    /// is localized.
     pub fn with_error_description<T>(mut self, desc: T) -> Self
     where
         T: Into<Cow<'static, str>>,
     {
-        self.challenge_mut().error_description = Some(desc.into());
+        let challenge = self.challenge_mut();
+        challenge.error_description = Some(desc.into());
+        challenge.error_description_locales.clear();
         self
     }
 
     /// Attach error URI to the current Authentication error.
     ///
-    /// It is up to implementor to provide properly formed absolute URI.
+    /// It is up to implementor to provide properly formed absolute URI. URIs containing characters
+    /// that are not allowed in the `error_uri` attribute are left out, see
+    /// [`try_with_error_uri`](Self::try_with_error_uri).
     pub fn with_error_uri<T>(mut self, uri: T) -> Self
     where
         T: Into<Cow<'static, str>>,
     {
-        self.challenge_mut().error_uri = Some(uri.into());
+        match bearer::validate_error_uri(uri.into()) {
+            Ok(uri) => self.challenge_mut().error_uri = Some(uri),
+            Err(err) => log::warn!("Bearer challenge attribute left out: {}", err),
+        }
         self
     }
+
+    /// Attach error URI to the current Authentication error.
+    ///
+    /// Returns an error if `uri` contains characters that are not allowed in the `error_uri`
+    /// attribute. See [`BearerBuilder::try_error_uri`](bearer::BearerBuilder::try_error_uri).
+    pub fn try_with_error_uri<T>(mut self, uri: T) -> Result<Self, BearerBuildError>
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.challenge_mut().error_uri = Some(bearer::validate_error_uri(uri.into())?);
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_custom_scheme_name() {
+        let req = TestRequest::default()
+            .insert_header((AUTHORIZATION, "AccessToken mF_9.B5f-4.1JqM"))
+            .app_data(Config::default().scheme_name("AccessToken"))
+            .to_http_request();
+        let auth = BearerAuth::extract(&req).await.unwrap();
+        assert_eq!(auth.token(), "mF_9.B5f-4.1JqM");
+
+        let req = TestRequest::default()
+            .insert_header((AUTHORIZATION, "Bearer mF_9.B5f-4.1JqM"))
+            .app_data(Config::default().scheme_name("AccessToken"))
+            .to_http_request();
+        assert!(BearerAuth::extract(&req).await.is_err());
+
+        let req = TestRequest::default()
+            .insert_header((AUTHORIZATION, "AccessToken mF_9.B5f-4.1JqM"))
+            .to_http_request();
+        assert!(BearerAuth::extract(&req).await.is_err());
+    }
+
+    #[test]
+    fn test_error_uri() {
+        let mut err = AuthenticationError::new(bearer::Bearer::default())
+            .with_error(Error::InvalidToken)
+            .with_error_uri("https://example.com/\"errors\"");
+        assert_eq!(err.challenge_mut().error_uri, None);
+
+        let err = AuthenticationError::new(bearer::Bearer::default())
+            .try_with_error_uri("https://example.com/\"errors\"")
+            .unwrap_err();
+        assert!(matches!(err, BearerBuildError::InvalidErrorUri(_)));
+
+        let mut err = AuthenticationError::new(bearer::Bearer::default())
+            .try_with_error_uri("https://example.com/errors")
+            .unwrap();
+        assert_eq!(
+            err.challenge_mut().error_uri.as_deref(),
+            Some("https://example.com/errors")
+        );
+    }
 }
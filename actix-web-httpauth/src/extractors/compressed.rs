@@ -0,0 +1,302 @@
+//! Extractor for the claims of JSON Web Tokens with a zstd-compressed payload.
+
+use std::ops::Deref;
+
+use actix_utils::future::{ready, Ready};
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use jsonwebtoken::{crypto, Algorithm};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::{
+    bearer::{self, BearerAuth},
+    errors::AuthenticationError,
+    jwt::{self, JwtClaimsConfig},
+};
+use crate::headers::www_authenticate::bearer::Bearer;
+
+/// Value of the `alg` header parameter of tokens with a compressed payload.
+const ZSTD_HS256: &str = "zstd+HS256";
+
+/// Maximum size of a decompressed payload.
+const MAX_PAYLOAD_SIZE: usize = 64 * 1024;
+
+/// Leeway applied to the `exp` and `nbf` claims, in seconds.
+const LEEWAY: u64 = 60;
+
+/// Extractor for the claims of a bearer token that is a JSON Web Token, whose payload may be
+/// compressed with zstd.
+///
+/// Tokens with a compressed payload are marked with the non-standard `alg` header parameter
+/// `zstd+HS256`. Their payload segment is the base64url-encoded, zstd-compressed claims set, and
+/// their signature is an HS256 MAC over the header and payload segments as transmitted. The
+/// signature is verified using the configured [`JwtSecret`](super::jwt::JwtSecret) before the
+/// payload is decompressed, so that forged tokens are rejected without decompressing anything.
+/// Compressed tokens are always rejected when no secret is configured, and payloads that
+/// decompress to more than 64 KiB are rejected as well.
+///
+/// Tokens with any other `alg` are handled like [`JwtClaims`](super::jwt::JwtClaims), according to
+/// the [`JwtClaimsConfig`] registered as app data. In both cases, tokens are only accepted within
+/// their validity period, and requests without a valid token are rejected with
+/// `401 Unauthorized`. Requests are rejected with `500 Internal Server Error` when no config is
+/// registered.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+///
+/// use actix_web::{web, App};
+/// use actix_web_httpauth::extractors::{
+///     compressed::CompressedBearerAuth,
+///     jwt::{JwtClaimsConfig, JwtSecret},
+/// };
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Claims {
+///     sub: String,
+/// }
+///
+/// async fn index(auth: CompressedBearerAuth<Claims>) -> String {
+///     format!("Hello, {}!", auth.sub)
+/// }
+///
+/// let secret = Arc::new(JwtSecret::from_secret(b"secret"));
+///
+/// let app = App::new()
+///     .app_data(JwtClaimsConfig::validated(secret))
+///     .route("/", web::get().to(index));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt-compress")))]
+#[derive(Debug, Clone)]
+pub struct CompressedBearerAuth<T> {
+    token: String,
+    claims: T,
+}
+
+impl<T> CompressedBearerAuth<T> {
+    /// Returns the full token provided by the client.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Returns the claims of the token.
+    pub fn claims(&self) -> &T {
+        &self.claims
+    }
+
+    /// Unwraps into the claims.
+    pub fn into_claims(self) -> T {
+        self.claims
+    }
+}
+
+impl<T> Deref for CompressedBearerAuth<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.claims
+    }
+}
+
+impl<T> FromRequest for CompressedBearerAuth<T>
+where
+    T: DeserializeOwned,
+{
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Error = AuthenticationError<Bearer>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> <Self as FromRequest>::Future {
+        let auth = match BearerAuth::from_request(req, payload).into_inner() {
+            Ok(auth) => auth,
+            Err(err) => return ready(Err(err)),
+        };
+
+        let config = match req.app_data::<JwtClaimsConfig>() {
+            Some(config) => config,
+            None => return ready(Err(jwt::missing_config(req))),
+        };
+
+        let token = auth.token();
+        let claims = if is_compressed(token) {
+            decode_compressed(config, token)
+        } else {
+            config.decode(token)
+        };
+
+        ready(
+            claims
+                .map(|claims| CompressedBearerAuth {
+                    token: token.to_owned(),
+                    claims,
+                })
+                .map_err(|desc| {
+                    let challenge = req
+                        .app_data::<bearer::Config>()
+                        .map(|config| config.as_ref().clone())
+                        .unwrap_or_default();
+
+                    AuthenticationError::new(challenge)
+                        .with_error(bearer::Error::InvalidToken)
+                        .with_error_description(desc)
+                }),
+        )
+    }
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+}
+
+#[derive(Deserialize)]
+struct Validity {
+    exp: Option<u64>,
+    nbf: Option<u64>,
+}
+
+/// Returns true if the `alg` header parameter of `token` is `zstd+HS256`.
+fn is_compressed(token: &str) -> bool {
+    token
+        .split('.')
+        .next()
+        .and_then(|header| base64::decode_config(header, base64::URL_SAFE_NO_PAD).ok())
+        .and_then(|header| serde_json::from_slice::<Header>(&header).ok())
+        .is_some_and(|header| header.alg == ZSTD_HS256)
+}
+
+fn decode_compressed<T: DeserializeOwned>(
+    config: &JwtClaimsConfig,
+    token: &str,
+) -> Result<T, &'static str> {
+    let (message, signature) = token
+        .rsplit_once('.')
+        .ok_or("The access token is invalid")?;
+    let payload = message
+        .split_once('.')
+        .map(|(_, payload)| payload)
+        .ok_or("The access token is invalid")?;
+
+    // never decompress unauthenticated payloads
+    let secret = config
+        .secret
+        .as_ref()
+        .ok_or("The access token is invalid")?;
+
+    let valid = crypto::verify(signature, message.as_bytes(), &secret.key, Algorithm::HS256)
+        .map_err(|_| "The access token is invalid")?;
+
+    if !valid {
+        return Err("The access token signature is invalid");
+    }
+
+    let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD)
+        .map_err(|_| "The access token is invalid")?;
+    let claims = zstd::bulk::decompress(&payload, MAX_PAYLOAD_SIZE)
+        .map_err(|_| "The access token payload could not be decompressed")?;
+
+    let validity: Validity =
+        serde_json::from_slice(&claims).map_err(|_| "The access token is invalid")?;
+    let now = jsonwebtoken::get_current_timestamp();
+
+    if validity.exp.is_some_and(|exp| exp + LEEWAY < now) {
+        return Err("The access token expired");
+    }
+
+    if validity.nbf.is_some_and(|nbf| nbf > now + LEEWAY) {
+        return Err("The access token is invalid");
+    }
+
+    serde_json::from_slice(&claims).map_err(|_| "The access token is invalid")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::header::AUTHORIZATION, test::TestRequest, ResponseError as _};
+    use jsonwebtoken::EncodingKey;
+
+    use super::*;
+    use crate::extractors::jwt::JwtSecret;
+
+    #[derive(Debug, Deserialize)]
+    struct Claims {
+        sub: String,
+    }
+
+    fn compressed_token(secret: &[u8], claims: &serde_json::Value) -> String {
+        let encode = |part: &[u8]| base64::encode_config(part, base64::URL_SAFE_NO_PAD);
+
+        let header = encode(br#"{"alg":"zstd+HS256","typ":"JWT"}"#);
+        let claims = zstd::bulk::compress(claims.to_string().as_bytes(), 0).unwrap();
+        let message = format!("{}.{}", header, encode(&claims));
+
+        let key = EncodingKey::from_secret(secret);
+        let signature = crypto::sign(message.as_bytes(), &key, Algorithm::HS256).unwrap();
+
+        format!("{}.{}", message, signature)
+    }
+
+    fn request(token: &str) -> HttpRequest {
+        let secret = Arc::new(JwtSecret::from_secret(b"secret"));
+
+        TestRequest::default()
+            .app_data(JwtClaimsConfig::validated(secret))
+            .insert_header((AUTHORIZATION, format!("Bearer {}", token)))
+            .to_http_request()
+    }
+
+    #[actix_web::test]
+    async fn test_compressed() {
+        let claims = serde_json::json!({ "sub": "alice" });
+
+        let req = request(&compressed_token(b"secret", &claims));
+        let auth = CompressedBearerAuth::<Claims>::extract(&req).await.unwrap();
+        assert_eq!(auth.sub, "alice");
+
+        let req = request(&compressed_token(b"other", &claims));
+        let err = CompressedBearerAuth::<Claims>::extract(&req)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), 401);
+
+        let claims = serde_json::json!({ "sub": "alice", "exp": 1 });
+        let req = request(&compressed_token(b"secret", &claims));
+        let err = CompressedBearerAuth::<Claims>::extract(&req)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_compressed_unverified() {
+        let claims = serde_json::json!({ "sub": "alice" });
+
+        let req = TestRequest::default()
+            .app_data(JwtClaimsConfig::unverified())
+            .insert_header((
+                AUTHORIZATION,
+                format!("Bearer {}", compressed_token(b"secret", &claims)),
+            ))
+            .to_http_request();
+        let err = CompressedBearerAuth::<Claims>::extract(&req)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_uncompressed() {
+        let claims = serde_json::json!({ "sub": "alice" });
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claims,
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+
+        let req = request(&token);
+        let auth = CompressedBearerAuth::<Claims>::extract(&req).await.unwrap();
+        assert_eq!(auth.into_claims().sub, "alice");
+    }
+}
@@ -0,0 +1,372 @@
+//! Extractor for DPoP-bound access tokens ([RFC 9449]).
+//!
+//! [RFC 9449]: https://tools.ietf.org/html/rfc9449
+
+use std::{
+    borrow::Cow,
+    time::{Duration, SystemTime},
+};
+
+use actix_utils::future::{ready, Ready};
+use actix_web::{
+    dev::Payload,
+    http::header::{HeaderName, AUTHORIZATION},
+    FromRequest, HttpRequest,
+};
+use josekit::{
+    jwk::Jwk,
+    jws::{
+        EdDSA, JwsHeader, JwsVerifier, ES256, ES384, ES512, PS256, PS384, PS512, RS256, RS384,
+        RS512,
+    },
+    jwt::{self, JwtPayload},
+};
+use sha2::{Digest as _, Sha256};
+
+use super::{bearer, errors::AuthenticationError};
+pub use crate::headers::www_authenticate::dpop::Error;
+use crate::headers::{authorization, www_authenticate::dpop::DPoP};
+
+/// Name of the request header carrying the DPoP proof.
+pub const DPOP: HeaderName = HeaderName::from_static("dpop");
+
+/// Space-delimited list of the algorithms accepted for DPoP proofs, see [`verifier`].
+const SUPPORTED_ALGS: &str = "ES256 ES384 ES512 RS256 RS384 RS512 PS256 PS384 PS512 EdDSA";
+
+/// Maximum difference between the issue time of a proof and the current time.
+const MAX_PROOF_AGE: Duration = Duration::from_secs(60);
+
+/// Extractor for access tokens bound to a key using DPoP (Demonstrating Proof-of-Possession).
+///
+/// Expects an `Authorization: DPoP <token>` header along with a single `DPoP` header containing a
+/// proof JWT. The proof is accepted if:
+///
+/// - its `typ` header is `dpop+jwt` and it is signed with an asymmetric algorithm, using the
+///   public key in its `jwk` header;
+/// - its `htm` and `htu` claims match the method and URL (without query and fragment) of the
+///   request;
+/// - its `iat` claim is within one minute of the current time;
+/// - its `ath` claim is the hash of the access token; and
+/// - it contains a `jti` claim.
+///
+/// Checking that the access token is bound to the proof key (e.g. through the `cnf` claim of the
+/// token) and that `jti` values are not reused is left to the application.
+///
+/// On failure, requests are rejected with a `DPoP` challenge listing the accepted algorithms in its
+/// `algs` attribute, as described in [RFC 9449 §7.1]. The realm of the [`bearer::Config`]
+/// registered as app data (if any) is included in the challenge.
+///
+/// [RFC 9449 §7.1]: https://tools.ietf.org/html/rfc9449#section-7.1
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::extractors::dpop::DPoPAuth;
+///
+/// async fn index(auth: DPoPAuth) -> String {
+///     format!("Hello, user with token {}!", auth.token())
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "dpop")))]
+#[derive(Debug, Clone)]
+pub struct DPoPAuth {
+    token: String,
+    proof: DPoPProof,
+}
+
+impl DPoPAuth {
+    /// Returns the access token provided by the client.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Returns the verified DPoP proof.
+    pub fn proof(&self) -> &DPoPProof {
+        &self.proof
+    }
+}
+
+/// A verified DPoP proof JWT.
+#[cfg_attr(docsrs, doc(cfg(feature = "dpop")))]
+#[derive(Debug, Clone)]
+pub struct DPoPProof {
+    header: JwsHeader,
+    payload: JwtPayload,
+    jwk: Jwk,
+}
+
+impl DPoPProof {
+    /// Returns the JOSE header of the proof.
+    pub fn header(&self) -> &JwsHeader {
+        &self.header
+    }
+
+    /// Returns the claims of the proof.
+    pub fn payload(&self) -> &JwtPayload {
+        &self.payload
+    }
+
+    /// Returns the public key the proof was signed with.
+    pub fn jwk(&self) -> &Jwk {
+        &self.jwk
+    }
+
+    /// Returns the unique identifier (`jti`) of the proof.
+    pub fn jti(&self) -> &str {
+        self.payload.jwt_id().unwrap_or_default()
+    }
+
+    fn verify(req: &HttpRequest, proof: &str, token: &str) -> Result<Self, &'static str> {
+        let unverified = jwt::decode_header(proof).map_err(|_| "The DPoP proof is malformed")?;
+
+        let typ = unverified.claim("typ").and_then(|typ| typ.as_str());
+        if typ != Some("dpop+jwt") {
+            return Err("The DPoP proof has an invalid type");
+        }
+
+        let jwk = unverified
+            .claim("jwk")
+            .and_then(|jwk| jwk.as_object())
+            .and_then(|jwk| Jwk::from_map(jwk.clone()).ok())
+            .ok_or("The DPoP proof does not contain a valid public key")?;
+
+        if jwk.parameter("d").is_some() {
+            return Err("The DPoP proof key must not contain private key material");
+        }
+
+        let alg = unverified.claim("alg").and_then(|alg| alg.as_str());
+        let verifier = verifier(alg, &jwk).ok_or("The DPoP proof algorithm is unsupported")?;
+
+        let (payload, header) = jwt::decode_with_verifier(proof, &*verifier)
+            .map_err(|_| "The DPoP proof signature is invalid")?;
+
+        let htm = payload.claim("htm").and_then(|htm| htm.as_str());
+        if htm != Some(req.method().as_str()) {
+            return Err("The DPoP proof does not match the request method");
+        }
+
+        let htu = payload.claim("htu").and_then(|htu| htu.as_str());
+        if htu.map(strip_query) != Some(request_url(req).as_str()) {
+            return Err("The DPoP proof does not match the request URL");
+        }
+
+        let iat = payload
+            .issued_at()
+            .ok_or("The DPoP proof has no issue time")?;
+        let now = SystemTime::now();
+        let age = now
+            .duration_since(iat)
+            .or_else(|_| iat.duration_since(now))
+            .unwrap_or_default();
+        if age > MAX_PROOF_AGE {
+            return Err("The DPoP proof is not fresh");
+        }
+
+        let ath = base64::encode_config(Sha256::digest(token.as_bytes()), base64::URL_SAFE_NO_PAD);
+        if payload.claim("ath").and_then(|ath| ath.as_str()) != Some(ath.as_str()) {
+            return Err("The DPoP proof does not match the access token");
+        }
+
+        if payload.jwt_id().is_none() {
+            return Err("The DPoP proof has no identifier");
+        }
+
+        Ok(DPoPProof {
+            header,
+            payload,
+            jwk,
+        })
+    }
+}
+
+/// Creates a verifier for the asymmetric algorithm `alg` using `jwk`.
+pub(crate) fn verifier(alg: Option<&str>, jwk: &Jwk) -> Option<Box<dyn JwsVerifier>> {
+    fn boxed<V: JwsVerifier + 'static>(verifier: V) -> Box<dyn JwsVerifier> {
+        Box::new(verifier)
+    }
+
+    match alg? {
+        "ES256" => ES256.verifier_from_jwk(jwk).ok().map(boxed),
+        "ES384" => ES384.verifier_from_jwk(jwk).ok().map(boxed),
+        "ES512" => ES512.verifier_from_jwk(jwk).ok().map(boxed),
+        "RS256" => RS256.verifier_from_jwk(jwk).ok().map(boxed),
+        "RS384" => RS384.verifier_from_jwk(jwk).ok().map(boxed),
+        "RS512" => RS512.verifier_from_jwk(jwk).ok().map(boxed),
+        "PS256" => PS256.verifier_from_jwk(jwk).ok().map(boxed),
+        "PS384" => PS384.verifier_from_jwk(jwk).ok().map(boxed),
+        "PS512" => PS512.verifier_from_jwk(jwk).ok().map(boxed),
+        "EdDSA" => EdDSA.verifier_from_jwk(jwk).ok().map(boxed),
+        _ => None,
+    }
+}
+
+/// Returns the URL of `req`, without query and fragment.
+pub(crate) fn request_url(req: &HttpRequest) -> String {
+    let conn = req.connection_info();
+    format!("{}://{}{}", conn.scheme(), conn.host(), req.path())
+}
+
+pub(crate) fn strip_query(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+impl FromRequest for DPoPAuth {
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Error = AuthenticationError<DPoP>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> <Self as FromRequest>::Future {
+        let challenge = || DPoP {
+            realm: req
+                .app_data::<bearer::Config>()
+                .and_then(|config| config.as_ref().realm.clone()),
+            ..DPoP::with_algs(SUPPORTED_ALGS)
+        };
+
+        let token = match req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|header| authorization::Bearer::parse_with_scheme(header, "DPoP").ok())
+        {
+            Some(token) => token,
+            None => return ready(Err(AuthenticationError::new(challenge()))),
+        };
+
+        let mut proofs = req.headers().get_all(DPOP);
+        let proof = match (proofs.next(), proofs.next()) {
+            (Some(proof), None) => proof
+                .to_str()
+                .map_err(|_| "The DPoP proof is malformed")
+                .and_then(|proof| DPoPProof::verify(req, proof, token.token())),
+            (None, _) => Err("The DPoP proof is missing"),
+            (Some(_), Some(_)) => Err("Only one DPoP proof may be provided"),
+        };
+
+        ready(match proof {
+            Ok(proof) => Ok(DPoPAuth {
+                token: token.token().to_owned(),
+                proof,
+            }),
+            Err(desc) => Err(AuthenticationError::new(challenge())
+                .with_error(Error::InvalidDPoPProof)
+                .with_error_description(desc)),
+        })
+    }
+}
+
+/// Extended error customization for HTTP `DPoP` auth.
+impl AuthenticationError<DPoP> {
+    /// Attach `Error` to the current Authentication error.
+    ///
+    /// Error status code will be changed to the one provided by the `kind` Error.
+    pub fn with_error(mut self, kind: Error) -> Self {
+        *self.status_code_mut() = kind.status_code();
+        self.challenge_mut().error = Some(kind);
+        self
+    }
+
+    /// Attach error description to the current Authentication error.
+    pub fn with_error_description<T>(mut self, desc: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.challenge_mut().error_description = Some(desc.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::header::{HOST, WWW_AUTHENTICATE},
+        test::TestRequest,
+        ResponseError as _,
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    fn proof(htm: &str, htu: &str, token: &str) -> String {
+        let key_pair = ES256.generate_key_pair().unwrap();
+
+        let mut header = JwsHeader::new();
+        header.set_token_type("dpop+jwt");
+        header.set_jwk(key_pair.to_jwk_public_key());
+
+        let ath = base64::encode_config(Sha256::digest(token.as_bytes()), base64::URL_SAFE_NO_PAD);
+
+        let mut payload = JwtPayload::new();
+        payload.set_jwt_id("e1j3V_bKic8-LAEB");
+        payload.set_issued_at(&SystemTime::now());
+        payload.set_claim("htm", Some(json!(htm))).unwrap();
+        payload.set_claim("htu", Some(json!(htu))).unwrap();
+        payload.set_claim("ath", Some(json!(ath))).unwrap();
+
+        let signer = ES256.signer_from_jwk(&key_pair.to_jwk_key_pair()).unwrap();
+        jwt::encode_with_signer(&payload, &header, &signer).unwrap()
+    }
+
+    const TOKEN: &str = "Kz~8mXK1EalYznwH-LC-1fBAo.4Ljp~zsPE_NeO.gxU";
+
+    fn request(proof: &str) -> HttpRequest {
+        TestRequest::get()
+            .uri("/resource?page=2")
+            .insert_header((HOST, "example.com"))
+            .insert_header((AUTHORIZATION, format!("DPoP {}", TOKEN)))
+            .insert_header((DPOP, proof))
+            .to_http_request()
+    }
+
+    #[actix_web::test]
+    async fn test_dpop() {
+        let proof = proof("GET", "http://example.com/resource", TOKEN);
+
+        let auth = DPoPAuth::extract(&request(&proof)).await.unwrap();
+        assert_eq!(auth.token(), TOKEN);
+        assert_eq!(auth.proof().jti(), "e1j3V_bKic8-LAEB");
+        assert_eq!(auth.proof().jwk().key_type(), "EC");
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_dpop() {
+        let proofs = [
+            proof("POST", "http://example.com/resource", TOKEN),
+            proof("GET", "http://example.com/other", TOKEN),
+            proof("GET", "http://example.com/resource", "other-token"),
+            "not-a-jwt".to_owned(),
+        ];
+
+        for proof in &proofs {
+            let err = DPoPAuth::extract(&request(proof)).await.unwrap_err();
+            assert_eq!(err.status_code(), 401);
+        }
+
+        let req = TestRequest::get()
+            .insert_header((AUTHORIZATION, format!("DPoP {}", TOKEN)))
+            .to_http_request();
+        let err = DPoPAuth::extract(&req).await.unwrap_err();
+        assert_eq!(err.status_code(), 401);
+        assert_eq!(
+            err.error_response()
+                .headers()
+                .get(WWW_AUTHENTICATE)
+                .unwrap(),
+            "DPoP error=\"invalid_dpop_proof\", error_description=\"The DPoP proof is missing\", \
+             algs=\"ES256 ES384 ES512 RS256 RS384 RS512 PS256 PS384 PS512 EdDSA\""
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_multiple_proofs() {
+        let proof = proof("GET", "http://example.com/resource", TOKEN);
+
+        let req = TestRequest::get()
+            .uri("/resource")
+            .insert_header((HOST, "example.com"))
+            .insert_header((AUTHORIZATION, format!("DPoP {}", TOKEN)))
+            .append_header((DPOP, proof.as_str()))
+            .append_header((DPOP, proof.as_str()))
+            .to_http_request();
+        let mut err = DPoPAuth::extract(&req).await.unwrap_err();
+        assert_eq!(err.status_code(), 401);
+        assert_eq!(err.challenge_mut().error(), Some(Error::InvalidDPoPProof));
+    }
+}
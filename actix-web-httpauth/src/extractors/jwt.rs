@@ -0,0 +1,283 @@
+//! Extractor for the claims of bearer tokens that are JSON Web Tokens.
+
+use std::{fmt, ops::Deref, sync::Arc};
+
+use actix_utils::future::{ready, Ready};
+use actix_web::{dev::Payload, http::StatusCode, FromRequest, HttpRequest};
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, Validation};
+use serde::de::DeserializeOwned;
+
+use super::{
+    bearer::{self, BearerAuth},
+    errors::AuthenticationError,
+};
+use crate::headers::www_authenticate::bearer::Bearer;
+
+/// Extractor for the claims of a bearer token that is a JSON Web Token.
+///
+/// The payload of the token is deserialized into `T`. Tokens are extracted from the
+/// `Authorization` header in the same way as [`BearerAuth`].
+///
+/// How tokens are checked is configured by the [`JwtClaimsConfig`] registered as app data:
+///
+/// - if it holds a [`JwtSecret`] (see [`JwtClaimsConfig::validated`]), the signature of the token
+///   is verified using the configured algorithm before the claims are deserialized;
+/// - if it was created using [`JwtClaimsConfig::unverified`], the signature is **not** verified and
+///   the claims must not be trusted, e.g. when the token has already been validated by a gateway.
+///
+/// Requests are rejected with `500 Internal Server Error` when no config is registered. In the
+/// other cases, tokens with an `exp` or `nbf` claim are only accepted within their validity
+/// period. Requests without a valid token are rejected with `401 Unauthorized`, using the
+/// [`bearer::Config`] registered as app data (if any) to form the `WWW-Authenticate` challenge.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+///
+/// use actix_web::{web, App};
+/// use actix_web_httpauth::extractors::jwt::{JwtClaims, JwtSecret};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Claims {
+///     sub: String,
+/// }
+///
+/// async fn index(claims: JwtClaims<Claims>) -> String {
+///     format!("Hello, {}!", claims.sub)
+/// }
+///
+/// let secret = Arc::new(JwtSecret::from_secret(b"secret"));
+///
+/// let app = App::new()
+///     .app_data(JwtClaims::<Claims>::validated(secret))
+///     .route("/", web::get().to(index));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+#[derive(Debug, Clone)]
+pub struct JwtClaims<T>(T);
+
+impl<T> JwtClaims<T> {
+    /// Creates a config verifying token signatures using `secret`.
+    ///
+    /// Shorthand for [`JwtClaimsConfig::validated`].
+    pub fn validated(secret: Arc<JwtSecret>) -> JwtClaimsConfig {
+        JwtClaimsConfig::validated(secret)
+    }
+
+    /// Unwraps into the claims.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for JwtClaims<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for JwtClaims<T>
+where
+    T: DeserializeOwned,
+{
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Error = AuthenticationError<Bearer>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> <Self as FromRequest>::Future {
+        let auth = match BearerAuth::from_request(req, payload).into_inner() {
+            Ok(auth) => auth,
+            Err(err) => return ready(Err(err)),
+        };
+
+        let config = match req.app_data::<JwtClaimsConfig>() {
+            Some(config) => config,
+            None => return ready(Err(missing_config(req))),
+        };
+
+        ready(config.decode(auth.token()).map(JwtClaims).map_err(|desc| {
+            let challenge = req
+                .app_data::<bearer::Config>()
+                .map(|config| config.as_ref().clone())
+                .unwrap_or_default();
+
+            AuthenticationError::new(challenge)
+                .with_error(bearer::Error::InvalidToken)
+                .with_error_description(desc)
+        }))
+    }
+}
+
+/// Key used to verify the signature of JSON Web Tokens.
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+#[derive(Clone)]
+pub struct JwtSecret {
+    pub(crate) key: DecodingKey,
+}
+
+impl JwtSecret {
+    /// Creates a key from a shared secret, for HMAC algorithms.
+    pub fn from_secret(secret: &[u8]) -> Self {
+        JwtSecret {
+            key: DecodingKey::from_secret(secret),
+        }
+    }
+
+    /// Creates a key from a PEM-encoded RSA public key, for RSA algorithms.
+    pub fn from_rsa_pem(pem: &[u8]) -> Result<Self, jsonwebtoken::errors::Error> {
+        Ok(JwtSecret {
+            key: DecodingKey::from_rsa_pem(pem)?,
+        })
+    }
+}
+
+impl fmt::Debug for JwtSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtSecret").finish_non_exhaustive()
+    }
+}
+
+/// Returns the error for requests with a JSON Web Token when no [`JwtClaimsConfig`] is registered.
+///
+/// Decoding tokens without verifying them must be opted into, so a missing config is treated as a
+/// server misconfiguration rather than silently accepting unverified claims.
+pub(crate) fn missing_config(req: &HttpRequest) -> AuthenticationError<Bearer> {
+    log::error!("No JwtClaimsConfig registered as app data, rejecting JSON Web Token");
+
+    let challenge = req
+        .app_data::<bearer::Config>()
+        .map(|config| config.as_ref().clone())
+        .unwrap_or_default();
+
+    let mut err = AuthenticationError::new(challenge);
+    *err.status_code_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    err
+}
+
+/// [`JwtClaims`] extractor configuration.
+///
+/// Created using either [`validated`](Self::validated) or [`unverified`](Self::unverified).
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+#[derive(Debug, Clone)]
+pub struct JwtClaimsConfig {
+    pub(crate) secret: Option<Arc<JwtSecret>>,
+    algorithm: Algorithm,
+}
+
+impl JwtClaimsConfig {
+    /// Creates a config verifying token signatures using `secret`.
+    ///
+    /// Tokens are expected to be signed using HS256, unless configured otherwise using
+    /// [`algorithm`](Self::algorithm).
+    pub fn validated(secret: Arc<JwtSecret>) -> Self {
+        JwtClaimsConfig {
+            secret: Some(secret),
+            algorithm: Algorithm::HS256,
+        }
+    }
+
+    /// Creates a config that does **not** verify token signatures.
+    ///
+    /// The claims of tokens accepted using this config must not be trusted, unless the tokens
+    /// have already been validated elsewhere, e.g. by a gateway.
+    pub fn unverified() -> Self {
+        JwtClaimsConfig {
+            secret: None,
+            algorithm: Algorithm::HS256,
+        }
+    }
+
+    /// Sets the algorithm tokens must be signed with. Defaults to HS256.
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub(crate) fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<T, &'static str> {
+        let mut validation = Validation::new(self.algorithm);
+        validation.required_spec_claims.clear();
+        validation.validate_aud = false;
+
+        let res = match self.secret {
+            Some(ref secret) => jsonwebtoken::decode::<T>(token, &secret.key, &validation),
+            None => {
+                validation.insecure_disable_signature_validation();
+                jsonwebtoken::decode::<T>(token, &DecodingKey::from_secret(&[]), &validation)
+            }
+        };
+
+        res.map(|data| data.claims).map_err(|err| match err.kind() {
+            ErrorKind::ExpiredSignature => "The access token expired",
+            ErrorKind::InvalidSignature => "The access token signature is invalid",
+            _ => "The access token is invalid",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header::AUTHORIZATION, test::TestRequest, ResponseError as _};
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Claims {
+        sub: String,
+    }
+
+    fn token(secret: &[u8]) -> String {
+        let claims = Claims {
+            sub: "alice".to_owned(),
+        };
+        let key = EncodingKey::from_secret(secret);
+        jsonwebtoken::encode(&Header::default(), &claims, &key).unwrap()
+    }
+
+    fn request(config: JwtClaimsConfig, token: &str) -> HttpRequest {
+        TestRequest::default()
+            .app_data(config)
+            .insert_header((AUTHORIZATION, format!("Bearer {}", token)))
+            .to_http_request()
+    }
+
+    #[actix_web::test]
+    async fn test_validated() {
+        let config = JwtClaimsConfig::validated(Arc::new(JwtSecret::from_secret(b"secret")));
+
+        let req = request(config.clone(), &token(b"secret"));
+        let claims = JwtClaims::<Claims>::extract(&req).await.unwrap();
+        assert_eq!(claims.sub, "alice");
+
+        let req = request(config.clone(), &token(b"other"));
+        let err = JwtClaims::<Claims>::extract(&req).await.unwrap_err();
+        assert_eq!(err.status_code(), 401);
+
+        let req = request(config.algorithm(Algorithm::HS512), &token(b"secret"));
+        let err = JwtClaims::<Claims>::extract(&req).await.unwrap_err();
+        assert_eq!(err.status_code(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_unverified() {
+        let req = request(JwtClaimsConfig::unverified(), &token(b"other"));
+        let claims = JwtClaims::<Claims>::extract(&req).await.unwrap();
+        assert_eq!(claims.into_inner().sub, "alice");
+
+        let req = request(JwtClaimsConfig::unverified(), "not-a-jwt");
+        let err = JwtClaims::<Claims>::extract(&req).await.unwrap_err();
+        assert_eq!(err.status_code(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_missing_config() {
+        let req = TestRequest::default()
+            .insert_header((AUTHORIZATION, format!("Bearer {}", token(b"secret"))))
+            .to_http_request();
+        let err = JwtClaims::<Claims>::extract(&req).await.unwrap_err();
+        assert_eq!(err.status_code(), 500);
+    }
+}
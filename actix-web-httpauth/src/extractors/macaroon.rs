@@ -0,0 +1,284 @@
+//! Extractor for bearer tokens in the form of [Macaroons].
+//!
+//! [Macaroons]: https://research.google/pubs/pub41892/
+
+use std::{
+    fmt,
+    sync::{Arc, Once},
+};
+
+use ::macaroon::{ByteString, Macaroon, MacaroonKey, Verifier};
+use actix_utils::future::{ready, Ready};
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+
+use super::{
+    bearer::{self, BearerAuth},
+    errors::AuthenticationError,
+};
+use crate::headers::www_authenticate::bearer::Bearer;
+
+pub use ::macaroon::Caveat;
+
+type CaveatVerifier = Arc<dyn Fn(&HttpRequest, &ByteString) -> bool + Send + Sync>;
+
+/// Extractor for bearer tokens in the form of Macaroons.
+///
+/// Expects an `Authorization: Bearer <macaroon>` header containing a serialized Macaroon. The
+/// Macaroon is accepted if its chain of signatures can be verified using the root [`MacaroonKey`]
+/// of the [`MacaroonConfig`] registered as app data, and all of its first-party caveats are
+/// satisfied by the config's [caveat verifier](MacaroonConfig::caveat_verifier). Requests without
+/// a token, with a malformed token, with a broken signature chain or with an unsatisfied caveat
+/// are rejected with `401 Unauthorized`.
+///
+/// Without a caveat verifier, Macaroons with any first-party caveat are rejected. Macaroons with
+/// third-party caveats are always rejected, since no discharge Macaroons can be provided.
+///
+/// On failure, the [`bearer::Config`] registered as app data (if any) is used to form the
+/// `WWW-Authenticate` challenge.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App, HttpRequest};
+/// use actix_web_httpauth::extractors::macaroon::{MacaroonAuth, MacaroonConfig};
+/// use macaroon::{ByteString, MacaroonKey};
+///
+/// fn verify_caveat(req: &HttpRequest, predicate: &ByteString) -> bool {
+///     *predicate == ByteString::from(format!("method = {}", req.method()))
+/// }
+///
+/// async fn index(auth: MacaroonAuth) -> String {
+///     format!("Hello, Macaroon with {} caveats!", auth.caveats().len())
+/// }
+///
+/// let config =
+///     MacaroonConfig::new(MacaroonKey::generate(b"root-key")).caveat_verifier(verify_caveat);
+///
+/// let app = App::new()
+///     .app_data(config)
+///     .route("/", web::get().to(index));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "macaroon")))]
+#[derive(Debug, Clone)]
+pub struct MacaroonAuth {
+    macaroon: Macaroon,
+    caveats: Vec<Caveat>,
+}
+
+impl MacaroonAuth {
+    /// Returns the verified Macaroon.
+    pub fn macaroon(&self) -> &Macaroon {
+        &self.macaroon
+    }
+
+    /// Returns the caveats of the Macaroon, in the order they were added.
+    pub fn caveats(&self) -> &[Caveat] {
+        &self.caveats
+    }
+
+    /// Consumes the extractor, returning the caveats of the Macaroon.
+    pub fn into_caveats(self) -> Vec<Caveat> {
+        self.caveats
+    }
+
+    fn verify(req: &HttpRequest, token: &str) -> Result<Self, &'static str> {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            if let Err(err) = ::macaroon::initialize() {
+                log::error!("Failed to initialize Macaroon cryptography: {:?}", err);
+            }
+        });
+
+        let config = req.app_data::<MacaroonConfig>().ok_or_else(|| {
+            log::error!("MacaroonAuth requires a `MacaroonConfig` to be registered as app data");
+            "The access token could not be verified"
+        })?;
+
+        let macaroon = Macaroon::deserialize(token).map_err(|_| "The access token is malformed")?;
+
+        // without a caveat verifier, no caveat is satisfied; the verifier only accepts plain
+        // functions, so the predicates satisfied for this request are marked as exact matches
+        let mut verifier = Verifier::default();
+        if let Some(caveat_verifier) = &config.caveat_verifier {
+            for caveat in macaroon.first_party_caveats() {
+                if let Caveat::FirstParty(caveat) = caveat {
+                    let predicate = caveat.predicate();
+                    if caveat_verifier(req, &predicate) {
+                        verifier.satisfy_exact(predicate);
+                    }
+                }
+            }
+        }
+
+        verifier
+            .verify(&macaroon, &config.key, Vec::new())
+            .map_err(|_| "The access token signature is invalid or a caveat is not satisfied")?;
+
+        Ok(MacaroonAuth {
+            caveats: macaroon.caveats(),
+            macaroon,
+        })
+    }
+}
+
+/// [`MacaroonAuth`] extractor configuration.
+#[cfg_attr(docsrs, doc(cfg(feature = "macaroon")))]
+#[derive(Clone)]
+pub struct MacaroonConfig {
+    key: MacaroonKey,
+    caveat_verifier: Option<CaveatVerifier>,
+}
+
+impl MacaroonConfig {
+    /// Creates a config verifying Macaroons minted with the root `key`.
+    pub fn new(key: MacaroonKey) -> Self {
+        MacaroonConfig {
+            key,
+            caveat_verifier: None,
+        }
+    }
+
+    /// Sets the function deciding whether a first-party caveat is satisfied, given the request and
+    /// the caveat's predicate.
+    ///
+    /// Macaroons are only accepted if the function returns `true` for each of their first-party
+    /// caveats. Without a caveat verifier, Macaroons with first-party caveats are rejected.
+    pub fn caveat_verifier<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&HttpRequest, &ByteString) -> bool + Send + Sync + 'static,
+    {
+        self.caveat_verifier = Some(Arc::new(f));
+        self
+    }
+}
+
+impl fmt::Debug for MacaroonConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MacaroonConfig")
+            .field("caveat_verifier", &self.caveat_verifier.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl FromRequest for MacaroonAuth {
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Error = AuthenticationError<Bearer>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> <Self as FromRequest>::Future {
+        let bearer = match BearerAuth::from_request(req, payload).into_inner() {
+            Ok(bearer) => bearer,
+            Err(err) => return ready(Err(err)),
+        };
+
+        ready(MacaroonAuth::verify(req, bearer.token()).map_err(|desc| {
+            let challenge = req
+                .app_data::<bearer::Config>()
+                .map(|config| config.as_ref().clone())
+                .unwrap_or_default();
+
+            AuthenticationError::new(challenge)
+                .with_error(bearer::Error::InvalidToken)
+                .with_error_description(desc)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::macaroon::Format;
+    use actix_web::{http::header::AUTHORIZATION, test::TestRequest, ResponseError as _};
+
+    use super::*;
+
+    fn key() -> MacaroonKey {
+        MacaroonKey::generate(b"root-key")
+    }
+
+    fn config() -> MacaroonConfig {
+        MacaroonConfig::new(key())
+            .caveat_verifier(|_, predicate| *predicate == ByteString::from("account = 3735928559"))
+    }
+
+    fn token(key: &MacaroonKey) -> String {
+        let mut macaroon =
+            Macaroon::create(Some("http://example.com".into()), key, "keyid".into()).unwrap();
+        macaroon.add_first_party_caveat("account = 3735928559".into());
+        macaroon.serialize(Format::V2).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_macaroon() {
+        let req = TestRequest::default()
+            .app_data(config())
+            .insert_header((AUTHORIZATION, format!("Bearer {}", token(&key()))))
+            .to_http_request();
+        let auth = MacaroonAuth::extract(&req).await.unwrap();
+
+        assert_eq!(auth.caveats().len(), 1);
+        match &auth.caveats()[0] {
+            Caveat::FirstParty(caveat) => {
+                assert_eq!(caveat.predicate(), ByteString::from("account = 3735928559"))
+            }
+            Caveat::ThirdParty(_) => panic!("expected first-party caveat"),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_macaroon() {
+        let other_key = MacaroonKey::generate(b"other-key");
+        let tokens = [token(&other_key), "not-a-macaroon".to_owned()];
+
+        for token in &tokens {
+            let req = TestRequest::default()
+                .app_data(config())
+                .insert_header((AUTHORIZATION, format!("Bearer {}", token)))
+                .to_http_request();
+            let err = MacaroonAuth::extract(&req).await.unwrap_err();
+            assert_eq!(err.status_code(), 401);
+        }
+
+        let req = TestRequest::default().app_data(config()).to_http_request();
+        let err = MacaroonAuth::extract(&req).await.unwrap_err();
+        assert_eq!(err.status_code(), 401);
+    }
+
+    #[actix_web::test]
+    async fn test_unsatisfied_caveat() {
+        let configs = [
+            MacaroonConfig::new(key()),
+            MacaroonConfig::new(key()).caveat_verifier(|_, _| false),
+        ];
+
+        for config in configs {
+            let req = TestRequest::default()
+                .app_data(config)
+                .insert_header((AUTHORIZATION, format!("Bearer {}", token(&key()))))
+                .to_http_request();
+            let err = MacaroonAuth::extract(&req).await.unwrap_err();
+            assert_eq!(err.status_code(), 401);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_request_caveat() {
+        let mut macaroon = Macaroon::create(None, &key(), "keyid".into()).unwrap();
+        macaroon.add_first_party_caveat("method = GET".into());
+        let token = macaroon.serialize(Format::V2).unwrap();
+
+        let config = MacaroonConfig::new(key()).caveat_verifier(|req, predicate| {
+            *predicate == ByteString::from(format!("method = {}", req.method()))
+        });
+
+        let req = TestRequest::get()
+            .app_data(config.clone())
+            .insert_header((AUTHORIZATION, format!("Bearer {}", token)))
+            .to_http_request();
+        assert!(MacaroonAuth::extract(&req).await.is_ok());
+
+        let req = TestRequest::post()
+            .app_data(config)
+            .insert_header((AUTHORIZATION, format!("Bearer {}", token)))
+            .to_http_request();
+        let err = MacaroonAuth::extract(&req).await.unwrap_err();
+        assert_eq!(err.status_code(), 401);
+    }
+}
@@ -1,9 +1,21 @@
 //! Type-safe authentication information extractors.
 
+pub mod api_key;
 pub mod basic;
 pub mod bearer;
+#[cfg(feature = "jwt-compress")]
+pub mod compressed;
 mod config;
+#[cfg(feature = "dpop")]
+pub mod dpop;
 mod errors;
+pub mod jws;
+#[cfg(feature = "jwt")]
+pub mod jwt;
+#[cfg(feature = "macaroon")]
+pub mod macaroon;
+#[cfg(feature = "scram")]
+pub mod scram;
 
 pub use self::config::AuthExtractorConfig;
 pub use self::errors::AuthenticationError;
@@ -0,0 +1,525 @@
+//! Extractor for the "SCRAM-SHA-256" HTTP Authentication Scheme.
+
+use std::{borrow::Cow, fmt, sync::Arc};
+
+use actix_session::SessionExt as _;
+use actix_utils::future::{ready, Ready};
+use actix_web::{dev::Payload, http::header::AUTHORIZATION, FromRequest, HttpRequest};
+use rand::{rngs::StdRng, Rng as _, SeedableRng as _};
+use scram_rs::{AuthenticationStatus, ScramServer};
+use serde::{Deserialize, Serialize};
+
+use super::{config::AuthExtractorConfig, errors::AuthenticationError};
+use crate::{
+    headers::www_authenticate::scram::Scram as Challenge,
+    utils::{constant_time_eq, unix_timestamp},
+    validators::replay::ReplayCache,
+};
+
+pub use scram_rs::{hash_password, AuthenticationProvider, PasswordInfo};
+
+/// Default session key holding the state of an exchange.
+const DEFAULT_SESSION_KEY: &str = "scram";
+
+/// Name of the authentication scheme.
+const SCHEME: &str = "SCRAM-SHA-256";
+
+/// Number of seconds a client has to complete an exchange.
+const EXCHANGE_TIMEOUT: u64 = 300;
+
+/// [`ScramAuth`] extractor configuration.
+///
+/// Holds the [`AuthenticationProvider`] looking up the stored credentials of users and is used
+/// to form the [`WWW-Authenticate`] header.
+///
+/// [`WWW-Authenticate`]: crate::headers::www_authenticate::WwwAuthenticate
+#[cfg_attr(docsrs, doc(cfg(feature = "scram")))]
+#[derive(Clone)]
+pub struct Config {
+    provider: Arc<dyn AuthenticationProvider + Send + Sync>,
+    challenge: Challenge,
+    session_key: Cow<'static, str>,
+    completed: ReplayCache,
+}
+
+impl Config {
+    /// Creates a configuration looking up credentials with `provider`.
+    pub fn new<P>(provider: P) -> Config
+    where
+        P: AuthenticationProvider + Send + Sync + 'static,
+    {
+        Config {
+            provider: Arc::new(provider),
+            challenge: Challenge::default(),
+            session_key: Cow::Borrowed(DEFAULT_SESSION_KEY),
+            completed: ReplayCache::default(),
+        }
+    }
+
+    /// Set challenge `realm` attribute.
+    pub fn realm<T>(mut self, value: T) -> Config
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.challenge.realm = Some(value.into());
+        self
+    }
+
+    /// Sets the session key holding the state of an exchange between its two requests.
+    ///
+    /// Defaults to `scram`.
+    pub fn session_key<T>(mut self, key: T) -> Config
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.session_key = key.into();
+        self
+    }
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("challenge", &self.challenge)
+            .field("session_key", &self.session_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsRef<Challenge> for Config {
+    fn as_ref(&self) -> &Challenge {
+        &self.challenge
+    }
+}
+
+impl AuthExtractorConfig for Config {
+    type Inner = Challenge;
+
+    fn into_inner(self) -> Self::Inner {
+        self.challenge
+    }
+}
+
+/// Extractor for HTTP SCRAM-SHA-256 auth, described in [RFC 7804].
+///
+/// SCRAM authenticates users with a challenge-response exchange, so that passwords are never sent
+/// to the server. The exchange takes two requests:
+///
+/// 1. The client sends `Authorization: SCRAM-SHA-256 data=<client-first>`. The request is
+///    rejected with `401 Unauthorized` and `WWW-Authenticate: SCRAM-SHA-256 sid=<sid>,
+///    data=<server-first>`, carrying the nonce and salt the client needs to prove that it knows
+///    the password.
+/// 2. The client retries with `Authorization: SCRAM-SHA-256 sid=<sid>, data=<client-final>`.
+///    If the proof is valid the request is accepted, and the handler should return the
+///    [server signature](Self::authentication_info) in the `Authentication-Info` header, so that
+///    the client can authenticate the server as well.
+///
+/// The state of the exchange is kept in the user's session between both requests, under the
+/// `scram` key by default. It only contains a random seed from which the server nonce is derived,
+/// never the nonce itself. Each exchange can be completed only once, within five minutes;
+/// completed exchanges are remembered by the [`Config`] and its clones, so that they cannot be
+/// replayed with a stale session cookie either. Requires the
+/// [`SessionMiddleware`](actix_session::SessionMiddleware) to be registered, and a [`Config`]
+/// to be registered as app data. Requests without credentials, with malformed credentials or with
+/// an invalid proof are rejected with `401 Unauthorized`.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App, HttpResponse};
+/// use actix_web_httpauth::extractors::scram::{
+///     self, AuthenticationProvider, PasswordInfo, ScramAuth,
+/// };
+///
+/// struct Users;
+///
+/// impl AuthenticationProvider for Users {
+///     fn get_password_for(&self, _username: &str) -> Option<PasswordInfo> {
+///         // look up the salted password, e.g. in a database
+///         None
+///     }
+/// }
+///
+/// async fn index(auth: ScramAuth) -> HttpResponse {
+///     HttpResponse::Ok()
+///         .insert_header(("Authentication-Info", auth.authentication_info()))
+///         .body(format!("Hello, {}!", auth.username()))
+/// }
+///
+/// let app = App::new()
+///     .app_data(scram::Config::new(Users).realm("Restricted area"))
+///     .route("/", web::get().to(index));
+/// ```
+///
+/// [RFC 7804]: https://tools.ietf.org/html/rfc7804
+#[cfg_attr(docsrs, doc(cfg(feature = "scram")))]
+#[derive(Debug, Clone)]
+pub struct ScramAuth {
+    username: String,
+    sid: String,
+    server_final: String,
+}
+
+impl ScramAuth {
+    /// Returns the name of the authenticated user.
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    /// Returns the value of the `Authentication-Info` header carrying the server-final message.
+    pub fn authentication_info(&self) -> String {
+        format!(
+            "sid={}, data={}",
+            self.sid,
+            base64::encode(&self.server_final)
+        )
+    }
+}
+
+/// State of an exchange kept in the session between its two requests.
+#[derive(Debug, Serialize, Deserialize)]
+struct State {
+    sid: String,
+    client_first: String,
+    seed: [u8; 32],
+    expires_at: u64,
+}
+
+/// Lends a shared provider to a [`ScramServer`].
+struct Provider<'a>(&'a (dyn AuthenticationProvider + Send + Sync));
+
+impl AuthenticationProvider for Provider<'_> {
+    fn get_password_for(&self, username: &str) -> Option<PasswordInfo> {
+        self.0.get_password_for(username)
+    }
+
+    fn authorize(&self, authcid: &str, authzid: &str) -> bool {
+        self.0.authorize(authcid, authzid)
+    }
+}
+
+/// Credentials sent in the `Authorization` header.
+struct Credentials {
+    sid: Option<String>,
+    data: String,
+}
+
+impl Credentials {
+    fn parse(req: &HttpRequest) -> Option<Credentials> {
+        let header = req.headers().get(AUTHORIZATION)?.to_str().ok()?;
+        let (scheme, params) = header.trim().split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case(SCHEME) {
+            return None;
+        }
+
+        let mut sid = None;
+        let mut data = None;
+
+        for param in params.split(',') {
+            let (name, value) = param.trim().split_once('=')?;
+            let value = value.trim().trim_matches('"').to_owned();
+
+            match name.trim() {
+                "sid" => sid = Some(value),
+                "data" => data = Some(value),
+                _ => {}
+            }
+        }
+
+        let data = String::from_utf8(base64::decode(data?).ok()?).ok()?;
+        Some(Credentials { sid, data })
+    }
+}
+
+/// Returns the username from the client-first message, see [RFC 5802 §5.1].
+///
+/// [RFC 5802 §5.1]: https://tools.ietf.org/html/rfc5802#section-5.1
+fn username(client_first: &str) -> Option<String> {
+    let bare = client_first.splitn(3, ',').nth(2)?;
+    let name = bare.split(',').next()?.strip_prefix("n=")?;
+    Some(name.replace("=2C", ",").replace("=3D", "="))
+}
+
+impl ScramAuth {
+    fn authenticate(req: &HttpRequest, config: &Config) -> Result<Self, Challenge> {
+        let mut challenge = config.challenge.clone();
+
+        let credentials = Credentials::parse(req).ok_or_else(|| challenge.clone())?;
+        let server = ScramServer::new(Provider(&*config.provider));
+        let session = req.get_session();
+
+        match credentials.sid {
+            // client-first message, starting an exchange
+            None => {
+                let seed = rand::thread_rng().gen::<[u8; 32]>();
+                let (_, server_first) = server
+                    .handle_client_first(&credentials.data)
+                    .map_err(|_| challenge.clone())?
+                    .server_first_with_rng(&mut StdRng::from_seed(seed));
+
+                let sid = base64::encode_config(
+                    rand::thread_rng().gen::<[u8; 16]>(),
+                    base64::URL_SAFE_NO_PAD,
+                );
+
+                let state = State {
+                    sid: sid.clone(),
+                    client_first: credentials.data,
+                    seed,
+                    expires_at: unix_timestamp() + EXCHANGE_TIMEOUT,
+                };
+
+                if let Err(err) = session.insert(&*config.session_key, state) {
+                    log::error!("Failed to store SCRAM exchange in session: {}", err);
+                    return Err(challenge);
+                }
+
+                challenge.sid = Some(sid);
+                challenge.data = Some(base64::encode(server_first));
+                Err(challenge)
+            }
+
+            // client-final message, completing an exchange
+            Some(sid) => {
+                let state = session
+                    .remove_as::<State>(&config.session_key)
+                    .and_then(Result::ok)
+                    .filter(|state| constant_time_eq(state.sid.as_bytes(), sid.as_bytes()))
+                    .filter(|state| state.expires_at > unix_timestamp())
+                    .ok_or_else(|| challenge.clone())?;
+
+                if !config.completed.insert(&state.sid, state.expires_at) {
+                    return Err(challenge);
+                }
+
+                // replays the first step with the stored seed to restore the server nonce
+                let (server_final, _) = server
+                    .handle_client_first(&state.client_first)
+                    .map_err(|_| challenge.clone())?
+                    .server_first_with_rng(&mut StdRng::from_seed(state.seed));
+
+                let (status, server_final) = server_final
+                    .handle_client_final(&credentials.data)
+                    .map_err(|_| challenge.clone())?
+                    .server_final();
+
+                match (status, username(&state.client_first)) {
+                    (AuthenticationStatus::Authenticated, Some(username)) => Ok(ScramAuth {
+                        username,
+                        sid,
+                        server_final,
+                    }),
+                    _ => Err(challenge),
+                }
+            }
+        }
+    }
+}
+
+impl FromRequest for ScramAuth {
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Error = AuthenticationError<Challenge>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> <Self as FromRequest>::Future {
+        let config = match req.app_data::<Config>() {
+            Some(config) => config,
+            None => {
+                log::error!("ScramAuth requires a `scram::Config` to be registered as app data");
+                return ready(Err(AuthenticationError::new(Challenge::default())));
+            }
+        };
+
+        ready(ScramAuth::authenticate(req, config).map_err(AuthenticationError::new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use actix_session::{storage::CookieSessionStore, SessionMiddleware};
+    use actix_web::{
+        cookie::{Cookie, Key},
+        http::{header::WWW_AUTHENTICATE, StatusCode},
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App, HttpResponse,
+    };
+    use scram_rs::ScramClient;
+
+    use super::*;
+
+    struct Users;
+
+    impl AuthenticationProvider for Users {
+        fn get_password_for(&self, username: &str) -> Option<PasswordInfo> {
+            if username != "user" {
+                return None;
+            }
+
+            let iterations = NonZeroU32::new(4096).unwrap();
+            let password = hash_password("pencil", iterations, b"salt");
+            Some(PasswordInfo::new(password.to_vec(), 4096, b"salt".to_vec()))
+        }
+    }
+
+    async fn index(auth: ScramAuth) -> HttpResponse {
+        HttpResponse::Ok()
+            .insert_header(("Authentication-Info", auth.authentication_info()))
+            .body(auth.username().to_owned())
+    }
+
+    /// Returns the value of the `name` parameter of a challenge or `Authentication-Info` header.
+    fn param(header: &str, name: &str) -> String {
+        header
+            .split([' ', ','])
+            .find_map(|param| param.strip_prefix(name)?.strip_prefix('='))
+            .unwrap()
+            .to_owned()
+    }
+
+    #[actix_web::test]
+    async fn test_scram() {
+        let srv = init_service(
+            App::new()
+                .app_data(Config::new(Users).realm("Restricted area"))
+                .wrap(
+                    SessionMiddleware::builder(CookieSessionStore::default(), Key::generate())
+                        .cookie_secure(false)
+                        .build(),
+                )
+                .route("/", web::get().to(index)),
+        )
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            res.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "SCRAM-SHA-256 realm=\"Restricted area\""
+        );
+
+        let client = ScramClient::new("user", "pencil", None);
+        let (client, client_first) = client.client_first();
+
+        let req = TestRequest::default()
+            .insert_header((
+                AUTHORIZATION,
+                format!("SCRAM-SHA-256 data={}", base64::encode(client_first)),
+            ))
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let cookies: Vec<Cookie<'static>> = res
+            .response()
+            .cookies()
+            .map(|cookie| cookie.into_owned())
+            .collect();
+        let challenge = res
+            .headers()
+            .get(WWW_AUTHENTICATE)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        let sid = param(challenge, "sid");
+        let server_first = base64::decode(param(challenge, "data")).unwrap();
+
+        let client = client
+            .handle_server_first(std::str::from_utf8(&server_first).unwrap())
+            .unwrap();
+        let (client, client_final) = client.client_final();
+
+        let call = |client_final: String| {
+            let mut req = TestRequest::default().insert_header((
+                AUTHORIZATION,
+                format!(
+                    "SCRAM-SHA-256 sid={}, data={}",
+                    sid,
+                    base64::encode(client_final)
+                ),
+            ));
+            for cookie in &cookies {
+                req = req.cookie(cookie.clone());
+            }
+            call_service(&srv, req.to_request())
+        };
+
+        let res = call(client_final.clone()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let info = res.headers().get("Authentication-Info").unwrap();
+        let server_final = base64::decode(param(info.to_str().unwrap(), "data")).unwrap();
+        client
+            .handle_server_final(std::str::from_utf8(&server_final).unwrap())
+            .unwrap();
+        assert_eq!(read_body(res).await, "user");
+
+        // replayed exchange
+        let res = call(client_final).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn test_scram_wrong_password() {
+        let srv = init_service(
+            App::new()
+                .app_data(Config::new(Users))
+                .wrap(
+                    SessionMiddleware::builder(CookieSessionStore::default(), Key::generate())
+                        .cookie_secure(false)
+                        .build(),
+                )
+                .route("/", web::get().to(index)),
+        )
+        .await;
+
+        let (client, client_first) = ScramClient::new("user", "wrong", None).client_first();
+
+        let req = TestRequest::default()
+            .insert_header((
+                AUTHORIZATION,
+                format!("SCRAM-SHA-256 data={}", base64::encode(client_first)),
+            ))
+            .to_request();
+        let res = call_service(&srv, req).await;
+        let cookies: Vec<Cookie<'static>> = res
+            .response()
+            .cookies()
+            .map(|cookie| cookie.into_owned())
+            .collect();
+        let challenge = res
+            .headers()
+            .get(WWW_AUTHENTICATE)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        let sid = param(challenge, "sid");
+        let server_first = base64::decode(param(challenge, "data")).unwrap();
+
+        let (_, client_final) = client
+            .handle_server_first(std::str::from_utf8(&server_first).unwrap())
+            .unwrap()
+            .client_final();
+
+        let mut req = TestRequest::default().insert_header((
+            AUTHORIZATION,
+            format!(
+                "SCRAM-SHA-256 sid={}, data={}",
+                sid,
+                base64::encode(client_final)
+            ),
+        ));
+        for cookie in cookies {
+            req = req.cookie(cookie);
+        }
+        let res = call_service(&srv, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_username() {
+        assert_eq!(username("n,,n=user,r=abc").unwrap(), "user");
+        assert_eq!(username("n,a=admin,n=a=2Cb=3D,r=abc").unwrap(), "a,b=");
+        assert!(username("n,,r=abc").is_none());
+    }
+}
@@ -5,7 +5,7 @@ use actix_web::{
     web::{BufMut, BytesMut},
 };
 
-use crate::headers::authorization::{errors::ParseError, Scheme};
+use crate::headers::authorization::{errors::ParseError, scheme::unfold, Scheme};
 
 /// Credentials for `Basic` authentication scheme, defined in [RFC 7617](https://tools.ietf.org/html/rfc7617)
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -52,9 +52,10 @@ impl Scheme for Basic {
             return Err(ParseError::Invalid);
         }
 
-        let mut parts = header.to_str()?.splitn(2, ' ');
+        let header = unfold(header.to_str()?);
+        let mut parts = header.splitn(2, ' ');
         match parts.next() {
-            Some(scheme) if scheme == "Basic" => (),
+            Some(scheme) if scheme.eq_ignore_ascii_case("Basic") => (),
             _ => return Err(ParseError::MissingScheme),
         }
 
@@ -135,6 +136,17 @@ mod tests {
         assert_eq!(scheme.password, Some("open sesame".into()));
     }
 
+    #[test]
+    fn test_parse_header_case_insensitive() {
+        let value = HeaderValue::from_static("basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==");
+        let scheme = Basic::parse(&value);
+
+        assert!(scheme.is_ok());
+        let scheme = scheme.unwrap();
+        assert_eq!(scheme.user_id, "Aladdin");
+        assert_eq!(scheme.password, Some("open sesame".into()));
+    }
+
     #[test]
     fn test_empty_password() {
         let value = HeaderValue::from_static("Basic QWxhZGRpbjo=");
@@ -146,6 +158,18 @@ mod tests {
         assert_eq!(scheme.password, None);
     }
 
+    #[test]
+    fn test_password_containing_colons() {
+        // only the first colon separates the user-ID from the password (RFC 7617 §2)
+        let value = HeaderValue::from_static("Basic QWxhZGRpbjpvcGVuOnNlc2FtZTo=");
+        let scheme = Basic::parse(&value);
+
+        assert!(scheme.is_ok());
+        let scheme = scheme.unwrap();
+        assert_eq!(scheme.user_id, "Aladdin");
+        assert_eq!(scheme.password, Some("open:sesame:".into()));
+    }
+
     #[test]
     fn test_empty_header() {
         let value = HeaderValue::from_static("");
@@ -5,7 +5,10 @@ use actix_web::{
     web::{BufMut, BytesMut},
 };
 
-use crate::headers::authorization::{errors::ParseError, scheme::Scheme};
+use crate::headers::authorization::{
+    errors::ParseError,
+    scheme::{unfold, Scheme},
+};
 
 /// Credentials for `Bearer` authentication scheme, defined in [RFC 6750].
 ///
@@ -38,19 +41,31 @@ impl Bearer {
     pub fn token(&self) -> &str {
         self.token.as_ref()
     }
-}
 
-impl Scheme for Bearer {
-    fn parse(header: &HeaderValue) -> Result<Self, ParseError> {
-        // "Bearer *" length
-        if header.len() < 8 {
+    /// Parses bearer-style credentials from the header using a custom scheme name.
+    ///
+    /// Useful for services that send tokens as, e.g., `Authorization: AccessToken <token>`. The
+    /// scheme name is compared case-insensitively.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::http::header::HeaderValue;
+    /// # use actix_web_httpauth::headers::authorization::Bearer;
+    /// let value = HeaderValue::from_static("AccessToken mF_9.B5f-4.1JqM");
+    /// let credentials = Bearer::parse_with_scheme(&value, "AccessToken").unwrap();
+    /// assert_eq!(credentials.token(), "mF_9.B5f-4.1JqM");
+    /// ```
+    pub fn parse_with_scheme(header: &HeaderValue, scheme: &str) -> Result<Bearer, ParseError> {
+        // "<scheme> *" length
+        if header.len() < scheme.len() + 2 {
             return Err(ParseError::Invalid);
         }
 
-        let mut parts = header.to_str()?.splitn(2, ' ');
+        let header = unfold(header.to_str()?);
+        let mut parts = header.splitn(2, ' ');
 
         match parts.next() {
-            Some(scheme) if scheme == "Bearer" => {}
+            Some(name) if name.eq_ignore_ascii_case(scheme) => {}
             _ => return Err(ParseError::MissingScheme),
         }
 
@@ -62,6 +77,12 @@ impl Scheme for Bearer {
     }
 }
 
+impl Scheme for Bearer {
+    fn parse(header: &HeaderValue) -> Result<Self, ParseError> {
+        Bearer::parse_with_scheme(header, "Bearer")
+    }
+}
+
 impl fmt::Debug for Bearer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_fmt(format_args!("Bearer ******"))
@@ -100,6 +121,16 @@ mod tests {
         assert_eq!(scheme.token, "mF_9.B5f-4.1JqM");
     }
 
+    #[test]
+    fn test_parse_folded_header() {
+        // obs-fold, with the line break replaced by spaces by the HTTP parser or a proxy
+        let value = HeaderValue::from_static("Bearer  \t mF_9.B5f-4.1JqM");
+        let scheme = Bearer::parse(&value);
+
+        assert!(scheme.is_ok());
+        assert_eq!(scheme.unwrap().token, "mF_9.B5f-4.1JqM");
+    }
+
     #[test]
     fn test_empty_header() {
         let value = HeaderValue::from_static("");
@@ -124,6 +155,17 @@ mod tests {
         assert!(scheme.is_err());
     }
 
+    #[test]
+    fn test_parse_custom_scheme() {
+        let value = HeaderValue::from_static("ServiceToken mF_9.B5f-4.1JqM");
+
+        let scheme = Bearer::parse_with_scheme(&value, "ServiceToken");
+        assert!(scheme.is_ok());
+        assert_eq!(scheme.unwrap().token, "mF_9.B5f-4.1JqM");
+
+        assert!(Bearer::parse(&value).is_err());
+    }
+
     #[test]
     fn test_into_header_value() {
         let bearer = Bearer::new("mF_9.B5f-4.1JqM");
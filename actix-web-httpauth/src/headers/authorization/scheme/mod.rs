@@ -1,4 +1,7 @@
-use std::fmt::{Debug, Display};
+use std::{
+    borrow::Cow,
+    fmt::{Debug, Display},
+};
 
 use actix_web::http::header::{HeaderValue, TryIntoHeaderValue};
 
@@ -12,3 +15,51 @@ pub trait Scheme: TryIntoHeaderValue + Debug + Display + Clone + Send + Sync {
     /// Try to parse an authentication scheme from the `Authorization` header.
     fn parse(header: &HeaderValue) -> Result<Self, ParseError>;
 }
+
+/// Collapses linear whitespace in a header value, including obsolete line folding (`obs-fold`,
+/// [RFC 7230 §3.2.4]), into single spaces and trims it from both ends.
+///
+/// Folded values are still emitted by some legacy proxies. Since [`HeaderValue`] cannot contain
+/// line breaks, they usually arrive with each fold already replaced by spaces, as the RFC allows.
+///
+/// [RFC 7230 §3.2.4]: https://tools.ietf.org/html/rfc7230#section-3.2.4
+pub(crate) fn unfold(value: &str) -> Cow<'_, str> {
+    let is_lws = |c: char| matches!(c, ' ' | '\t' | '\r' | '\n');
+
+    let folded = value.starts_with(is_lws)
+        || value.ends_with(is_lws)
+        || value.contains(['\t', '\r', '\n'])
+        || value.contains("  ");
+
+    if !folded {
+        return Cow::Borrowed(value);
+    }
+
+    let parts: Vec<_> = value
+        .split(is_lws)
+        .filter(|part| !part.is_empty())
+        .collect();
+    Cow::Owned(parts.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unfold() {
+        assert!(matches!(unfold("Bearer mF_9.B5f-4.1JqM"), Cow::Borrowed(_)));
+        assert_eq!(
+            unfold("Bearer\r\n mF_9.B5f-4.1JqM"),
+            "Bearer mF_9.B5f-4.1JqM"
+        );
+        assert_eq!(
+            unfold("Bearer \r\n\t mF_9.B5f-4.1JqM "),
+            "Bearer mF_9.B5f-4.1JqM"
+        );
+        assert_eq!(
+            unfold("Bearer   \t mF_9.B5f-4.1JqM"),
+            "Bearer mF_9.B5f-4.1JqM"
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use std::borrow::Cow;
+
+use super::Basic;
+
+/// Builder for the [`Basic`] challenge.
+#[derive(Debug, Default)]
+pub struct BasicBuilder(Basic);
+
+impl BasicBuilder {
+    /// Provides the `realm` attribute, as defined in [RFC 7617 §2].
+    ///
+    /// Quotes and backslashes in the realm are escaped when the challenge is serialized.
+    ///
+    /// [RFC 7617 §2]: https://tools.ietf.org/html/rfc7617#section-2
+    pub fn realm<T>(mut self, value: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.0.realm = Some(value.into());
+        self
+    }
+
+    /// Consumes the builder and returns built `Basic` instance.
+    pub fn finish(self) -> Basic {
+        self.0
+    }
+}
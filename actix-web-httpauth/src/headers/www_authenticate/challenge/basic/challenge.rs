@@ -1,13 +1,16 @@
-//! Challenge for the "Basic" HTTP Authentication Scheme.
-
 use std::{borrow::Cow, fmt, str};
 
 use actix_web::{
-    http::header::{HeaderValue, InvalidHeaderValue, TryIntoHeaderValue},
+    error::ParseError,
+    http::header::{
+        Header, HeaderName, HeaderValue, InvalidHeaderValue, TryIntoHeaderValue, WWW_AUTHENTICATE,
+    },
     web::{BufMut, Bytes, BytesMut},
+    HttpMessage,
 };
 
-use super::Challenge;
+use super::super::Challenge;
+use super::BasicBuilder;
 use crate::utils;
 
 /// Challenge for [`WWW-Authenticate`] header with HTTP Basic auth scheme,
@@ -28,7 +31,18 @@ use crate::utils;
 /// }
 /// ```
 ///
-/// [`WWW-Authenticate`]: ../struct.WwwAuthenticate.html
+/// Since `Basic` implements [`Header`], it can also be inserted into a response directly:
+///
+/// ```
+/// # use actix_web::HttpResponse;
+/// use actix_web_httpauth::headers::www_authenticate::basic::Basic;
+///
+/// let res = HttpResponse::Unauthorized()
+///     .insert_header(Basic::build().realm("My App").finish())
+///     .finish();
+/// ```
+///
+/// [`WWW-Authenticate`]: crate::headers::www_authenticate::WwwAuthenticate
 #[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Clone)]
 pub struct Basic {
     // "realm" parameter is optional now: https://tools.ietf.org/html/rfc7235#appendix-A
@@ -69,6 +83,45 @@ impl Basic {
             realm: Some(value.into()),
         }
     }
+
+    /// Creates the builder for `Basic` challenge.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web_httpauth::headers::www_authenticate::basic::Basic;
+    /// let challenge = Basic::build().realm("Restricted area").finish();
+    /// ```
+    pub fn build() -> BasicBuilder {
+        BasicBuilder::default()
+    }
+
+    /// Parses a `Basic` challenge, returning `None` if it is for another scheme or malformed.
+    fn parse_challenge(value: &str) -> Option<Basic> {
+        let value = value.trim();
+        let scheme_len = value.find(utils::is_separator).unwrap_or(value.len());
+        let (scheme, mut rest) = value.split_at(scheme_len);
+
+        if !scheme.eq_ignore_ascii_case("Basic") {
+            return None;
+        }
+
+        let mut challenge = Basic::default();
+
+        loop {
+            rest = rest.trim_start_matches(utils::is_separator);
+            if rest.is_empty() {
+                return Some(challenge);
+            }
+
+            let (key, value, remaining) = utils::parse_auth_param(rest).ok()?;
+            rest = remaining;
+
+            // other parameters, e.g. `charset`, are ignored
+            if key.eq_ignore_ascii_case("realm") {
+                challenge.realm = Some(value.into());
+            }
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -108,6 +161,22 @@ impl TryIntoHeaderValue for Basic {
     }
 }
 
+impl Header for Basic {
+    #[inline]
+    fn name() -> HeaderName {
+        WWW_AUTHENTICATE
+    }
+
+    /// Parses the first `Basic` challenge of the `WWW-Authenticate` headers of `msg`.
+    fn parse<T: HttpMessage>(msg: &T) -> Result<Self, ParseError> {
+        msg.headers()
+            .get_all(WWW_AUTHENTICATE)
+            .filter_map(|value| value.to_str().ok())
+            .find_map(Basic::parse_challenge)
+            .ok_or(ParseError::Header)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
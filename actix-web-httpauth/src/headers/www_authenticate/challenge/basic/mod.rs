@@ -0,0 +1,58 @@
+//! Challenge for the "Basic" HTTP Authentication Scheme.
+
+mod builder;
+mod challenge;
+
+pub use self::builder::BasicBuilder;
+pub use self::challenge::Basic;
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::header::{Header as _, WWW_AUTHENTICATE},
+        test::TestRequest,
+        HttpResponse,
+    };
+
+    use super::*;
+
+    #[test]
+    fn to_bytes() {
+        let b = Basic::build().realm(r#"My "App" \ Admin"#).finish();
+
+        assert_eq!(r#"Basic realm="My \"App\" \\ Admin""#, b.to_string());
+    }
+
+    #[test]
+    fn insert_header() {
+        let res = HttpResponse::Unauthorized()
+            .insert_header(Basic::build().realm(r#"My "App""#).finish())
+            .finish();
+
+        assert_eq!(
+            res.headers().get(WWW_AUTHENTICATE).unwrap(),
+            r#"Basic realm="My \"App\"""#
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let req = TestRequest::default()
+            .append_header((WWW_AUTHENTICATE, r#"Bearer realm="api""#))
+            .append_header((
+                WWW_AUTHENTICATE,
+                r#"Basic realm="My \"App\" \\ Admin", charset=UTF-8"#,
+            ))
+            .to_http_request();
+
+        assert_eq!(
+            Basic::parse(&req).unwrap(),
+            Basic::build().realm(r#"My "App" \ Admin"#).finish()
+        );
+
+        let req = TestRequest::default()
+            .insert_header((WWW_AUTHENTICATE, r#"Basic realm="unterminated"#))
+            .to_http_request();
+        assert!(Basic::parse(&req).is_err());
+    }
+}
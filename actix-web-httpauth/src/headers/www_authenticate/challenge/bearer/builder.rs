@@ -1,13 +1,36 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, error::Error as StdError, fmt};
 
-use super::{Bearer, Error};
+use super::{challenge, Bearer, Error};
+
+/// Possible errors while building a `Bearer` challenge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BearerBuildError {
+    /// `error_uri` contains characters that RFC 6750 does not allow in the attribute, i.e. `"`,
+    /// `\`, whitespace or non-ASCII characters. They must be percent-encoded.
+    InvalidErrorUri(String),
+}
+
+impl fmt::Display for BearerBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BearerBuildError::InvalidErrorUri(uri) => write!(
+                f,
+                "Invalid error_uri ({:?}): quotes, backslashes, whitespace and non-ASCII \
+                 characters must be percent-encoded",
+                uri
+            ),
+        }
+    }
+}
+
+impl StdError for BearerBuildError {}
 
 /// Builder for the [`Bearer`] challenge.
 ///
-/// It is up to implementor to fill all required fields, neither this `Builder` nor [`Bearer`]
-/// provide any validation.
+/// It is up to implementor to fill all required fields. Apart from the characters allowed in
+/// [`error_uri`](Self::error_uri), neither this `Builder` nor [`Bearer`] provide any validation.
 #[derive(Debug, Default)]
-pub struct BearerBuilder(Bearer);
+pub struct BearerBuilder(Bearer, Option<BearerBuildError>);
 
 impl BearerBuilder {
     /// Provides the `scope` attribute, as defined in [RFC 6749 §3.3].
@@ -51,21 +74,78 @@ impl BearerBuilder {
         self
     }
 
+    /// Provides a localized `error_description` attribute for the language `lang`.
+    ///
+    /// The description that is sent is selected from the request's `Accept-Language` header using
+    /// [`Bearer::localize`]. The description set with [`error_description`](Self::error_description)
+    /// is used when no locale matches. Localized descriptions are only sent along with an
+    /// [`error`](Self::error).
+    pub fn error_description_locale<L, T>(mut self, lang: L, value: T) -> Self
+    where
+        L: Into<Cow<'static, str>>,
+        T: Into<Cow<'static, str>>,
+    {
+        self.0
+            .error_description_locales
+            .push((lang.into(), value.into()));
+        self
+    }
+
     /// Provides the `error_uri` attribute, as defined in [RFC 6750 §3].
     ///
-    /// It is up to implementor to provide properly-formed absolute URI.
+    /// It is up to implementor to provide properly-formed absolute URI. If `value` contains
+    /// characters that RFC 6750 does not allow in the attribute, i.e. `"`, `\`, whitespace or
+    /// non-ASCII characters, the attribute is left out and [`try_finish`](Self::try_finish)
+    /// returns an error. Use [`try_error_uri`](Self::try_error_uri) to check the URI right away.
     ///
     /// [RFC 6750 §3](https://tools.ietf.org/html/rfc6750#section-3)
-    pub fn error_uri<T>(mut self, value: T) -> Self
+    pub fn error_uri<T>(self, value: T) -> Self
     where
         T: Into<Cow<'static, str>>,
     {
-        self.0.error_uri = Some(value.into());
-        self
+        match challenge::validate_error_uri(value.into()) {
+            Ok(value) => BearerBuilder(
+                Bearer {
+                    error_uri: Some(value),
+                    ..self.0
+                },
+                self.1,
+            ),
+            Err(err) => BearerBuilder(self.0, self.1.or(Some(err))),
+        }
+    }
+
+    /// Provides the `error_uri` attribute, as defined in [RFC 6750 §3].
+    ///
+    /// Returns an error if `value` contains characters that RFC 6750 does not allow in the
+    /// attribute, i.e. `"`, `\`, whitespace or non-ASCII characters. They must be percent-encoded.
+    ///
+    /// [RFC 6750 §3](https://tools.ietf.org/html/rfc6750#section-3)
+    pub fn try_error_uri<T>(mut self, value: T) -> Result<Self, BearerBuildError>
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.0.error_uri = Some(challenge::validate_error_uri(value.into())?);
+        Ok(self)
     }
 
     /// Consumes the builder and returns built `Bearer` instance.
+    ///
+    /// Attributes with invalid values are left out, see [`try_finish`](Self::try_finish).
     pub fn finish(self) -> Bearer {
+        if let Some(err) = self.1 {
+            log::warn!("Bearer challenge attribute left out: {}", err);
+        }
+
         self.0
     }
+
+    /// Consumes the builder and returns built `Bearer` instance, or the first error encountered
+    /// while building it.
+    pub fn try_finish(self) -> Result<Bearer, BearerBuildError> {
+        match self.1 {
+            Some(err) => Err(err),
+            None => Ok(self.0),
+        }
+    }
 }
@@ -1,12 +1,14 @@
 use std::{borrow::Cow, fmt, str};
 
 use actix_web::{
-    http::header::{HeaderValue, InvalidHeaderValue, TryIntoHeaderValue},
+    http::header::{
+        AcceptLanguage, HeaderValue, InvalidHeaderValue, Preference, TryIntoHeaderValue,
+    },
     web::{BufMut, Bytes, BytesMut},
 };
 
 use super::super::Challenge;
-use super::{BearerBuilder, Error};
+use super::{BearerBuildError, BearerBuilder, Error};
 use crate::utils;
 
 /// Challenge for [`WWW-Authenticate`] header with HTTP Bearer auth scheme, described in [RFC 6750].
@@ -43,6 +45,7 @@ pub struct Bearer {
     pub(crate) error: Option<Error>,
     pub(crate) error_description: Option<Cow<'static, str>>,
     pub(crate) error_uri: Option<Cow<'static, str>>,
+    pub(crate) error_description_locales: Vec<(Cow<'static, str>, Cow<'static, str>)>,
 }
 
 impl Bearer {
@@ -59,6 +62,83 @@ impl Bearer {
     pub fn build() -> BearerBuilder {
         BearerBuilder::default()
     }
+
+    /// Selects the `error_description` that best matches the client's language preferences.
+    ///
+    /// Descriptions provided using [`BearerBuilder::error_description_locale`] are matched against
+    /// the ranked languages of `accept_language`, first by full language tag and then by primary
+    /// language subtag. If no locale matches, the default `error_description` is kept.
+    ///
+    /// Challenges without an `error` are left unchanged, since [RFC 6750 §3.1] forbids error
+    /// information in the response to a request that lacks authentication information.
+    ///
+    /// [RFC 6750 §3.1]: https://tools.ietf.org/html/rfc6750#section-3.1
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::http::header::AcceptLanguage;
+    /// # use actix_web_httpauth::headers::www_authenticate::bearer::{Bearer, Error};
+    /// let mut challenge = Bearer::build()
+    ///     .error(Error::InvalidToken)
+    ///     .error_description("The access token expired")
+    ///     .error_description_locale("de", "Das Zugriffstoken ist abgelaufen")
+    ///     .finish();
+    ///
+    /// challenge.localize(&AcceptLanguage(vec!["de-CH".parse().unwrap()]));
+    ///
+    /// assert_eq!(
+    ///     challenge.to_string(),
+    ///     r#"Bearer error="invalid_token" error_description="Das Zugriffstoken ist abgelaufen""#,
+    /// );
+    /// ```
+    pub fn localize(&mut self, accept_language: &AcceptLanguage) {
+        if self.error.is_none() {
+            return;
+        }
+
+        for lang in accept_language.ranked() {
+            let lang = match lang {
+                Preference::Specific(lang) => lang,
+                Preference::Any => return,
+            };
+
+            let desc = self
+                .error_description_locales
+                .iter()
+                .find(|(locale, _)| locale.eq_ignore_ascii_case(lang.as_str()))
+                .or_else(|| {
+                    self.error_description_locales.iter().find(|(locale, _)| {
+                        let primary = locale.split('-').next().unwrap_or_default();
+                        primary.eq_ignore_ascii_case(lang.primary_language())
+                    })
+                });
+
+            if let Some((_, desc)) = desc {
+                self.error_description = Some(desc.clone());
+                return;
+            }
+        }
+    }
+}
+
+/// Returns an error if `uri` contains characters that are not allowed in the `error_uri`
+/// attribute.
+///
+/// See the `error-uri` ABNF of [RFC 6750 Appendix A.4].
+///
+/// [RFC 6750 Appendix A.4]: https://tools.ietf.org/html/rfc6750#appendix-A.4
+pub(crate) fn validate_error_uri(
+    uri: Cow<'static, str>,
+) -> Result<Cow<'static, str>, BearerBuildError> {
+    let valid = uri
+        .bytes()
+        .all(|b| matches!(b, 0x21 | 0x23..=0x5B | 0x5D..=0x7E));
+
+    if valid {
+        Ok(uri)
+    } else {
+        Err(BearerBuildError::InvalidErrorUri(uri.into_owned()))
+    }
 }
 
 #[doc(hidden)]
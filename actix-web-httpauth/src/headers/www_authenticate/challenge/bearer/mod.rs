@@ -3,13 +3,19 @@
 mod builder;
 mod challenge;
 mod errors;
+mod parser;
 
-pub use self::builder::BearerBuilder;
+pub use self::builder::{BearerBuildError, BearerBuilder};
 pub use self::challenge::Bearer;
 pub use self::errors::Error;
+pub use self::parser::BearerParseError;
+
+pub(crate) use self::challenge::validate_error_uri;
 
 #[cfg(test)]
 mod tests {
+    use actix_web::http::header::AcceptLanguage;
+
     use super::*;
 
     #[test]
@@ -24,4 +30,92 @@ mod tests {
         format!("{}", b)
     );
     }
+
+    #[test]
+    fn scope() {
+        let b = Bearer::build()
+            .realm("example")
+            .scope("read write")
+            .error(Error::InsufficientScope)
+            .finish();
+
+        assert_eq!(
+            r#"Bearer realm="example" scope="read write" error="insufficient_scope""#,
+            b.to_string()
+        );
+    }
+
+    #[test]
+    fn error_uri() {
+        let b = Bearer::build()
+            .error(Error::InvalidToken)
+            .error_description("The access token expired")
+            .error_uri("https://example.com/errors/invalid_token?lang=en#expired")
+            .finish();
+
+        assert_eq!(
+            "Bearer error=\"invalid_token\" error_description=\"The access token expired\" \
+             error_uri=\"https://example.com/errors/invalid_token?lang=en#expired\"",
+            b.to_string()
+        );
+    }
+
+    #[test]
+    fn error_uri_with_quote() {
+        let uri = "https://example.com/\"errors\"";
+
+        let err = Bearer::build().try_error_uri(uri).unwrap_err();
+        assert_eq!(err, BearerBuildError::InvalidErrorUri(uri.to_owned()));
+
+        let err = Bearer::build()
+            .error(Error::InvalidToken)
+            .error_uri(uri)
+            .try_finish()
+            .unwrap_err();
+        assert_eq!(err, BearerBuildError::InvalidErrorUri(uri.to_owned()));
+
+        // the invalid URI is left out
+        let challenge = Bearer::build()
+            .error(Error::InvalidToken)
+            .error_uri(uri)
+            .finish();
+        assert_eq!(challenge.to_string(), r#"Bearer error="invalid_token""#);
+    }
+
+    #[test]
+    fn localize() {
+        let challenge = Bearer::build()
+            .error(Error::InvalidToken)
+            .error_description("The access token expired")
+            .error_description_locale("fr", "Le jeton d'accès a expiré")
+            .error_description_locale("pt-BR", "O token de acesso expirou")
+            .finish();
+
+        let localized = |header: &str| {
+            let mut challenge = challenge.clone();
+            let accept_language = header
+                .split(',')
+                .map(|lang| lang.trim().parse().unwrap())
+                .collect();
+            challenge.localize(&AcceptLanguage(accept_language));
+            challenge.error_description.unwrap()
+        };
+
+        assert_eq!(localized("fr-CA"), "Le jeton d'accès a expiré");
+        assert_eq!(localized("pt-br"), "O token de acesso expirou");
+        assert_eq!(localized("de, fr;q=0.5"), "Le jeton d'accès a expiré");
+        assert_eq!(localized("de"), "The access token expired");
+        assert_eq!(localized("*, fr;q=0.5"), "The access token expired");
+    }
+
+    #[test]
+    fn localize_without_error() {
+        let mut challenge = Bearer::build()
+            .realm("example")
+            .error_description_locale("fr", "Le jeton d'accès a expiré")
+            .finish();
+
+        challenge.localize(&AcceptLanguage(vec!["fr".parse().unwrap()]));
+        assert_eq!(challenge.error_description, None);
+    }
 }
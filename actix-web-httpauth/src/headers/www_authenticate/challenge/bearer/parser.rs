@@ -0,0 +1,219 @@
+use std::{borrow::Cow, error::Error as StdError, fmt, str::FromStr};
+
+use super::{Bearer, Error};
+use crate::utils::{is_separator, parse_auth_param};
+
+/// Possible errors while parsing a `Bearer` challenge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BearerParseError {
+    /// Challenge does not start with an authentication scheme.
+    MissingSchemeName,
+
+    /// Challenge is for a scheme other than `Bearer`.
+    UnknownScheme(String),
+
+    /// Attribute with the given name, or the part of the challenge following the last valid
+    /// attribute, is malformed.
+    MalformedAttribute(String),
+
+    /// Attribute is not defined for the `Bearer` scheme.
+    UnrecognizedKey(String),
+}
+
+impl fmt::Display for BearerParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BearerParseError::MissingSchemeName => f.write_str("Missing authentication scheme"),
+            BearerParseError::UnknownScheme(scheme) => {
+                write!(f, "Unknown authentication scheme ({})", scheme)
+            }
+            BearerParseError::MalformedAttribute(attr) => {
+                write!(f, "Malformed attribute ({})", attr)
+            }
+            BearerParseError::UnrecognizedKey(key) => write!(f, "Unrecognized attribute ({})", key),
+        }
+    }
+}
+
+impl StdError for BearerParseError {}
+
+impl Bearer {
+    /// Parses a `Bearer` challenge, e.g. from the `WWW-Authenticate` header of a response.
+    ///
+    /// Attributes may be separated by commas, as [RFC 7235 §4.1] requires, or by whitespace only,
+    /// as they are serialized by this crate. Localized error descriptions are not recovered.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web_httpauth::headers::www_authenticate::bearer::{Bearer, Error};
+    ///
+    /// let challenge =
+    ///     Bearer::parse(r#"Bearer realm="example", error="invalid_token""#).unwrap();
+    ///
+    /// assert_eq!(
+    ///     challenge,
+    ///     Bearer::build()
+    ///         .realm("example")
+    ///         .error(Error::InvalidToken)
+    ///         .finish(),
+    /// );
+    /// ```
+    ///
+    /// [RFC 7235 §4.1]: https://tools.ietf.org/html/rfc7235#section-4.1
+    pub fn parse(s: &str) -> Result<Bearer, BearerParseError> {
+        let s = s.trim_start();
+        let scheme_len = s.find(is_separator).unwrap_or(s.len());
+        let (scheme, mut rest) = s.split_at(scheme_len);
+
+        if scheme.is_empty() {
+            return Err(BearerParseError::MissingSchemeName);
+        }
+
+        if !scheme.eq_ignore_ascii_case("Bearer") {
+            return Err(BearerParseError::UnknownScheme(scheme.to_owned()));
+        }
+
+        let mut challenge = Bearer::default();
+
+        loop {
+            rest = rest.trim_start_matches(is_separator);
+            if rest.is_empty() {
+                return Ok(challenge);
+            }
+
+            let (key, value, remaining) = parse_auth_param(rest).map_err(malformed)?;
+            rest = remaining;
+
+            let value = Cow::Owned(value);
+            match key.to_ascii_lowercase().as_str() {
+                "realm" => challenge.realm = Some(value),
+                "scope" => challenge.scope = Some(value),
+                "error" => challenge.error = Some(error(&value).ok_or_else(|| malformed(key))?),
+                "error_description" => challenge.error_description = Some(value),
+                "error_uri" => challenge.error_uri = Some(value),
+                _ => return Err(BearerParseError::UnrecognizedKey(key.to_owned())),
+            }
+        }
+    }
+}
+
+impl FromStr for Bearer {
+    type Err = BearerParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Bearer::parse(s)
+    }
+}
+
+fn malformed(attr: &str) -> BearerParseError {
+    BearerParseError::MalformedAttribute(attr.to_owned())
+}
+
+fn error(value: &str) -> Option<Error> {
+    match value {
+        "invalid_request" => Some(Error::InvalidRequest),
+        "invalid_token" => Some(Error::InvalidToken),
+        "insufficient_scope" => Some(Error::InsufficientScope),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let challenge = Bearer::parse(
+            r#"Bearer realm="example", scope="read write", error="insufficient_scope",
+               error_description="The \"read\" scope is required", error_uri=https://example.com"#,
+        )
+        .unwrap();
+
+        assert_eq!(challenge.realm.as_deref(), Some("example"));
+        assert_eq!(challenge.scope.as_deref(), Some("read write"));
+        assert_eq!(challenge.error, Some(Error::InsufficientScope));
+        assert_eq!(
+            challenge.error_description.as_deref(),
+            Some("The \"read\" scope is required")
+        );
+        assert_eq!(challenge.error_uri.as_deref(), Some("https://example.com"));
+
+        assert_eq!("bearer".parse::<Bearer>().unwrap(), Bearer::default());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Bearer::parse("  "),
+            Err(BearerParseError::MissingSchemeName)
+        );
+        assert_eq!(
+            Bearer::parse(r#"Basic realm="example""#),
+            Err(BearerParseError::UnknownScheme("Basic".to_owned()))
+        );
+        assert_eq!(
+            Bearer::parse(r#"Bearer realm="example"#),
+            Err(BearerParseError::MalformedAttribute("realm".to_owned()))
+        );
+        assert_eq!(
+            Bearer::parse(r#"Bearer error="unknown""#),
+            Err(BearerParseError::MalformedAttribute("error".to_owned()))
+        );
+        assert_eq!(
+            Bearer::parse("Bearer realm"),
+            Err(BearerParseError::MalformedAttribute("realm".to_owned()))
+        );
+        assert_eq!(
+            Bearer::parse(r#"Bearer nonce="abc""#),
+            Err(BearerParseError::UnrecognizedKey("nonce".to_owned()))
+        );
+    }
+
+    fn error_strategy() -> impl Strategy<Value = Option<Error>> {
+        prop_oneof![
+            Just(None),
+            Just(Some(Error::InvalidRequest)),
+            Just(Some(Error::InvalidToken)),
+            Just(Some(Error::InsufficientScope)),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(
+            realm in proptest::option::of("\\PC*"),
+            scope in proptest::option::of("\\PC*"),
+            error in error_strategy(),
+            error_description in proptest::option::of("\\PC*"),
+            error_uri in proptest::option::of("[!#-\\[\\]-~]*"),
+        ) {
+            let mut builder = Bearer::build();
+            if let Some(realm) = realm {
+                builder = builder.realm(realm);
+            }
+            if let Some(scope) = scope {
+                builder = builder.scope(scope);
+            }
+            if let Some(error) = error {
+                builder = builder.error(error);
+            }
+            if let Some(error_description) = error_description {
+                builder = builder.error_description(error_description);
+            }
+            if let Some(error_uri) = error_uri {
+                builder = builder.error_uri(error_uri);
+            }
+            let challenge = builder.finish();
+
+            prop_assert_eq!(Bearer::parse(&challenge.to_string()), Ok(challenge));
+        }
+
+        #[test]
+        fn test_parse_arbitrary(s in "\\PC*") {
+            let _ = Bearer::parse(&s);
+        }
+    }
+}
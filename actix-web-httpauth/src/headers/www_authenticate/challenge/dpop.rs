@@ -0,0 +1,175 @@
+//! Challenge for the "DPoP" HTTP Authentication Scheme.
+
+use std::{borrow::Cow, fmt, str};
+
+use actix_web::{
+    http::{
+        header::{HeaderValue, InvalidHeaderValue, TryIntoHeaderValue},
+        StatusCode,
+    },
+    web::{BufMut, Bytes, BytesMut},
+};
+
+use super::Challenge;
+use crate::utils;
+
+/// Challenge for [`WWW-Authenticate`] header with the HTTP DPoP auth scheme, described in
+/// [RFC 9449 §7.1](https://tools.ietf.org/html/rfc9449#section-7.1).
+///
+/// The `algs` attribute lists the algorithms accepted for DPoP proofs, so that clients can pick
+/// one they support.
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::headers::www_authenticate::dpop::DPoP;
+///
+/// let challenge = DPoP::with_algs("ES256 EdDSA");
+/// assert_eq!(challenge.to_string(), "DPoP algs=\"ES256 EdDSA\"");
+/// ```
+///
+/// [`WWW-Authenticate`]: super::WwwAuthenticate
+#[cfg_attr(docsrs, doc(cfg(feature = "dpop")))]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Clone)]
+pub struct DPoP {
+    pub(crate) realm: Option<Cow<'static, str>>,
+    pub(crate) error: Option<Error>,
+    pub(crate) error_description: Option<Cow<'static, str>>,
+    pub(crate) algs: Option<Cow<'static, str>>,
+}
+
+impl DPoP {
+    /// Creates new `DPoP` challenge without any attributes.
+    pub fn new() -> DPoP {
+        Default::default()
+    }
+
+    /// Creates new `DPoP` challenge from the provided space-delimited list of accepted algorithms.
+    pub fn with_algs<T>(value: T) -> DPoP
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        DPoP {
+            algs: Some(value.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the error of the challenge, if any.
+    pub fn error(&self) -> Option<Error> {
+        self.error
+    }
+
+    /// Returns the space-delimited list of accepted algorithms, if set.
+    pub fn algs(&self) -> Option<&str> {
+        self.algs.as_deref()
+    }
+}
+
+#[doc(hidden)]
+impl Challenge for DPoP {
+    fn to_bytes(&self) -> Bytes {
+        let mut buffer = BytesMut::with_capacity(128);
+        buffer.put(&b"DPoP"[..]);
+
+        let attributes = [
+            ("realm", self.realm.as_deref()),
+            ("error", self.error.as_ref().map(Error::as_str)),
+            ("error_description", self.error_description.as_deref()),
+            ("algs", self.algs.as_deref()),
+        ];
+
+        let mut separator = &b" "[..];
+        for (name, value) in attributes {
+            if let Some(value) = value {
+                buffer.put(separator);
+                buffer.put(name.as_bytes());
+                buffer.put(&b"=\""[..]);
+                utils::put_quoted(&mut buffer, value);
+                buffer.put_u8(b'"');
+                separator = &b", "[..];
+            }
+        }
+
+        buffer.freeze()
+    }
+}
+
+impl fmt::Display for DPoP {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let bytes = self.to_bytes();
+        let repr = str::from_utf8(&bytes)
+            // Should not happen since challenges are crafted manually
+            // from a `&'static str` or `String`
+            .map_err(|_| fmt::Error)?;
+
+        f.write_str(repr)
+    }
+}
+
+impl TryIntoHeaderValue for DPoP {
+    type Error = InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        HeaderValue::from_maybe_shared(self.to_bytes())
+    }
+}
+
+/// DPoP authorization error types, described in [RFC 9449 §7.1].
+///
+/// [RFC 9449 §7.1]: https://tools.ietf.org/html/rfc9449#section-7.1
+#[cfg_attr(docsrs, doc(cfg(feature = "dpop")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Error {
+    /// The access token provided is expired, revoked, malformed, or invalid for other reasons.
+    InvalidToken,
+
+    /// The DPoP proof provided is missing, malformed, or invalid for other reasons.
+    InvalidDPoPProof,
+}
+
+impl Error {
+    /// Returns [HTTP status code] suitable for current error type.
+    ///
+    /// [HTTP status code]: `actix_web::http::StatusCode`
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn status_code(&self) -> StatusCode {
+        StatusCode::UNAUTHORIZED
+    }
+
+    #[doc(hidden)]
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Error::InvalidToken => "invalid_token",
+            Error::InvalidDPoPProof => "invalid_dpop_proof",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_header_value() {
+        let challenge = DPoP {
+            realm: Some("example".into()),
+            error: Some(Error::InvalidDPoPProof),
+            error_description: Some("The DPoP proof is not fresh".into()),
+            algs: Some("ES256 PS256".into()),
+        };
+
+        let value = challenge.try_into_value().unwrap();
+        assert_eq!(
+            value,
+            "DPoP realm=\"example\", error=\"invalid_dpop_proof\", \
+             error_description=\"The DPoP proof is not fresh\", algs=\"ES256 PS256\""
+        );
+    }
+}
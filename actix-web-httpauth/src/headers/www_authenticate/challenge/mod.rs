@@ -4,6 +4,10 @@ use actix_web::{http::header::TryIntoHeaderValue, web::Bytes};
 
 pub mod basic;
 pub mod bearer;
+#[cfg(feature = "dpop")]
+pub mod dpop;
+#[cfg(feature = "scram")]
+pub mod scram;
 
 /// Authentication challenge for `WWW-Authenticate` header.
 pub trait Challenge: TryIntoHeaderValue + Debug + Display + Clone + Send + Sync {
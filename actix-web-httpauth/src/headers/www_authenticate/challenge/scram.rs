@@ -0,0 +1,136 @@
+//! Challenge for the "SCRAM-SHA-256" HTTP Authentication Scheme.
+
+use std::{borrow::Cow, fmt, str};
+
+use actix_web::{
+    http::header::{HeaderValue, InvalidHeaderValue, TryIntoHeaderValue},
+    web::{BufMut, Bytes, BytesMut},
+};
+
+use super::Challenge;
+use crate::utils;
+
+/// Challenge for [`WWW-Authenticate`] header with the HTTP SCRAM-SHA-256 auth scheme,
+/// described in [RFC 7804](https://tools.ietf.org/html/rfc7804).
+///
+/// The initial challenge only carries the `realm`. Once the client has sent its first message,
+/// the server responds with a challenge carrying the session ID (`sid`) of the exchange and the
+/// base64 encoded server-first message (`data`).
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::headers::www_authenticate::scram::Scram;
+///
+/// let challenge = Scram::with_realm("Restricted area");
+/// assert_eq!(challenge.to_string(), "SCRAM-SHA-256 realm=\"Restricted area\"");
+/// ```
+///
+/// [`WWW-Authenticate`]: super::WwwAuthenticate
+#[cfg_attr(docsrs, doc(cfg(feature = "scram")))]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Clone)]
+pub struct Scram {
+    pub(crate) realm: Option<Cow<'static, str>>,
+    pub(crate) sid: Option<String>,
+    pub(crate) data: Option<String>,
+}
+
+impl Scram {
+    /// Creates new `Scram` challenge with an empty `realm` field.
+    pub fn new() -> Scram {
+        Default::default()
+    }
+
+    /// Creates new `Scram` challenge from the provided `realm` field value.
+    pub fn with_realm<T>(value: T) -> Scram
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        Scram {
+            realm: Some(value.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the session ID of the exchange, if the challenge continues one.
+    pub fn sid(&self) -> Option<&str> {
+        self.sid.as_deref()
+    }
+
+    /// Returns the base64 encoded server-first message, if the challenge continues an exchange.
+    pub fn data(&self) -> Option<&str> {
+        self.data.as_deref()
+    }
+}
+
+#[doc(hidden)]
+impl Challenge for Scram {
+    fn to_bytes(&self) -> Bytes {
+        let mut buffer = BytesMut::with_capacity(64);
+        buffer.put(&b"SCRAM-SHA-256"[..]);
+
+        let mut separator = &b" "[..];
+        if let Some(ref realm) = self.realm {
+            buffer.put(separator);
+            buffer.put(&b"realm=\""[..]);
+            utils::put_quoted(&mut buffer, realm);
+            buffer.put_u8(b'"');
+            separator = &b", "[..];
+        }
+
+        if let Some(ref sid) = self.sid {
+            buffer.put(separator);
+            buffer.put(&b"sid="[..]);
+            buffer.extend_from_slice(sid.as_bytes());
+            separator = &b", "[..];
+        }
+
+        if let Some(ref data) = self.data {
+            buffer.put(separator);
+            buffer.put(&b"data="[..]);
+            buffer.extend_from_slice(data.as_bytes());
+        }
+
+        buffer.freeze()
+    }
+}
+
+impl fmt::Display for Scram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let bytes = self.to_bytes();
+        let repr = str::from_utf8(&bytes)
+            // Should not happen since challenges are crafted manually
+            // from a `&'static str` or `String`
+            .map_err(|_| fmt::Error)?;
+
+        f.write_str(repr)
+    }
+}
+
+impl TryIntoHeaderValue for Scram {
+    type Error = InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        HeaderValue::from_maybe_shared(self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_continuation_into_header_value() {
+        let challenge = Scram {
+            realm: Some("Restricted area".into()),
+            sid: Some("AAAABBBBCCCCDDDD".to_owned()),
+            data: Some("cj1yT3ByTkdmd0ViZVJXZ2JORWtxTyU".to_owned()),
+        };
+
+        let value = challenge.try_into_value().unwrap();
+        assert_eq!(
+            value,
+            "SCRAM-SHA-256 realm=\"Restricted area\", sid=AAAABBBBCCCCDDDD, \
+             data=cj1yT3ByTkdmd0ViZVJXZ2JORWtxTyU"
+        );
+    }
+}
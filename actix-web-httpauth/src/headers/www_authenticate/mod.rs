@@ -5,3 +5,8 @@ mod header;
 
 pub use self::challenge::{basic, bearer, Challenge};
 pub use self::header::WwwAuthenticate;
+
+#[cfg(feature = "dpop")]
+pub use self::challenge::dpop;
+#[cfg(feature = "scram")]
+pub use self::challenge::scram;
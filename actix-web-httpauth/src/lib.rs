@@ -4,21 +4,33 @@
 //! - Typed [Authorization] and [WWW-Authenticate] headers
 //! - [Extractors] for an [Authorization] header
 //! - [Middleware] for easier authorization checking
+//! - [Guards] for routing requests with bearer credentials
+//! - [Validators] for common bearer token formats
+//! - [PKCE] verification for OAuth 2.0 token endpoints
 //!
 //! ## Supported schemes
 //! - `Bearer` as defined in [RFC 6750](https://tools.ietf.org/html/rfc6750).
 //! - `Basic` as defined in [RFC 7617](https://tools.ietf.org/html/rfc7617).
+//! - `SCRAM-SHA-256` as defined in [RFC 7804](https://tools.ietf.org/html/rfc7804), with the
+//!   `scram` crate feature.
 //!
 //! [Authorization]: `self::headers::authorization::Authorization`
 //! [WWW-Authenticate]: `self::headers::www_authenticate::WwwAuthenticate`
 //! [Extractors]: https://actix.rs/docs/extractors
 //! [Middleware]: self::middleware
+//! [Guards]: self::guard
+//! [Validators]: self::validators
+//! [PKCE]: self::pkce
 
 #![forbid(unsafe_code)]
 #![deny(rust_2018_idioms, nonstandard_style)]
 #![warn(future_incompatible, missing_docs)]
+#![cfg_attr(docsrs, feature(doc_cfg))]
 
 pub mod extractors;
+pub mod guard;
 pub mod headers;
 pub mod middleware;
+pub mod pkce;
 mod utils;
+pub mod validators;
@@ -12,17 +12,53 @@ use std::{
 use actix_web::{
     body::{EitherBody, MessageBody},
     dev::{Service, ServiceRequest, ServiceResponse, Transform},
-    Error, FromRequest,
+    http::header::{
+        ContentType, HeaderName, HeaderValue, TryIntoHeaderValue as _, AUTHORIZATION, CONTENT_TYPE,
+    },
+    Error, FromRequest, HttpMessage as _, HttpResponse,
 };
 use futures_core::ready;
-use futures_util::future::{self, LocalBoxFuture, TryFutureExt as _};
+use futures_util::future::{self, FutureExt as _, LocalBoxFuture, TryFutureExt as _};
 
-use crate::extractors::{basic, bearer};
+use crate::{
+    extractors::{basic, bearer},
+    validators::{TokenValidator, ValidatedToken},
+};
+
+mod any;
+mod cache;
+mod forward;
+mod redact;
+#[cfg(feature = "refresh")]
+mod refresh;
+#[cfg(feature = "session")]
+mod session;
+mod size_limit;
+#[cfg(feature = "totp")]
+mod totp;
+
+pub use self::any::{boxed_basic, boxed_bearer, boxed_validator, BoxedValidator};
+pub use self::cache::CachingValidator;
+pub use self::forward::ServiceRequestAuthExt;
+pub use self::redact::TokenLogRedactor;
+#[cfg(feature = "refresh")]
+pub use self::refresh::{AutoRefreshMiddleware, X_ACCESS_TOKEN};
+#[cfg(feature = "session")]
+pub use self::session::SessionAuth;
+pub use self::size_limit::AuthHeaderSizeLimit;
+#[cfg(feature = "totp")]
+pub use self::totp::{TotpSecondFactor, X_OTP};
+
+/// Name of the response header indicating the authentication scheme used by a request.
+///
+/// See [`HttpAuthentication::emit_scheme_header`].
+pub const X_AUTH_SCHEME: HeaderName = HeaderName::from_static("x-auth-scheme");
 
 /// Middleware for checking HTTP authentication.
 ///
 /// If there is no `Authorization` header in the request, this middleware returns an error
-/// immediately, without calling the `F` callback.
+/// immediately, without calling the `F` callback, unless it was constructed using
+/// [`optional`](Self::optional).
 ///
 /// Otherwise, it will pass both the request and the parsed credentials into it. In case of
 /// successful validation `F` callback is required to return the `ServiceRequest` back.
@@ -32,6 +68,9 @@ where
     T: FromRequest,
 {
     process_fn: Arc<F>,
+    emit_scheme_header: bool,
+    missing_credentials_message: Option<(HeaderValue, Arc<str>)>,
+    optional: bool,
     _extractor: PhantomData<T>,
 }
 
@@ -46,9 +85,151 @@ where
     pub fn with_fn(process_fn: F) -> HttpAuthentication<T, F> {
         HttpAuthentication {
             process_fn: Arc::new(process_fn),
+            emit_scheme_header: false,
+            missing_credentials_message: None,
+            optional: false,
             _extractor: PhantomData,
         }
     }
+
+    /// Construct `HttpAuthentication` middleware with the provided auth extractor `T` and
+    /// validation callback `F` that lets requests without an `Authorization` header through.
+    ///
+    /// Requests with credentials are handled as by [`with_fn`](Self::with_fn): malformed or
+    /// rejected credentials still result in an error. Requests without an `Authorization` header
+    /// are passed to the wrapped service as is, without calling `F`, so that public endpoints can
+    /// serve anonymous clients while enriching responses for authenticated ones. Handlers can tell
+    /// them apart by extracting `Option<T>`, or by checking the request extensions.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::{dev::ServiceRequest, http::header::ContentType, Error};
+    /// # use actix_web_httpauth::{extractors::bearer::BearerAuth, middleware::HttpAuthentication};
+    /// async fn validator(
+    ///     req: ServiceRequest,
+    ///     _credentials: BearerAuth,
+    /// ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    ///     Ok(req)
+    /// }
+    ///
+    /// async fn index(credentials: Option<BearerAuth>) -> &'static str {
+    ///     match credentials {
+    ///         Some(_) => "Hello, authenticated client!",
+    ///         None => "Hello, anonymous client!",
+    ///     }
+    /// }
+    ///
+    /// let middleware = HttpAuthentication::optional(validator);
+    /// ```
+    pub fn optional(process_fn: F) -> HttpAuthentication<T, F> {
+        HttpAuthentication {
+            optional: true,
+            ..Self::with_fn(process_fn)
+        }
+    }
+}
+
+impl<T, F> HttpAuthentication<T, F>
+where
+    T: FromRequest,
+{
+    /// Sets whether successfully authenticated responses include an `X-Auth-Scheme` header
+    /// containing the scheme of the request's `Authorization` header, e.g. `Bearer`.
+    ///
+    /// Useful for clients behind several authentication layers that need to know which scheme was
+    /// applied. Disabled by default.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::{dev::ServiceRequest, http::header::ContentType, Error};
+    /// # use actix_web_httpauth::{extractors::bearer::BearerAuth, middleware::HttpAuthentication};
+    /// async fn validator(
+    ///     req: ServiceRequest,
+    ///     _credentials: BearerAuth,
+    /// ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    ///     Ok(req)
+    /// }
+    ///
+    /// let middleware = HttpAuthentication::bearer(validator).emit_scheme_header(true);
+    /// ```
+    pub fn emit_scheme_header(mut self, emit: bool) -> Self {
+        self.emit_scheme_header = emit;
+        self
+    }
+
+    /// Sets the body of `401 Unauthorized` responses to requests without an `Authorization`
+    /// header.
+    ///
+    /// Lets API consumers tell a forgotten header apart from invalid credentials, which are
+    /// rejected as before. The message is sent with the given `Content-Type`. Not set by default.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::{dev::ServiceRequest, http::header::ContentType, Error};
+    /// # use actix_web_httpauth::{extractors::bearer::BearerAuth, middleware::HttpAuthentication};
+    /// # async fn validator(
+    /// #     req: ServiceRequest,
+    /// #     _credentials: BearerAuth,
+    /// # ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    /// #     Ok(req)
+    /// # }
+    /// let middleware = HttpAuthentication::bearer(validator)
+    ///     .missing_credentials_message(
+    ///         ContentType::json(),
+    ///         r#"{"error": "Authorization header is missing"}"#,
+    ///     );
+    /// ```
+    pub fn missing_credentials_message(
+        mut self,
+        content_type: ContentType,
+        msg: impl Into<String>,
+    ) -> Self {
+        let content_type = content_type
+            .try_into_value()
+            .expect("MIME types are valid header values");
+        self.missing_credentials_message = Some((content_type, msg.into().into()));
+        self
+    }
+}
+
+impl<T, F> HttpAuthentication<T, F>
+where
+    T: FromRequest,
+    F: Fn(ServiceRequest, T) -> Result<ServiceRequest, (Error, ServiceRequest)>,
+{
+    /// Construct `HttpAuthentication` middleware with the provided auth extractor `T` and a
+    /// synchronous validation callback `F`.
+    ///
+    /// Useful for simple validators that don't need to perform any async I/O.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::{error::ErrorForbidden, dev::ServiceRequest, Error};
+    /// # use actix_web_httpauth::{extractors::basic::BasicAuth, middleware::HttpAuthentication};
+    /// fn validator(
+    ///     req: ServiceRequest,
+    ///     credentials: BasicAuth,
+    /// ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    ///     if credentials.user_id() == "admin" {
+    ///         Ok(req)
+    ///     } else {
+    ///         Err((ErrorForbidden("Admins only"), req))
+    ///     }
+    /// }
+    ///
+    /// let middleware = HttpAuthentication::sync(validator);
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn sync(
+        validator: F,
+    ) -> HttpAuthentication<
+        T,
+        impl Fn(ServiceRequest, T) -> future::Ready<Result<ServiceRequest, (Error, ServiceRequest)>>,
+    > {
+        HttpAuthentication::with_fn(move |req, credentials| {
+            future::ready(validator(req, credentials))
+        })
+    }
 }
 
 impl<F, O> HttpAuthentication<basic::BasicAuth, F>
@@ -117,6 +298,85 @@ where
     }
 }
 
+impl<V> HttpAuthentication<bearer::BearerAuth, V>
+where
+    V: TokenValidator + 'static,
+{
+    /// Construct `HttpAuthentication` middleware for the HTTP "Bearer" authentication scheme that
+    /// checks tokens using a [`TokenValidator`].
+    ///
+    /// Successfully validated tokens are inserted into the request extensions as a
+    /// [`ValidatedToken`], holding both the raw token and its claims. Rejected tokens result in a
+    /// `401 Unauthorized` response with an `invalid_token` challenge, formed using the
+    /// [`bearer::Config`] registered as app data (if any).
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::HttpRequest;
+    /// # use futures_util::future::{self, LocalBoxFuture};
+    /// # use actix_web_httpauth::{
+    /// #     middleware::HttpAuthentication,
+    /// #     validators::{TokenValidator, ValidationError},
+    /// # };
+    /// struct StaticToken;
+    ///
+    /// impl TokenValidator for StaticToken {
+    ///     type Claims = ();
+    ///
+    ///     fn validate(
+    ///         &self,
+    ///         _req: &HttpRequest,
+    ///         token: &str,
+    ///     ) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+    ///         let res = if token == "mF_9.B5f-4.1JqM" {
+    ///             Ok(())
+    ///         } else {
+    ///             Err(ValidationError::invalid("Unknown token"))
+    ///         };
+    ///
+    ///         Box::pin(future::ready(res))
+    ///     }
+    /// }
+    ///
+    /// let middleware = HttpAuthentication::bearer_validator(StaticToken);
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn bearer_validator(
+        validator: V,
+    ) -> HttpAuthentication<
+        bearer::BearerAuth,
+        impl Fn(
+            ServiceRequest,
+            bearer::BearerAuth,
+        ) -> LocalBoxFuture<'static, Result<ServiceRequest, (Error, ServiceRequest)>>,
+    > {
+        let validator = Rc::new(validator);
+
+        HttpAuthentication::with_fn(
+            move |req: ServiceRequest, credentials: bearer::BearerAuth| {
+                let fut = validator.validate(req.request(), credentials.token());
+
+                async move {
+                    match fut.await {
+                        Ok(claims) => {
+                            req.extensions_mut().insert(ValidatedToken {
+                                raw_token: credentials.token().to_owned(),
+                                claims,
+                            });
+                            Ok(req)
+                        }
+                        Err(err) => {
+                            let err = err.into_response_error(req.request());
+                            Err((err, req))
+                        }
+                    }
+                }
+                .boxed_local()
+            },
+        )
+    }
+}
+
 impl<S, B, T, F, O> Transform<S, ServiceRequest> for HttpAuthentication<T, F>
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
@@ -136,6 +396,9 @@ where
         future::ok(AuthenticationMiddleware {
             service: Rc::new(service),
             process_fn: self.process_fn.clone(),
+            emit_scheme_header: self.emit_scheme_header,
+            missing_credentials_message: self.missing_credentials_message.clone(),
+            optional: self.optional,
             _extractor: PhantomData,
         })
     }
@@ -148,6 +411,9 @@ where
 {
     service: Rc<S>,
     process_fn: Arc<F>,
+    emit_scheme_header: bool,
+    missing_credentials_message: Option<(HeaderValue, Arc<str>)>,
+    optional: bool,
     _extractor: PhantomData<T>,
 }
 
@@ -170,26 +436,101 @@ where
         let process_fn = Arc::clone(&self.process_fn);
         let service = Rc::clone(&self.service);
 
+        let scheme = if self.emit_scheme_header {
+            auth_scheme(&req)
+        } else {
+            None
+        };
+
+        let has_credentials = req.headers().contains_key(AUTHORIZATION);
+
+        if self.optional && !has_credentials {
+            return Box::pin(async move {
+                let res = service.call(req).await?;
+                Ok(res.map_into_left_body())
+            });
+        }
+
+        let missing_credentials_message = self
+            .missing_credentials_message
+            .clone()
+            .filter(|_| !has_credentials);
+
         Box::pin(async move {
             let (req, credentials) = match Extract::<T>::new(req).await {
                 Ok(req) => req,
                 Err((err, req)) => {
-                    return Ok(req.error_response(err).map_into_right_body());
+                    let res = match missing_credentials_message {
+                        Some((content_type, msg)) => {
+                            with_message(err.error_response(), content_type, &msg)
+                        }
+                        None => err.error_response(),
+                    };
+
+                    return Ok(req.into_response(res).map_into_right_body());
                 }
             };
 
-            let req = match process_fn(req, credentials).await {
+            let req = match instrument_validation(process_fn(req, credentials)).await {
                 Ok(req) => req,
                 Err((err, req)) => {
                     return Ok(req.error_response(err).map_into_right_body());
                 }
             };
 
-            service.call(req).await.map(|res| res.map_into_left_body())
+            let mut res = service.call(req).await?;
+
+            if let Some(scheme) = scheme {
+                res.headers_mut().insert(X_AUTH_SCHEME, scheme);
+            }
+
+            Ok(res.map_into_left_body())
         })
     }
 }
 
+/// Records the latency and outcome of a validation in an `auth.validate` span.
+#[cfg(feature = "tracing")]
+async fn instrument_validation<O>(fut: O) -> O::Output
+where
+    O: Future<Output = Result<ServiceRequest, (Error, ServiceRequest)>>,
+{
+    use tracing::{field, Instrument as _};
+
+    let span = tracing::info_span!(
+        "auth.validate",
+        auth.latency_us = field::Empty,
+        auth.result = field::Empty,
+    );
+
+    let start = std::time::Instant::now();
+    let res = fut.instrument(span.clone()).await;
+
+    span.record("auth.latency_us", start.elapsed().as_micros() as u64);
+    span.record("auth.result", if res.is_ok() { "ok" } else { "rejected" });
+
+    res
+}
+
+#[cfg(not(feature = "tracing"))]
+fn instrument_validation<O>(fut: O) -> O {
+    fut
+}
+
+/// Replaces the body of `res` with `msg`.
+fn with_message(res: HttpResponse, content_type: HeaderValue, msg: &str) -> HttpResponse {
+    let mut res = res.set_body(msg.to_owned()).map_into_boxed_body();
+    res.headers_mut().insert(CONTENT_TYPE, content_type);
+    res
+}
+
+/// Returns the scheme of the `Authorization` header of `req`, if any.
+fn auth_scheme(req: &ServiceRequest) -> Option<HeaderValue> {
+    let header = req.headers().get(AUTHORIZATION)?.to_str().ok()?;
+    let scheme = header.split_whitespace().next()?;
+    HeaderValue::from_str(scheme).ok()
+}
+
 struct Extract<T> {
     req: Option<ServiceRequest>,
     fut: Option<LocalBoxFuture<'static, Result<T, Error>>>,
@@ -244,14 +585,17 @@ mod tests {
     use actix_service::into_service;
     use actix_web::{
         dev::Service,
-        error::{self, ErrorForbidden},
+        error::{self, ErrorForbidden, ErrorUnauthorized},
         http::StatusCode,
         test::TestRequest,
-        web, App, HttpResponse,
+        web, App, HttpMessage as _, HttpRequest, HttpResponse,
     };
 
     use super::*;
-    use crate::extractors::{basic::BasicAuth, bearer::BearerAuth};
+    use crate::{
+        extractors::{basic::BasicAuth, bearer::BearerAuth},
+        validators::ValidationError,
+    };
 
     /// This is a test for https://github.com/actix/actix-extras/issues/10
     #[actix_web::test]
@@ -262,6 +606,9 @@ mod tests {
                 Err::<ServiceResponse, _>(error::ErrorBadRequest("error"))
             })),
             process_fn: Arc::new(|req, _: BearerAuth| async { Ok(req) }),
+            emit_scheme_header: false,
+            missing_credentials_message: None,
+            optional: false,
             _extractor: PhantomData,
         };
 
@@ -285,6 +632,9 @@ mod tests {
                 Err::<ServiceResponse, _>(error::ErrorBadRequest("error"))
             })),
             process_fn: Arc::new(|req, _: BearerAuth| async { Ok(req) }),
+            emit_scheme_header: false,
+            missing_credentials_message: None,
+            optional: false,
             _extractor: PhantomData,
         };
 
@@ -323,6 +673,9 @@ mod tests {
                 assert!(auth.is_none());
                 async { Ok(req) }
             }),
+            emit_scheme_header: false,
+            missing_credentials_message: None,
+            optional: false,
             _extractor: PhantomData,
         };
 
@@ -349,6 +702,9 @@ mod tests {
                     async { Ok(req) }
                 },
             ),
+            emit_scheme_header: false,
+            missing_credentials_message: None,
+            optional: false,
             _extractor: PhantomData,
         };
 
@@ -388,6 +744,60 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
     }
 
+    #[actix_web::test]
+    async fn test_optional() {
+        async fn validator(
+            req: ServiceRequest,
+            credentials: BearerAuth,
+        ) -> Result<ServiceRequest, (actix_web::Error, ServiceRequest)> {
+            if credentials.token() == "valid" {
+                Ok(req)
+            } else {
+                Err((ErrorUnauthorized("Invalid token"), req))
+            }
+        }
+
+        async fn index(credentials: Option<BearerAuth>) -> String {
+            match credentials {
+                Some(credentials) => format!("Hello, {}!", credentials.token()),
+                None => "Hello, stranger!".to_owned(),
+            }
+        }
+
+        let srv = actix_web::test::init_service(
+            App::new()
+                .wrap(HttpAuthentication::optional(validator))
+                .route("/", web::get().to(index)),
+        )
+        .await;
+
+        let req = actix_web::test::TestRequest::with_uri("/").to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = actix_web::test::read_body(resp).await;
+        assert_eq!(body, "Hello, stranger!");
+
+        let req = actix_web::test::TestRequest::with_uri("/")
+            .append_header(("Authorization", "Bearer valid"))
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = actix_web::test::read_body(resp).await;
+        assert_eq!(body, "Hello, valid!");
+
+        let req = actix_web::test::TestRequest::with_uri("/")
+            .append_header(("Authorization", "Bearer invalid"))
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = actix_web::test::TestRequest::with_uri("/")
+            .append_header(("Authorization", "Basic DontCare"))
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
     #[actix_web::test]
     async fn test_middleware_works_with_scope() {
         async fn validator(
@@ -414,4 +824,177 @@ mod tests {
         let resp = srv.call(req).await.unwrap();
         assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
     }
+
+    #[actix_web::test]
+    async fn test_sync_validator() {
+        fn validator(
+            req: ServiceRequest,
+            credentials: BasicAuth,
+        ) -> Result<ServiceRequest, (actix_web::Error, ServiceRequest)> {
+            if credentials.user_id() == "admin" {
+                Ok(req)
+            } else {
+                Err((ErrorForbidden("You are not welcome!"), req))
+            }
+        }
+
+        let srv = actix_web::test::init_service(
+            App::new()
+                .wrap(HttpAuthentication::sync(validator))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = actix_web::test::TestRequest::with_uri("/")
+            .insert_header(("Authorization", "Basic YWRtaW46cGFzcw=="))
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = actix_web::test::TestRequest::with_uri("/")
+            .insert_header(("Authorization", "Basic dXNlcjpwYXNz"))
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn test_bearer_validator() {
+        struct StaticToken;
+
+        impl TokenValidator for StaticToken {
+            type Claims = String;
+
+            fn validate(
+                &self,
+                _req: &HttpRequest,
+                token: &str,
+            ) -> LocalBoxFuture<'static, Result<String, ValidationError>> {
+                let res = if token == "valid" {
+                    Ok("alice".to_owned())
+                } else {
+                    Err(ValidationError::invalid("Unknown token"))
+                };
+
+                Box::pin(future::ready(res))
+            }
+        }
+
+        let srv = actix_web::test::init_service(
+            App::new()
+                .wrap(HttpAuthentication::bearer_validator(StaticToken))
+                .route(
+                    "/",
+                    web::get().to(|req: HttpRequest| async move {
+                        let extensions = req.extensions();
+                        let token = extensions.get::<ValidatedToken<String>>().unwrap();
+                        assert_eq!(token.raw_token, "valid");
+                        token.claims.clone()
+                    }),
+                ),
+        )
+        .await;
+
+        let req = actix_web::test::TestRequest::with_uri("/")
+            .append_header(("Authorization", "Bearer valid"))
+            .to_request();
+        let body = actix_web::test::call_and_read_body(&srv, req).await;
+        assert_eq!(body, "alice");
+
+        let req = actix_web::test::TestRequest::with_uri("/")
+            .append_header(("Authorization", "Bearer invalid"))
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            resp.headers().get("WWW-Authenticate").unwrap(),
+            "Bearer error=\"invalid_token\" error_description=\"Unknown token\""
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_emit_scheme_header() {
+        fn validator(
+            req: ServiceRequest,
+            credentials: BasicAuth,
+        ) -> Result<ServiceRequest, (actix_web::Error, ServiceRequest)> {
+            if credentials.user_id() == "admin" {
+                Ok(req)
+            } else {
+                Err((ErrorForbidden("You are not welcome!"), req))
+            }
+        }
+
+        let srv = actix_web::test::init_service(
+            App::new()
+                .wrap(HttpAuthentication::sync(validator).emit_scheme_header(true))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = actix_web::test::TestRequest::with_uri("/")
+            .insert_header(("Authorization", "Basic YWRtaW46cGFzcw=="))
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(X_AUTH_SCHEME).unwrap(), "Basic");
+
+        let req = actix_web::test::TestRequest::with_uri("/")
+            .insert_header(("Authorization", "Basic dXNlcjpwYXNz"))
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        assert!(!resp.headers().contains_key(X_AUTH_SCHEME));
+
+        let srv = actix_web::test::init_service(
+            App::new()
+                .wrap(HttpAuthentication::sync(validator))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = actix_web::test::TestRequest::with_uri("/")
+            .insert_header(("Authorization", "Basic YWRtaW46cGFzcw=="))
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert!(!resp.headers().contains_key(X_AUTH_SCHEME));
+    }
+
+    #[actix_web::test]
+    async fn test_missing_credentials_message() {
+        async fn validator(
+            req: ServiceRequest,
+            _credentials: BearerAuth,
+        ) -> Result<ServiceRequest, (actix_web::Error, ServiceRequest)> {
+            Err((ErrorForbidden("You are not welcome!"), req))
+        }
+
+        let middleware = HttpAuthentication::bearer(validator)
+            .missing_credentials_message(ContentType::json(), r#"{"error":"missing_credentials"}"#);
+        let srv = actix_web::test::init_service(
+            App::new()
+                .wrap(middleware)
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = actix_web::test::TestRequest::with_uri("/").to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert!(resp.headers().contains_key("WWW-Authenticate"));
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        let body = actix_web::test::read_body(resp).await;
+        assert_eq!(body, r#"{"error":"missing_credentials"}"#);
+
+        let req = actix_web::test::TestRequest::with_uri("/")
+            .insert_header(("Authorization", "Bearer foo"))
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let body = actix_web::test::read_body(resp).await;
+        assert_ne!(body, r#"{"error":"missing_credentials"}"#);
+    }
 }
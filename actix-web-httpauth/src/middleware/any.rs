@@ -0,0 +1,182 @@
+use std::{future::Future, rc::Rc};
+
+use actix_web::{
+    dev::ServiceRequest,
+    error::InternalError,
+    http::header::{HeaderValue, WWW_AUTHENTICATE},
+    Error, FromRequest, HttpResponse,
+};
+use futures_util::future::{FutureExt as _, LocalBoxFuture};
+
+use super::{Extract, HttpAuthentication};
+use crate::extractors::{basic::BasicAuth, bearer::BearerAuth};
+
+/// Type-erased validation callback, extracting its own credentials from the request.
+///
+/// Used to combine validators for several authentication schemes with
+/// [`HttpAuthentication::any`]. Constructed using [`boxed_validator`], [`boxed_basic`] or
+/// [`boxed_bearer`].
+pub type BoxedValidator = Box<
+    dyn Fn(
+        ServiceRequest,
+    ) -> LocalBoxFuture<'static, Result<ServiceRequest, (Error, ServiceRequest)>>,
+>;
+
+/// Boxes a validation callback for credentials extracted by `T`.
+///
+/// If the credentials cannot be extracted, e.g. because the request uses another scheme, the
+/// extractor's error is returned without calling `process_fn`.
+pub fn boxed_validator<T, F, O>(process_fn: F) -> BoxedValidator
+where
+    T: FromRequest + 'static,
+    F: Fn(ServiceRequest, T) -> O + 'static,
+    O: Future<Output = Result<ServiceRequest, (Error, ServiceRequest)>> + 'static,
+{
+    let process_fn = Rc::new(process_fn);
+
+    Box::new(move |req| {
+        let process_fn = Rc::clone(&process_fn);
+
+        async move {
+            let (req, credentials) = Extract::<T>::new(req).await?;
+            process_fn(req, credentials).await
+        }
+        .boxed_local()
+    })
+}
+
+/// Boxes a validation callback for the HTTP "Basic" authentication scheme.
+pub fn boxed_basic<F, O>(process_fn: F) -> BoxedValidator
+where
+    F: Fn(ServiceRequest, BasicAuth) -> O + 'static,
+    O: Future<Output = Result<ServiceRequest, (Error, ServiceRequest)>> + 'static,
+{
+    boxed_validator(process_fn)
+}
+
+/// Boxes a validation callback for the HTTP "Bearer" authentication scheme.
+pub fn boxed_bearer<F, O>(process_fn: F) -> BoxedValidator
+where
+    F: Fn(ServiceRequest, BearerAuth) -> O + 'static,
+    O: Future<Output = Result<ServiceRequest, (Error, ServiceRequest)>> + 'static,
+{
+    boxed_validator(process_fn)
+}
+
+impl HttpAuthentication<(), BoxedValidator> {
+    /// Construct `HttpAuthentication` middleware accepting credentials for any of several
+    /// authentication schemes, e.g. both "Basic" and "Bearer" for backward compatibility.
+    ///
+    /// The validators are tried in order on the same request, and the first to succeed lets the
+    /// request through. If all of them fail, a `401 Unauthorized` response is returned with a
+    /// `WWW-Authenticate` header combining their challenges, as allowed by [RFC 7235 §4.1].
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::{dev::ServiceRequest, Error};
+    /// use actix_web_httpauth::{
+    ///     extractors::{basic::BasicAuth, bearer::BearerAuth},
+    ///     middleware::{boxed_basic, boxed_bearer, HttpAuthentication},
+    /// };
+    ///
+    /// async fn basic_validator(
+    ///     req: ServiceRequest,
+    ///     _credentials: BasicAuth,
+    /// ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    ///     Ok(req)
+    /// }
+    ///
+    /// async fn bearer_validator(
+    ///     req: ServiceRequest,
+    ///     _credentials: BearerAuth,
+    /// ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    ///     Ok(req)
+    /// }
+    ///
+    /// let middleware = HttpAuthentication::any(vec![
+    ///     boxed_bearer(bearer_validator),
+    ///     boxed_basic(basic_validator),
+    /// ]);
+    /// ```
+    ///
+    /// [RFC 7235 §4.1]: https://tools.ietf.org/html/rfc7235#section-4.1
+    #[allow(clippy::type_complexity)]
+    pub fn any(
+        validators: Vec<BoxedValidator>,
+    ) -> HttpAuthentication<
+        (),
+        impl Fn(
+            ServiceRequest,
+            (),
+        ) -> LocalBoxFuture<'static, Result<ServiceRequest, (Error, ServiceRequest)>>,
+    > {
+        let validators: Rc<[BoxedValidator]> = validators.into();
+
+        HttpAuthentication::with_fn(move |req: ServiceRequest, _: ()| {
+            let validators = Rc::clone(&validators);
+
+            async move {
+                let mut req = req;
+                let mut challenges = Vec::new();
+
+                for validator in validators.iter() {
+                    match validator(req).await {
+                        Ok(req) => return Ok(req),
+                        Err((err, prev)) => {
+                            let res = err.error_response();
+                            challenges.extend(res.headers().get_all(WWW_AUTHENTICATE).cloned());
+                            req = prev;
+                        }
+                    }
+                }
+
+                Err((unauthorized(&challenges), req))
+            }
+            .boxed_local()
+        })
+    }
+}
+
+/// Returns a `401 Unauthorized` error with the comma-separated `challenges`.
+fn unauthorized(challenges: &[HeaderValue]) -> Error {
+    let mut res = HttpResponse::Unauthorized();
+
+    if !challenges.is_empty() {
+        let value = challenges
+            .iter()
+            .map(HeaderValue::as_bytes)
+            .collect::<Vec<_>>()
+            .join(&b", "[..]);
+
+        res.insert_header((
+            WWW_AUTHENTICATE,
+            HeaderValue::from_bytes(&value).expect("challenges are valid header values"),
+        ));
+    }
+
+    InternalError::from_response("No valid credentials", res.finish()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unauthorized() {
+        let res = unauthorized(&[
+            HeaderValue::from_static("Bearer"),
+            HeaderValue::from_static(r#"Basic realm="example""#),
+        ])
+        .error_response();
+
+        assert_eq!(res.status(), 401);
+        assert_eq!(
+            res.headers().get(WWW_AUTHENTICATE).unwrap(),
+            r#"Bearer, Basic realm="example""#
+        );
+
+        let res = unauthorized(&[]).error_response();
+        assert_eq!(res.status(), 401);
+        assert!(!res.headers().contains_key(WWW_AUTHENTICATE));
+    }
+}
@@ -0,0 +1,317 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use actix_web::HttpRequest;
+use futures_util::future::LocalBoxFuture;
+use sha2::{Digest as _, Sha256};
+use tokio::sync::Mutex;
+
+use crate::{
+    utils::unix_timestamp,
+    validators::{ExpiringClaims, TokenValidator, ValidationError},
+};
+
+type TokenHash = [u8; 32];
+
+type Cache<C> = Arc<Mutex<HashMap<TokenHash, (Instant, C)>>>;
+
+/// Validator that caches the claims of successfully validated tokens.
+///
+/// Wraps another validator, such as one querying a database or an introspection endpoint, and
+/// remembers the claims it produces for each token for the configured time to live, so that
+/// requests carrying the same token do not each pay the validation cost. Rejected tokens are not
+/// cached, so that a failed validation, e.g. due to a temporary outage, does not lock out a valid
+/// token.
+///
+/// Entries never outlive the token: they expire after the time to live or at the token's
+/// expiration time, whichever comes first. Note that a token revoked while it is cached is accepted
+/// until its entry expires, so the time to live should be kept short. Once `max_capacity` tokens
+/// are cached, expired entries are evicted, followed by the one expiring soonest if needed. Tokens
+/// are only kept as SHA-256 hashes. The cache is shared between clones of the validator.
+///
+/// Cached claims are returned for any request carrying the token, so the wrapped validator must
+/// only depend on the token. Validators that also check the request, such as
+/// [`TokenBindingValidator`](crate::validators::TokenBindingValidator) or policies comparing
+/// claims to the session, must wrap the caching validator instead of being wrapped by it.
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use actix_web::HttpRequest;
+/// # use futures_util::future::{self, LocalBoxFuture};
+/// # use actix_web_httpauth::validators::{TokenValidator, ValidationError};
+/// # struct DatabaseToken;
+/// # impl TokenValidator for DatabaseToken {
+/// #     type Claims = Claims;
+/// #     fn validate(&self, _: &HttpRequest, _: &str) -> LocalBoxFuture<'static, Result<Claims, ValidationError>> {
+/// #         Box::pin(future::ok(Claims))
+/// #     }
+/// # }
+/// # #[derive(Clone)]
+/// # struct Claims;
+/// # impl actix_web_httpauth::validators::ExpiringClaims for Claims {
+/// #     fn expires_at(&self) -> Option<u64> { None }
+/// # }
+/// use actix_web_httpauth::middleware::{CachingValidator, HttpAuthentication};
+///
+/// let validator = CachingValidator::new(DatabaseToken, Duration::from_secs(60), 10_000);
+///
+/// let middleware = HttpAuthentication::bearer_validator(validator);
+/// ```
+pub struct CachingValidator<V: TokenValidator> {
+    inner: Arc<V>,
+    ttl: Duration,
+    max_capacity: usize,
+    cache: Cache<V::Claims>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl<V: TokenValidator> CachingValidator<V> {
+    /// Wraps `inner`, caching the claims of up to `max_capacity` tokens for `ttl` each.
+    pub fn new(inner: V, ttl: Duration, max_capacity: usize) -> Self {
+        CachingValidator {
+            inner: Arc::new(inner),
+            ttl,
+            max_capacity,
+            cache: Arc::default(),
+            hits: Arc::default(),
+            misses: Arc::default(),
+        }
+    }
+
+    /// Returns the number of validations answered from the cache.
+    pub fn hit_count(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of validations passed on to the wrapped validator.
+    pub fn miss_count(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+impl<V> TokenValidator for CachingValidator<V>
+where
+    V: TokenValidator + 'static,
+    V::Claims: ExpiringClaims + Clone,
+{
+    type Claims = V::Claims;
+
+    fn validate(
+        &self,
+        req: &HttpRequest,
+        token: &str,
+    ) -> LocalBoxFuture<'static, Result<Self::Claims, ValidationError>> {
+        let inner = Arc::clone(&self.inner);
+        let ttl = self.ttl;
+        let max_capacity = self.max_capacity;
+        let cache = Arc::clone(&self.cache);
+        let hits = Arc::clone(&self.hits);
+        let misses = Arc::clone(&self.misses);
+        let req = req.clone();
+        let token = token.to_owned();
+
+        Box::pin(async move {
+            let hash: TokenHash = Sha256::digest(token.as_bytes()).into();
+
+            if let Some((expires_at, claims)) = cache.lock().await.get(&hash) {
+                if Instant::now() < *expires_at {
+                    hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(claims.clone());
+                }
+            }
+
+            misses.fetch_add(1, Ordering::Relaxed);
+
+            // the lock is not held while validating, so that other tokens are not held up
+            let claims = inner.validate(&req, &token).await?;
+
+            let ttl = match claims.expires_at() {
+                Some(exp) => ttl.min(Duration::from_secs(exp.saturating_sub(unix_timestamp()))),
+                None => ttl,
+            };
+
+            if max_capacity > 0 && !ttl.is_zero() {
+                let mut cache = cache.lock().await;
+
+                if cache.len() >= max_capacity && !cache.contains_key(&hash) {
+                    evict(&mut cache, max_capacity);
+                }
+
+                cache.insert(hash, (Instant::now() + ttl, claims.clone()));
+            }
+
+            Ok(claims)
+        })
+    }
+}
+
+/// Removes expired entries from `cache` and, if it is still full, the one expiring soonest.
+fn evict<C>(cache: &mut HashMap<TokenHash, (Instant, C)>, max_capacity: usize) {
+    let now = Instant::now();
+    cache.retain(|_, (expires_at, _)| now < *expires_at);
+
+    if cache.len() >= max_capacity {
+        let soonest = cache
+            .iter()
+            .min_by_key(|(_, (expires_at, _))| *expires_at)
+            .map(|(hash, _)| *hash);
+
+        if let Some(hash) = soonest {
+            cache.remove(&hash);
+        }
+    }
+}
+
+impl<V: TokenValidator> Clone for CachingValidator<V> {
+    fn clone(&self) -> Self {
+        CachingValidator {
+            inner: Arc::clone(&self.inner),
+            ttl: self.ttl,
+            max_capacity: self.max_capacity,
+            cache: Arc::clone(&self.cache),
+            hits: Arc::clone(&self.hits),
+            misses: Arc::clone(&self.misses),
+        }
+    }
+}
+
+impl<V: TokenValidator + fmt::Debug> fmt::Debug for CachingValidator<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachingValidator")
+            .field("inner", &self.inner)
+            .field("ttl", &self.ttl)
+            .field("max_capacity", &self.max_capacity)
+            .field("hits", &self.hit_count())
+            .field("misses", &self.miss_count())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use actix_web::test::TestRequest;
+    use futures_util::future;
+
+    use super::*;
+    use crate::validators::test_util::{Parsed, TestClaims};
+
+    /// Rejects tokens starting with `invalid` and otherwise parses them, counting its calls.
+    #[derive(Default)]
+    struct Counting {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl TokenValidator for Counting {
+        type Claims = TestClaims;
+
+        fn validate(
+            &self,
+            req: &HttpRequest,
+            token: &str,
+        ) -> LocalBoxFuture<'static, Result<TestClaims, ValidationError>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+
+            if token.starts_with("invalid") {
+                return Box::pin(future::err(ValidationError::invalid("Unknown token")));
+            }
+
+            Parsed.validate(req, token)
+        }
+    }
+
+    fn hash(token: &str) -> TokenHash {
+        Sha256::digest(token.as_bytes()).into()
+    }
+
+    #[actix_web::test]
+    async fn test_cache_hit() {
+        let req = TestRequest::default().to_http_request();
+        let inner = Counting::default();
+        let calls = Arc::clone(&inner.calls);
+        let validator = CachingValidator::new(inner, Duration::from_secs(60), 10);
+
+        let claims = validator.validate(&req, "sub=a").await.unwrap();
+        assert_eq!(claims.sub.as_deref(), Some("a"));
+        let claims = validator.validate(&req, "sub=a").await.unwrap();
+        assert_eq!(claims.sub.as_deref(), Some("a"));
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(validator.hit_count(), 1);
+        assert_eq!(validator.miss_count(), 1);
+
+        // only the hash of the token is kept
+        let cache = validator.cache.lock().await;
+        assert!(cache.contains_key(&hash("sub=a")));
+    }
+
+    #[actix_web::test]
+    async fn test_failures_not_cached() {
+        let req = TestRequest::default().to_http_request();
+        let inner = Counting::default();
+        let calls = Arc::clone(&inner.calls);
+        let validator = CachingValidator::new(inner, Duration::from_secs(60), 10);
+
+        assert!(validator.validate(&req, "invalid").await.is_err());
+        assert!(validator.validate(&req, "invalid").await.is_err());
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(validator.hit_count(), 0);
+        assert_eq!(validator.miss_count(), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_expiry() {
+        let req = TestRequest::default().to_http_request();
+        let inner = Counting::default();
+        let calls = Arc::clone(&inner.calls);
+        let validator = CachingValidator::new(inner, Duration::ZERO, 10);
+
+        assert!(validator.validate(&req, "sub=a").await.is_ok());
+        assert!(validator.validate(&req, "sub=a").await.is_ok());
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_token_expiry() {
+        let req = TestRequest::default().to_http_request();
+        let inner = Counting::default();
+        let calls = Arc::clone(&inner.calls);
+        let validator = CachingValidator::new(inner, Duration::from_secs(3600), 10);
+
+        // entries expire with the token, not after the time to live
+        assert!(validator.validate(&req, "exp=0").await.is_ok());
+        assert!(validator.validate(&req, "exp=0").await.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        assert!(validator.validate(&req, "exp=60").await.is_ok());
+        let cache = validator.cache.lock().await;
+        let (expires_at, _) = cache[&hash("exp=60")];
+        assert!(expires_at <= Instant::now() + Duration::from_secs(60));
+    }
+
+    #[actix_web::test]
+    async fn test_capacity() {
+        let req = TestRequest::default().to_http_request();
+        let validator = CachingValidator::new(Counting::default(), Duration::from_secs(60), 2);
+
+        for token in ["exp=10", "exp=20", "exp=30"] {
+            assert!(validator.validate(&req, token).await.is_ok());
+        }
+
+        let cache = validator.cache.lock().await;
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains_key(&hash("exp=10")));
+    }
+}
@@ -0,0 +1,231 @@
+use std::fmt::Write as _;
+
+use actix_web::{
+    dev::{Service, ServiceRequest, Transform},
+    http::header::{HeaderValue, AUTHORIZATION},
+    HttpMessage as _,
+};
+use futures_util::future::LocalBoxFuture;
+use sha2::{Digest as _, Sha256};
+
+/// Middleware that hides bearer tokens from an access logger.
+///
+/// Wraps a logging middleware, such as [`Logger`](actix_web::middleware::Logger), and replaces the
+/// token in the `Authorization` header of requests with a short fingerprint before they reach it,
+/// e.g. `Bearer 2c26b46b...***`. The fingerprint is made up of the first 8 hex digits of the
+/// SHA-256 hash of the token, which allows correlating requests made with the same token without
+/// revealing it. The original header is restored before the request is passed on, so the
+/// authentication middleware and handlers still see the full token.
+///
+/// This is only relevant if the logger is configured to print the `Authorization` header, e.g.
+/// using the `%{Authorization}i` format.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::Logger, App};
+/// use actix_web_httpauth::middleware::TokenLogRedactor;
+///
+/// let app = App::new().wrap(TokenLogRedactor::new(Logger::new(
+///     "%a \"%r\" %s %{Authorization}i",
+/// )));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TokenLogRedactor<L> {
+    logger: L,
+}
+
+impl<L> TokenLogRedactor<L> {
+    /// Wraps `logger`, which will only see redacted bearer tokens.
+    pub fn new(logger: L) -> Self {
+        TokenLogRedactor { logger }
+    }
+}
+
+impl<S, L> Transform<S, ServiceRequest> for TokenLogRedactor<L>
+where
+    L: Transform<RestoreAuthorization<S>, ServiceRequest>,
+    L::Future: 'static,
+{
+    type Response = L::Response;
+    type Error = L::Error;
+    type Transform = RedactAuthorization<L::Transform>;
+    type InitError = L::InitError;
+    type Future = LocalBoxFuture<'static, Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        let fut = self.logger.new_transform(RestoreAuthorization { service });
+
+        Box::pin(async move {
+            Ok(RedactAuthorization {
+                service: fut.await?,
+            })
+        })
+    }
+}
+
+/// Original `Authorization` header of a request, stashed while the logger runs.
+struct OriginalAuthorization(HeaderValue);
+
+#[doc(hidden)]
+pub struct RedactAuthorization<S> {
+    service: S,
+}
+
+impl<S> Service<ServiceRequest> for RedactAuthorization<S>
+where
+    S: Service<ServiceRequest>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let redacted = req.headers().get(AUTHORIZATION).and_then(redact);
+
+        if let Some(redacted) = redacted {
+            let original = req.headers_mut().remove(AUTHORIZATION).next();
+            req.headers_mut().insert(AUTHORIZATION, redacted);
+
+            if let Some(original) = original {
+                req.extensions_mut().insert(OriginalAuthorization(original));
+            }
+        }
+
+        self.service.call(req)
+    }
+}
+
+#[doc(hidden)]
+pub struct RestoreAuthorization<S> {
+    service: S,
+}
+
+impl<S> Service<ServiceRequest> for RestoreAuthorization<S>
+where
+    S: Service<ServiceRequest>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let original = req.extensions_mut().remove::<OriginalAuthorization>();
+
+        if let Some(OriginalAuthorization(original)) = original {
+            req.headers_mut().insert(AUTHORIZATION, original);
+        }
+
+        self.service.call(req)
+    }
+}
+
+/// Returns the redacted form of a bearer `Authorization` header, or `None` for other schemes.
+fn redact(header: &HeaderValue) -> Option<HeaderValue> {
+    let header = header.to_str().ok()?;
+    let (scheme, token) = header.split_once(' ')?;
+
+    if !scheme.eq_ignore_ascii_case("bearer") {
+        return None;
+    }
+
+    let digest = Sha256::digest(token.trim().as_bytes());
+
+    let mut redacted = format!("{} ", scheme);
+    for byte in &digest[..4] {
+        write!(redacted, "{:02x}", byte).unwrap();
+    }
+    redacted.push_str("...***");
+
+    HeaderValue::from_str(&redacted).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use actix_service::into_service;
+    use actix_web::{
+        dev::{Service as _, ServiceResponse},
+        test::TestRequest,
+        Error, HttpResponse,
+    };
+
+    use super::*;
+
+    /// Transform recording the `Authorization` header it sees, in place of a real logger.
+    struct Recorder(Rc<RefCell<Option<HeaderValue>>>);
+
+    impl<S> Transform<S, ServiceRequest> for Recorder
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse, Error = Error> + 'static,
+    {
+        type Response = ServiceResponse;
+        type Error = Error;
+        type Transform = RecorderService<S>;
+        type InitError = ();
+        type Future = futures_util::future::Ready<Result<Self::Transform, ()>>;
+
+        fn new_transform(&self, service: S) -> Self::Future {
+            futures_util::future::ok(RecorderService {
+                service,
+                logged: Rc::clone(&self.0),
+            })
+        }
+    }
+
+    struct RecorderService<S> {
+        service: S,
+        logged: Rc<RefCell<Option<HeaderValue>>>,
+    }
+
+    impl<S> Service<ServiceRequest> for RecorderService<S>
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse, Error = Error>,
+    {
+        type Response = ServiceResponse;
+        type Error = Error;
+        type Future = S::Future;
+
+        actix_web::dev::forward_ready!(service);
+
+        fn call(&self, req: ServiceRequest) -> Self::Future {
+            *self.logged.borrow_mut() = req.headers().get(AUTHORIZATION).cloned();
+            self.service.call(req)
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_token_log_redactor() {
+        let logged = Rc::new(RefCell::new(None));
+        let handled = Rc::new(RefCell::new(None));
+
+        let handled_in_service = Rc::clone(&handled);
+        let service = into_service(move |req: ServiceRequest| {
+            *handled_in_service.borrow_mut() = req.headers().get(AUTHORIZATION).cloned();
+            async move { Ok::<_, Error>(req.into_response(HttpResponse::Ok().finish())) }
+        });
+
+        let redactor = TokenLogRedactor::new(Recorder(Rc::clone(&logged)));
+        let srv = redactor.new_transform(service).await.unwrap();
+
+        let req = TestRequest::get()
+            .insert_header((AUTHORIZATION, "Bearer foo"))
+            .to_srv_request();
+        srv.call(req).await.unwrap();
+
+        assert_eq!(logged.borrow().as_ref().unwrap(), "Bearer 2c26b46b...***");
+        assert_eq!(handled.borrow().as_ref().unwrap(), "Bearer foo");
+
+        let req = TestRequest::get()
+            .insert_header((AUTHORIZATION, "Basic dXNlcjpwYXNz"))
+            .to_srv_request();
+        srv.call(req).await.unwrap();
+
+        assert_eq!(logged.borrow().as_ref().unwrap(), "Basic dXNlcjpwYXNz");
+        assert_eq!(handled.borrow().as_ref().unwrap(), "Basic dXNlcjpwYXNz");
+    }
+}
@@ -0,0 +1,309 @@
+use std::{convert::TryFrom as _, fmt, future::Future, rc::Rc, sync::Arc};
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
+    Error, FromRequest as _, HttpMessage as _,
+};
+use futures_util::future::{self, LocalBoxFuture};
+
+use crate::{
+    extractors::bearer::BearerAuth,
+    validators::{TokenValidator, ValidatedToken, ValidationError},
+};
+
+/// Name of the response header carrying a refreshed access token.
+pub const X_ACCESS_TOKEN: HeaderName = HeaderName::from_static("x-access-token");
+
+/// Cookie read by default.
+const DEFAULT_COOKIE: &str = "refresh_token";
+
+/// Middleware for bearer tokens that transparently refreshes expired access tokens.
+///
+/// Validates the bearer token of each request using the [`TokenValidator`] `V`. If the token has
+/// [expired](ValidationError::Expired), the refresh token is read from a cookie (`refresh_token` by
+/// default) and passed to the refresh callback `R`, which exchanges it for a new access token,
+/// e.g. at the token endpoint of an authorization server. The new access token is validated in
+/// turn and, once it is accepted, the request is forwarded and the new token is sent back to the
+/// client in the `X-Access-Token` response header, so that it can be used for later requests.
+///
+/// As with [`HttpAuthentication::bearer_validator`](super::HttpAuthentication::bearer_validator),
+/// the accepted token is inserted into the request extensions as a [`ValidatedToken`], holding
+/// either the original or the refreshed token along with its claims. Requests without a
+/// refresh cookie are rejected with the error of the expired token, and requests whose refresh
+/// callback fails with the error returned by the callback, e.g. [`ValidationError::Internal`] if
+/// the authorization server could not be reached. Tokens rejected for any other reason are never
+/// refreshed.
+///
+/// # Examples
+/// ```
+/// # use actix_web::{App, HttpRequest};
+/// # use futures_util::future::{self, LocalBoxFuture};
+/// # use actix_web_httpauth::validators::{TokenValidator, ValidationError};
+/// # struct StaticToken;
+/// # impl TokenValidator for StaticToken {
+/// #     type Claims = ();
+/// #     fn validate(&self, _: &HttpRequest, _: &str) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+/// #         Box::pin(future::ok(()))
+/// #     }
+/// # }
+/// use actix_web_httpauth::middleware::AutoRefreshMiddleware;
+///
+/// async fn refresh(refresh_token: String) -> Result<String, ValidationError> {
+///     // exchange the refresh token for a new access token
+///     # let _ = refresh_token;
+///     Ok("new-access-token".to_owned())
+/// }
+///
+/// let app = App::new().wrap(AutoRefreshMiddleware::new(StaticToken, refresh).cookie_name("rt"));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "refresh")))]
+pub struct AutoRefreshMiddleware<V, R> {
+    validator: Rc<V>,
+    refresh: Rc<R>,
+    cookie_name: Arc<str>,
+}
+
+impl<V, R, O> AutoRefreshMiddleware<V, R>
+where
+    V: TokenValidator,
+    R: Fn(String) -> O,
+    O: Future<Output = Result<String, ValidationError>>,
+{
+    /// Construct `AutoRefreshMiddleware` validating tokens with `validator` and refreshing expired
+    /// ones with the callback `refresh`.
+    pub fn new(validator: V, refresh: R) -> Self {
+        AutoRefreshMiddleware {
+            validator: Rc::new(validator),
+            refresh: Rc::new(refresh),
+            cookie_name: Arc::from(DEFAULT_COOKIE),
+        }
+    }
+}
+
+impl<V, R> AutoRefreshMiddleware<V, R> {
+    /// Sets the name of the cookie holding the refresh token.
+    ///
+    /// Defaults to `refresh_token`.
+    pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+        self.cookie_name = Arc::from(name.into());
+        self
+    }
+}
+
+impl<V, R> Clone for AutoRefreshMiddleware<V, R> {
+    fn clone(&self) -> Self {
+        AutoRefreshMiddleware {
+            validator: Rc::clone(&self.validator),
+            refresh: Rc::clone(&self.refresh),
+            cookie_name: Arc::clone(&self.cookie_name),
+        }
+    }
+}
+
+impl<V: fmt::Debug, R> fmt::Debug for AutoRefreshMiddleware<V, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutoRefreshMiddleware")
+            .field("validator", &self.validator)
+            .field("cookie_name", &self.cookie_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, B, V, R, O> Transform<S, ServiceRequest> for AutoRefreshMiddleware<V, R>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    V: TokenValidator + 'static,
+    R: Fn(String) -> O + 'static,
+    O: Future<Output = Result<String, ValidationError>> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = AutoRefreshMiddlewareService<S, V, R>;
+    type InitError = ();
+    type Future = future::Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        future::ok(AutoRefreshMiddlewareService {
+            service: Rc::new(service),
+            validator: Rc::clone(&self.validator),
+            refresh: Rc::clone(&self.refresh),
+            cookie_name: Arc::clone(&self.cookie_name),
+        })
+    }
+}
+
+#[doc(hidden)]
+pub struct AutoRefreshMiddlewareService<S, V, R> {
+    service: Rc<S>,
+    validator: Rc<V>,
+    refresh: Rc<R>,
+    cookie_name: Arc<str>,
+}
+
+impl<S, B, V, R, O> Service<ServiceRequest> for AutoRefreshMiddlewareService<S, V, R>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    V: TokenValidator + 'static,
+    R: Fn(String) -> O + 'static,
+    O: Future<Output = Result<String, ValidationError>> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let validator = Rc::clone(&self.validator);
+        let refresh = Rc::clone(&self.refresh);
+        let cookie_name = Arc::clone(&self.cookie_name);
+
+        Box::pin(async move {
+            let credentials = match BearerAuth::extract(req.request()).await {
+                Ok(credentials) => credentials,
+                Err(err) => return Ok(req.error_response(err).map_into_right_body()),
+            };
+
+            let token = credentials.token();
+            let res = match validator.validate(req.request(), token).await {
+                Ok(claims) => Ok((claims, None)),
+                Err(ValidationError::Expired) => match req.cookie(&cookie_name) {
+                    Some(cookie) => match refresh(cookie.value().to_owned()).await {
+                        Ok(token) => validator
+                            .validate(req.request(), &token)
+                            .await
+                            .map(|claims| (claims, Some(token))),
+                        Err(err) => Err(err),
+                    },
+                    None => Err(ValidationError::Expired),
+                },
+                Err(err) => Err(err),
+            };
+
+            let (claims, refreshed) = match res {
+                Ok(res) => res,
+                Err(err) => {
+                    let err = err.into_response_error(req.request());
+                    return Ok(req.error_response(err).map_into_right_body());
+                }
+            };
+
+            req.extensions_mut().insert(ValidatedToken {
+                raw_token: refreshed.clone().unwrap_or_else(|| token.to_owned()),
+                claims,
+            });
+
+            let mut res = service.call(req).await?;
+
+            if let Some(token) = refreshed {
+                match HeaderValue::try_from(token) {
+                    Ok(token) => {
+                        res.headers_mut().insert(X_ACCESS_TOKEN, token);
+                    }
+                    Err(_) => log::warn!("Refreshed access token is not a valid header value"),
+                }
+            }
+
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App, HttpRequest, HttpResponse,
+    };
+
+    use super::*;
+
+    /// Accepts `fresh` tokens, treating `stale` ones as expired.
+    #[derive(Debug)]
+    struct Freshness;
+
+    impl TokenValidator for Freshness {
+        type Claims = ();
+
+        fn validate(
+            &self,
+            _req: &HttpRequest,
+            token: &str,
+        ) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+            let res = match token {
+                "fresh" => Ok(()),
+                "stale" => Err(ValidationError::Expired),
+                _ => Err(ValidationError::invalid("Unknown token")),
+            };
+
+            Box::pin(future::ready(res))
+        }
+    }
+
+    async fn refresh(refresh_token: String) -> Result<String, ValidationError> {
+        if refresh_token == "valid" {
+            Ok("fresh".to_owned())
+        } else {
+            Err(ValidationError::invalid("Unknown refresh token"))
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_auto_refresh() {
+        let srv = test::init_service(
+            App::new()
+                .wrap(AutoRefreshMiddleware::new(Freshness, refresh))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let call = |token: &str, cookie: Option<&str>| {
+            let mut req = test::TestRequest::with_uri("/")
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)));
+            if let Some(cookie) = cookie {
+                req = req.insert_header((header::COOKIE, cookie));
+            }
+            test::call_service(&srv, req.to_request())
+        };
+
+        let res = call("fresh", None).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(!res.headers().contains_key(X_ACCESS_TOKEN));
+
+        let res = call("stale", None).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = call("stale", Some("refresh_token=valid")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(X_ACCESS_TOKEN).unwrap(), "fresh");
+
+        // the callback's error is returned
+        let res = call("stale", Some("refresh_token=revoked")).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let challenge = res.headers().get(header::WWW_AUTHENTICATE).unwrap();
+        assert!(challenge
+            .to_str()
+            .unwrap()
+            .contains("Unknown refresh token"));
+
+        // only expired tokens are refreshed
+        let res = call("forged", Some("refresh_token=valid")).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn test_debug() {
+        let middleware = AutoRefreshMiddleware::new(Freshness, refresh).cookie_name("rt");
+        assert_eq!(
+            format!("{:?}", middleware),
+            r#"AutoRefreshMiddleware { validator: Freshness, cookie_name: "rt", .. }"#
+        );
+    }
+}
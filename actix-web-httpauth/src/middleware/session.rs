@@ -0,0 +1,219 @@
+use std::{future::Future, marker::PhantomData, rc::Rc, sync::Arc};
+
+use actix_session::SessionExt as _;
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    error::ErrorUnauthorized,
+    Error,
+};
+use futures_util::future::{self, LocalBoxFuture};
+use serde::de::DeserializeOwned;
+
+/// Session key read by default.
+const DEFAULT_KEY: &str = "user_id";
+
+/// Middleware for authenticating requests using a value stored in their session.
+///
+/// Reads the value stored under a session key (`user_id` by default) from the
+/// [`Session`](actix_session::Session) of the request, deserializes it into `V` and passes it to
+/// the validation callback `F`. Requests are rejected with `401 Unauthorized` if the key is
+/// missing, the value cannot be deserialized or the callback resolves to `false`.
+///
+/// The session is managed by [`SessionMiddleware`](actix_session::SessionMiddleware), which must
+/// be registered *after* this middleware, so that it runs before it.
+///
+/// # Examples
+/// ```
+/// use actix_session::{storage::CookieSessionStore, SessionMiddleware};
+/// use actix_web::{cookie::Key, App};
+/// use actix_web_httpauth::middleware::SessionAuth;
+///
+/// async fn validator(user_id: u64) -> bool {
+///     // look up the user, e.g. in a database
+///     user_id != 0
+/// }
+///
+/// let app = App::new()
+///     .wrap(SessionAuth::new(validator))
+///     .wrap(SessionMiddleware::new(
+///         CookieSessionStore::default(),
+///         Key::generate(),
+///     ));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+#[derive(Debug)]
+pub struct SessionAuth<V, F> {
+    validator: Arc<F>,
+    key: Arc<str>,
+    _value: PhantomData<V>,
+}
+
+impl<V, F, O> SessionAuth<V, F>
+where
+    V: DeserializeOwned,
+    F: Fn(V) -> O,
+    O: Future<Output = bool>,
+{
+    /// Construct `SessionAuth` middleware with the validation callback `F`.
+    pub fn new(validator: F) -> Self {
+        SessionAuth {
+            validator: Arc::new(validator),
+            key: Arc::from(DEFAULT_KEY),
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<V, F> SessionAuth<V, F> {
+    /// Sets the session key holding the value passed to the validation callback.
+    ///
+    /// Defaults to `user_id`.
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = Arc::from(key.into());
+        self
+    }
+}
+
+impl<V, F> Clone for SessionAuth<V, F> {
+    fn clone(&self) -> Self {
+        SessionAuth {
+            validator: Arc::clone(&self.validator),
+            key: Arc::clone(&self.key),
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<S, B, V, F, O> Transform<S, ServiceRequest> for SessionAuth<V, F>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    V: DeserializeOwned + 'static,
+    F: Fn(V) -> O + 'static,
+    O: Future<Output = bool> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = SessionAuthMiddleware<S, V, F>;
+    type InitError = ();
+    type Future = future::Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        future::ok(SessionAuthMiddleware {
+            service: Rc::new(service),
+            validator: Arc::clone(&self.validator),
+            key: Arc::clone(&self.key),
+            _value: PhantomData,
+        })
+    }
+}
+
+#[doc(hidden)]
+pub struct SessionAuthMiddleware<S, V, F> {
+    service: Rc<S>,
+    validator: Arc<F>,
+    key: Arc<str>,
+    _value: PhantomData<V>,
+}
+
+impl<S, B, V, F, O> Service<ServiceRequest> for SessionAuthMiddleware<S, V, F>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    V: DeserializeOwned + 'static,
+    F: Fn(V) -> O + 'static,
+    O: Future<Output = bool> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let value = req.get_session().get::<V>(&self.key).unwrap_or_else(|err| {
+            log::debug!("Failed to read session key `{}`: {}", self.key, err);
+            None
+        });
+
+        let fut = value.map(|value| (self.validator)(value));
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            let authorized = match fut {
+                Some(fut) => fut.await,
+                None => false,
+            };
+
+            if !authorized {
+                let err = ErrorUnauthorized("Session is not authenticated");
+                return Ok(req.error_response(err).map_into_right_body());
+            }
+
+            service.call(req).await.map(|res| res.map_into_left_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{dev::Service as _, http::StatusCode, test, web, App, HttpResponse};
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_session_auth() {
+        let srv = test::init_service(
+            App::new()
+                .wrap(SessionAuth::new(
+                    |user_id: u64| async move { user_id == 42 },
+                ))
+                .wrap_fn(|req, srv| {
+                    if let Some(user_id) = req.headers().get("x-user-id") {
+                        let user_id: u64 = user_id.to_str().unwrap().parse().unwrap();
+                        req.get_session().insert("user_id", user_id).unwrap();
+                    }
+                    srv.call(req)
+                })
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = test::TestRequest::with_uri("/")
+            .insert_header(("x-user-id", "42"))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = test::TestRequest::with_uri("/")
+            .insert_header(("x-user-id", "7"))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::with_uri("/").to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn test_session_auth_key() {
+        let srv = test::init_service(
+            App::new()
+                .wrap(SessionAuth::new(|_: String| async { true }).key("account"))
+                .wrap_fn(|req, srv| {
+                    req.get_session().insert("user_id", "alice").unwrap();
+                    srv.call(req)
+                })
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = test::TestRequest::with_uri("/").to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+}
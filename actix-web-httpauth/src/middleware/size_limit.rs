@@ -0,0 +1,143 @@
+use std::rc::Rc;
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header::AUTHORIZATION, StatusCode},
+    Error, HttpResponse,
+};
+use futures_util::future::{self, LocalBoxFuture};
+
+/// Default maximum size of an `Authorization` header value, in bytes.
+const DEFAULT_LIMIT: usize = 8 * 1024;
+
+/// Middleware rejecting requests with oversized `Authorization` headers.
+///
+/// Requests with an `Authorization` header value longer than the configured limit (8 KiB by
+/// default) are rejected with `431 Request Header Fields Too Large`, before any credentials are
+/// parsed or validated. This protects validators, e.g. those decoding large JWTs, from having to
+/// process arbitrarily large tokens.
+///
+/// Register this middleware *after* [`HttpAuthentication`](super::HttpAuthentication), so that it
+/// runs before it.
+///
+/// # Examples
+/// ```
+/// # use actix_web::{dev::ServiceRequest, App, Error};
+/// # use actix_web_httpauth::extractors::bearer::BearerAuth;
+/// use actix_web_httpauth::middleware::{AuthHeaderSizeLimit, HttpAuthentication};
+///
+/// # async fn validator(
+/// #     req: ServiceRequest,
+/// #     _credentials: BearerAuth,
+/// # ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+/// #     Ok(req)
+/// # }
+/// let app = App::new()
+///     .wrap(HttpAuthentication::bearer(validator))
+///     .wrap(AuthHeaderSizeLimit::new(4096));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AuthHeaderSizeLimit {
+    limit: usize,
+}
+
+impl AuthHeaderSizeLimit {
+    /// Constructs middleware accepting `Authorization` header values of up to `limit` bytes.
+    pub fn new(limit: usize) -> Self {
+        AuthHeaderSizeLimit { limit }
+    }
+}
+
+impl Default for AuthHeaderSizeLimit {
+    fn default() -> Self {
+        Self::new(DEFAULT_LIMIT)
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AuthHeaderSizeLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = AuthHeaderSizeLimitMiddleware<S>;
+    type InitError = ();
+    type Future = future::Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        future::ok(AuthHeaderSizeLimitMiddleware {
+            service: Rc::new(service),
+            limit: self.limit,
+        })
+    }
+}
+
+#[doc(hidden)]
+pub struct AuthHeaderSizeLimitMiddleware<S> {
+    service: Rc<S>,
+    limit: usize,
+}
+
+impl<S, B> Service<ServiceRequest> for AuthHeaderSizeLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let oversized = req
+            .headers()
+            .get_all(AUTHORIZATION)
+            .any(|header| header.len() > self.limit);
+
+        if oversized {
+            let res = HttpResponse::new(StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+            return Box::pin(future::ok(req.into_response(res).map_into_right_body()));
+        }
+
+        let service = Rc::clone(&self.service);
+        Box::pin(async move { service.call(req).await.map(|res| res.map_into_left_body()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, web, App};
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_auth_header_size_limit() {
+        let srv = test::init_service(
+            App::new()
+                .wrap(AuthHeaderSizeLimit::new(16))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = test::TestRequest::with_uri("/")
+            .insert_header((AUTHORIZATION, "Bearer 123456789"))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = test::TestRequest::with_uri("/")
+            .insert_header((AUTHORIZATION, "Bearer 1234567890"))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+
+        let req = test::TestRequest::with_uri("/").to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
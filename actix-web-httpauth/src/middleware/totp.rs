@@ -0,0 +1,174 @@
+use std::rc::Rc;
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    error::{ErrorForbidden, ErrorInternalServerError},
+    http::header::HeaderName,
+    Error,
+};
+use futures_util::future::{self, LocalBoxFuture};
+use totp_rs::TOTP;
+
+/// Name of the request header carrying the one-time password.
+pub const X_OTP: HeaderName = HeaderName::from_static("x-otp");
+
+/// Middleware requiring a time-based one-time password (TOTP) as a second authentication factor.
+///
+/// Requests must contain the current one-time password in the `X-OTP` header. It is checked
+/// against the [`TOTP`] registered as app data, which holds the shared secret and the algorithm
+/// parameters. Requests with a missing or invalid password are rejected with `403 Forbidden`.
+///
+/// To require a valid bearer token as the first factor, register this middleware *before*
+/// [`HttpAuthentication`](super::HttpAuthentication), so that it runs after it.
+///
+/// # Examples
+/// ```
+/// # use actix_web::{dev::ServiceRequest, App, Error};
+/// # use actix_web_httpauth::extractors::bearer::BearerAuth;
+/// use actix_web_httpauth::middleware::{HttpAuthentication, TotpSecondFactor};
+/// use totp_rs::{Algorithm, TOTP};
+///
+/// # async fn validator(
+/// #     req: ServiceRequest,
+/// #     _credentials: BearerAuth,
+/// # ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+/// #     Ok(req)
+/// # }
+/// let totp = TOTP::new(Algorithm::SHA1, 6, 1, 30, b"my-shared-secret-key".to_vec()).unwrap();
+///
+/// let app = App::new()
+///     .app_data(totp)
+///     .wrap(TotpSecondFactor)
+///     .wrap(HttpAuthentication::bearer(validator));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "totp")))]
+#[derive(Debug, Clone, Default)]
+pub struct TotpSecondFactor;
+
+impl<S, B> Transform<S, ServiceRequest> for TotpSecondFactor
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = TotpSecondFactorMiddleware<S>;
+    type InitError = ();
+    type Future = future::Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        future::ok(TotpSecondFactorMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+#[doc(hidden)]
+pub struct TotpSecondFactorMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for TotpSecondFactorMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if let Err(err) = check_otp(&req) {
+            return Box::pin(future::ok(req.error_response(err).map_into_right_body()));
+        }
+
+        let service = Rc::clone(&self.service);
+        Box::pin(async move { service.call(req).await.map(|res| res.map_into_left_body()) })
+    }
+}
+
+/// Checks the one-time password of `req` against the [`TOTP`] registered as app data.
+fn check_otp(req: &ServiceRequest) -> Result<(), Error> {
+    let totp = req.app_data::<TOTP>().ok_or_else(|| {
+        log::error!("TotpSecondFactor requires a `TOTP` to be registered as app data");
+        ErrorInternalServerError("One-time password could not be verified")
+    })?;
+
+    let otp = req
+        .headers()
+        .get(X_OTP)
+        .and_then(|otp| otp.to_str().ok())
+        .ok_or_else(|| ErrorForbidden("One-time password is missing"))?;
+
+    match totp.check_current(otp.trim()) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(ErrorForbidden("One-time password is invalid")),
+        Err(err) => {
+            log::error!("Failed to read system time for TOTP verification: {}", err);
+            Err(ErrorInternalServerError(
+                "One-time password could not be verified",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+    use totp_rs::Algorithm;
+
+    use super::*;
+
+    fn totp() -> TOTP {
+        TOTP::new(Algorithm::SHA1, 6, 1, 30, b"12345678901234567890".to_vec()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_totp_second_factor() {
+        let srv = test::init_service(
+            App::new()
+                .app_data(totp())
+                .wrap(TotpSecondFactor)
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let otp = totp().generate_current().unwrap();
+        let req = test::TestRequest::with_uri("/")
+            .insert_header((X_OTP, otp))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = test::TestRequest::with_uri("/")
+            .insert_header((X_OTP, "abcdef"))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let req = test::TestRequest::with_uri("/").to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn test_missing_totp() {
+        let srv = test::init_service(
+            App::new()
+                .wrap(TotpSecondFactor)
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = test::TestRequest::with_uri("/")
+            .insert_header((X_OTP, "123456"))
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,129 @@
+//! Proof Key for Code Exchange ([RFC 7636]).
+//!
+//! Utilities for token endpoints of OAuth 2.0 authorization servers that exchange authorization
+//! codes for access tokens.
+//!
+//! [RFC 7636]: https://tools.ietf.org/html/rfc7636
+
+use std::str::FromStr;
+
+use sha2::{Digest as _, Sha256};
+
+use crate::utils::constant_time_eq;
+
+/// Method used to derive a code challenge from a code verifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PkceMethod {
+    /// The challenge is the base64url-encoded SHA-256 hash of the verifier (`S256`).
+    S256,
+
+    /// The challenge is the verifier itself (`plain`).
+    Plain,
+}
+
+impl FromStr for PkceMethod {
+    type Err = ();
+
+    /// Parses the value of a `code_challenge_method` parameter.
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        match method {
+            "S256" => Ok(PkceMethod::S256),
+            "plain" => Ok(PkceMethod::Plain),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Verifier for PKCE code challenges.
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::pkce::{PkceMethod, PkceVerifier};
+///
+/// let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+/// let challenge = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+///
+/// assert!(PkceVerifier::verify(verifier, challenge, PkceMethod::S256));
+/// ```
+#[derive(Debug)]
+pub struct PkceVerifier;
+
+impl PkceVerifier {
+    /// Returns whether `code_verifier` matches the `code_challenge` recorded during the
+    /// authorization request, using `method`.
+    ///
+    /// Verifiers that are not between 43 and 128 characters long or contain characters other
+    /// than ASCII letters, digits, `-`, `.`, `_` and `~` never match.
+    pub fn verify(code_verifier: &str, code_challenge: &str, method: PkceMethod) -> bool {
+        if !is_valid_verifier(code_verifier) {
+            return false;
+        }
+
+        match method {
+            PkceMethod::S256 => {
+                let digest = Sha256::digest(code_verifier.as_bytes());
+                let expected = base64::encode_config(digest, base64::URL_SAFE_NO_PAD);
+                constant_time_eq(expected.as_bytes(), code_challenge.as_bytes())
+            }
+            PkceMethod::Plain => {
+                constant_time_eq(code_verifier.as_bytes(), code_challenge.as_bytes())
+            }
+        }
+    }
+}
+
+fn is_valid_verifier(verifier: &str) -> bool {
+    (43..=128).contains(&verifier.len())
+        && verifier
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // example from RFC 7636, appendix B
+    const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    const CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+    #[test]
+    fn test_s256() {
+        assert!(PkceVerifier::verify(VERIFIER, CHALLENGE, PkceMethod::S256));
+        assert!(!PkceVerifier::verify(VERIFIER, VERIFIER, PkceMethod::S256));
+        assert!(!PkceVerifier::verify(
+            CHALLENGE,
+            CHALLENGE,
+            PkceMethod::S256
+        ));
+    }
+
+    #[test]
+    fn test_plain() {
+        assert!(PkceVerifier::verify(VERIFIER, VERIFIER, PkceMethod::Plain));
+        assert!(!PkceVerifier::verify(
+            VERIFIER,
+            CHALLENGE,
+            PkceMethod::Plain
+        ));
+    }
+
+    #[test]
+    fn test_invalid_verifier() {
+        assert!(!PkceVerifier::verify("short", "short", PkceMethod::Plain));
+
+        let verifier = format!("{}+", &VERIFIER[1..]);
+        assert!(!PkceVerifier::verify(
+            &verifier,
+            &verifier,
+            PkceMethod::Plain
+        ));
+    }
+
+    #[test]
+    fn test_method_from_str() {
+        assert_eq!("S256".parse::<PkceMethod>(), Ok(PkceMethod::S256));
+        assert_eq!("plain".parse::<PkceMethod>(), Ok(PkceMethod::Plain));
+        assert_eq!("s256".parse::<PkceMethod>(), Err(()));
+    }
+}
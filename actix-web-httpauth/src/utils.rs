@@ -1,55 +1,87 @@
-use std::str;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use actix_web::web::BytesMut;
+use actix_web::web::{BufMut as _, BytesMut};
 
-enum State {
-    YieldStr,
-    YieldQuote,
+/// Escapes the quotes and backslashes in `val`, see [RFC 7230 §3.2.6].
+///
+/// [RFC 7230 §3.2.6]: https://tools.ietf.org/html/rfc7230#section-3.2.6
+pub fn put_quoted(buf: &mut BytesMut, val: &str) {
+    buf.reserve(val.len());
+
+    for byte in val.bytes() {
+        if byte == b'"' || byte == b'\\' {
+            buf.put_u8(b'\\');
+        }
+        buf.put_u8(byte);
+    }
 }
 
-struct Quoted<'a> {
-    inner: ::std::iter::Peekable<str::Split<'a, char>>,
-    state: State,
+/// Returns whether `c` separates the auth-params of a challenge, see [RFC 7235 §2.1].
+///
+/// [RFC 7235 §2.1]: https://tools.ietf.org/html/rfc7235#section-2.1
+pub fn is_separator(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n' | ',')
 }
 
-impl<'a> Quoted<'a> {
-    pub fn new(s: &'a str) -> Quoted<'_> {
-        Quoted {
-            inner: s.split('"').peekable(),
-            state: State::YieldStr,
-        }
+/// Parses a single `key=value` auth-param of a challenge, where the value is either a token or a
+/// quoted string, see [RFC 7235 §2.1].
+///
+/// Returns the key, the unescaped value and the rest of the challenge. If the param is malformed,
+/// returns the key as the error, or all of `s` if there is no valid key.
+///
+/// [RFC 7235 §2.1]: https://tools.ietf.org/html/rfc7235#section-2.1
+pub fn parse_auth_param(s: &str) -> Result<(&str, String, &str), &str> {
+    let (key, rest) = s.split_once('=').ok_or(s)?;
+    let key = key.trim_end();
+
+    if key.is_empty() || key.contains(is_separator) {
+        return Err(s);
     }
-}
 
-impl<'a> Iterator for Quoted<'a> {
-    type Item = &'a str;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.state {
-            State::YieldStr => match self.inner.next() {
-                Some(val) => {
-                    self.state = State::YieldQuote;
-                    Some(val)
-                }
-                None => None,
-            },
+    let rest = rest.trim_start_matches([' ', '\t']);
 
-            State::YieldQuote => match self.inner.peek() {
-                Some(_) => {
-                    self.state = State::YieldStr;
-                    Some("\\\"")
-                }
-                None => None,
+    let quoted = match rest.strip_prefix('"') {
+        Some(quoted) => quoted,
+        None => {
+            let len = rest.find(is_separator).unwrap_or(rest.len());
+            let (value, rest) = rest.split_at(len);
+
+            if value.is_empty() || value.contains('"') {
+                return Err(key);
+            }
+
+            return Ok((key, value.to_owned(), rest));
+        }
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((key, value, &quoted[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, escaped)) => value.push(escaped),
+                None => break,
             },
+            c => value.push(c),
         }
     }
+
+    // unterminated quoted string
+    Err(key)
 }
 
-/// Escapes the quotes in `val`.
-pub fn put_quoted(buf: &mut BytesMut, val: &str) {
-    for part in Quoted::new(val) {
-        buf.extend_from_slice(part.as_bytes());
-    }
+/// Compares `a` and `b` in time independent of their contents.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
 }
 
 #[cfg(test)]
@@ -58,7 +90,7 @@ mod tests {
 
     use actix_web::web::BytesMut;
 
-    use super::put_quoted;
+    use super::{parse_auth_param, put_quoted};
 
     #[test]
     fn test_quote_str() {
@@ -109,4 +141,31 @@ mod tests {
 
         assert_eq!(result, "quote\\\"\\\"string");
     }
+
+    #[test]
+    fn test_backslash() {
+        let input = "back\\slash\\\"";
+        let mut output = BytesMut::new();
+        put_quoted(&mut output, input);
+        let result = str::from_utf8(&output).unwrap();
+
+        assert_eq!(result, "back\\\\slash\\\\\\\"");
+    }
+
+    #[test]
+    fn test_parse_auth_param() {
+        assert_eq!(
+            parse_auth_param(r#"realm="a \"quoted\" realm", charset=UTF-8"#),
+            Ok(("realm", r#"a "quoted" realm"#.to_owned(), ", charset=UTF-8"))
+        );
+        assert_eq!(
+            parse_auth_param("charset = UTF-8"),
+            Ok(("charset", "UTF-8".to_owned(), ""))
+        );
+
+        assert_eq!(parse_auth_param(r#"realm="unterminated"#), Err("realm"));
+        assert_eq!(parse_auth_param("realm="), Err("realm"));
+        assert_eq!(parse_auth_param("realm"), Err("realm"));
+        assert_eq!(parse_auth_param("a b=c"), Err("a b=c"));
+    }
 }
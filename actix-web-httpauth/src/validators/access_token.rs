@@ -0,0 +1,399 @@
+use std::{fmt, sync::Arc};
+
+use actix_web::HttpRequest;
+use futures_util::future::{self, LocalBoxFuture};
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{
+    jwt::deserialize_audience, AudienceClaims, ExpiringClaims, IssuedAtClaims, IssuedAtTolerance,
+    IssuerClaims, SubjectClaims, TokenValidator, ValidationError,
+};
+
+/// Claims of a JWT access token, defined in [RFC 9068 §2.2].
+///
+/// All claims that the RFC requires are guaranteed to be present. Other claims, such as `scope`
+/// or `auth_time`, are kept in [`extra`](Self::extra).
+///
+/// [RFC 9068 §2.2]: https://tools.ietf.org/html/rfc9068#section-2.2
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rfc9068Claims {
+    /// Issuer of the token.
+    pub iss: String,
+
+    /// Resource servers that the token is intended for.
+    ///
+    /// Tokens may contain either a single audience string or an array of them.
+    #[serde(deserialize_with = "deserialize_audience")]
+    pub aud: Vec<String>,
+
+    /// Expiration time, in seconds since the Unix epoch.
+    pub exp: u64,
+
+    /// Time at which the token was issued, in seconds since the Unix epoch.
+    pub iat: u64,
+
+    /// Subject of the token, i.e. the resource owner or, without one, the client.
+    pub sub: String,
+
+    /// Client that requested the token.
+    pub client_id: String,
+
+    /// Unique identifier of the token.
+    pub jti: String,
+
+    /// Claims not required by RFC 9068.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl AudienceClaims for Rfc9068Claims {
+    fn audience(&self) -> &[String] {
+        &self.aud
+    }
+}
+
+impl ExpiringClaims for Rfc9068Claims {
+    fn expires_at(&self) -> Option<u64> {
+        Some(self.exp)
+    }
+}
+
+impl IssuedAtClaims for Rfc9068Claims {
+    fn issued_at(&self) -> Option<u64> {
+        Some(self.iat)
+    }
+}
+
+impl IssuerClaims for Rfc9068Claims {
+    fn issuer(&self) -> Option<&str> {
+        Some(&self.iss)
+    }
+}
+
+impl SubjectClaims for Rfc9068Claims {
+    fn subject(&self) -> Option<&str> {
+        Some(&self.sub)
+    }
+}
+
+/// Validator for JWT access tokens, as profiled by [RFC 9068].
+///
+/// Besides verifying the signature, the validator checks that the `typ` header is `at+jwt`
+/// (compared case-insensitively, with an optional `application/` prefix), that the token was
+/// issued by one of the allowed issuers for one of the allowed audiences, that it has neither
+/// expired nor been issued in the future, allowing for 60 seconds of clock skew, and that all
+/// claims required by the RFC are present. This prevents other JWTs signed
+/// by the same authorization server, such as OpenID Connect ID tokens, from being accepted as
+/// access tokens. All tokens are rejected if no issuer or no audience is allowed.
+///
+/// Tokens are expected to be signed with RS256 by default.
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::{middleware::HttpAuthentication, validators::JwtAccessTokenValidator};
+/// use jsonwebtoken::{Algorithm, DecodingKey};
+///
+/// let validator = JwtAccessTokenValidator::builder(DecodingKey::from_secret(b"secret"))
+///     .algorithm(Algorithm::HS256)
+///     .issuer("https://auth.example.com")
+///     .audience("https://api.example.com")
+///     .build();
+///
+/// let middleware = HttpAuthentication::bearer_validator(validator);
+/// ```
+///
+/// [RFC 9068]: https://tools.ietf.org/html/rfc9068
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+#[derive(Clone)]
+pub struct JwtAccessTokenValidator {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    key: DecodingKey,
+    validation: Validation,
+    iat: IssuedAtTolerance,
+}
+
+impl JwtAccessTokenValidator {
+    /// Constructs a validator accepting RS256-signed tokens issued by `issuer` for `audience`.
+    pub fn new(key: DecodingKey, issuer: impl Into<String>, audience: impl Into<String>) -> Self {
+        Self::builder(key).issuer(issuer).audience(audience).build()
+    }
+
+    /// Creates a builder for configuring the validator.
+    pub fn builder(key: DecodingKey) -> JwtAccessTokenValidatorBuilder {
+        JwtAccessTokenValidatorBuilder {
+            key,
+            algorithm: Algorithm::RS256,
+            issuers: Vec::new(),
+            audiences: Vec::new(),
+        }
+    }
+
+    fn decode(&self, token: &str) -> Result<Rfc9068Claims, ValidationError> {
+        let header = jsonwebtoken::decode_header(token)
+            .map_err(|_| ValidationError::invalid("The access token is malformed"))?;
+
+        if !header.typ.as_deref().is_some_and(is_access_token_type) {
+            return Err(ValidationError::invalid(
+                "The access token has an unexpected type",
+            ));
+        }
+
+        if !self.inner.validation.algorithms.contains(&header.alg) {
+            return Err(ValidationError::invalid(
+                "The access token is signed with an unsupported algorithm",
+            ));
+        }
+
+        let claims =
+            jsonwebtoken::decode::<Rfc9068Claims>(token, &self.inner.key, &self.inner.validation)
+                .map(|data| data.claims)
+                .map_err(|err| match err.kind() {
+                    ErrorKind::ExpiredSignature => ValidationError::Expired,
+                    _ => ValidationError::invalid("The access token is invalid"),
+                })?;
+
+        self.inner.iat.check_issued_at(claims.issued_at())?;
+
+        Ok(claims)
+    }
+}
+
+impl fmt::Debug for JwtAccessTokenValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtAccessTokenValidator")
+            .field("algorithms", &self.inner.validation.algorithms)
+            .field("issuers", &self.inner.validation.iss)
+            .field("audiences", &self.inner.validation.aud)
+            .finish()
+    }
+}
+
+impl TokenValidator for JwtAccessTokenValidator {
+    type Claims = Rfc9068Claims;
+
+    fn validate(
+        &self,
+        _req: &HttpRequest,
+        token: &str,
+    ) -> LocalBoxFuture<'static, Result<Self::Claims, ValidationError>> {
+        Box::pin(future::ready(self.decode(token)))
+    }
+}
+
+/// Builder for [`JwtAccessTokenValidator`].
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+pub struct JwtAccessTokenValidatorBuilder {
+    key: DecodingKey,
+    algorithm: Algorithm,
+    issuers: Vec<String>,
+    audiences: Vec<String>,
+}
+
+impl JwtAccessTokenValidatorBuilder {
+    /// Sets the algorithm that tokens must be signed with.
+    ///
+    /// Defaults to RS256, which RFC 9068 requires authorization servers to support.
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Adds an allowed issuer, usually the URL of an authorization server.
+    ///
+    /// Can be called multiple times; issuers are compared exactly.
+    pub fn issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuers.push(issuer.into());
+        self
+    }
+
+    /// Adds an allowed audience, usually the URL of this resource server.
+    ///
+    /// Can be called multiple times; tokens are accepted if any of their audiences is allowed.
+    pub fn audience(mut self, audience: impl Into<String>) -> Self {
+        self.audiences.push(audience.into());
+        self
+    }
+
+    /// Constructs the validator.
+    pub fn build(self) -> JwtAccessTokenValidator {
+        let mut validation = Validation::new(self.algorithm);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.set_issuer(&self.issuers);
+        validation.set_audience(&self.audiences);
+
+        // tolerate the same clock skew as for the expiration time
+        let iat = IssuedAtTolerance::seconds(validation.leeway);
+
+        JwtAccessTokenValidator {
+            inner: Arc::new(Inner {
+                key: self.key,
+                validation,
+                iat,
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for JwtAccessTokenValidatorBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtAccessTokenValidatorBuilder")
+            .field("algorithm", &self.algorithm)
+            .field("issuers", &self.issuers)
+            .field("audiences", &self.audiences)
+            .finish()
+    }
+}
+
+/// Returns true if `typ` is the media type of JWT access tokens, see [RFC 9068 §4].
+///
+/// [RFC 9068 §4]: https://tools.ietf.org/html/rfc9068#section-4
+fn is_access_token_type(typ: &str) -> bool {
+    let typ = typ.strip_prefix("application/").unwrap_or(typ);
+    typ.eq_ignore_ascii_case("at+jwt")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use actix_web::test::TestRequest;
+    use jsonwebtoken::{EncodingKey, Header};
+
+    use super::*;
+
+    const SECRET: &[u8] = b"secret";
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn valid_claims() -> Value {
+        serde_json::json!({
+            "iss": "https://auth.example.com",
+            "aud": "https://api.example.com",
+            "exp": now() + 600,
+            "iat": now(),
+            "sub": "alice",
+            "client_id": "app",
+            "jti": "a1b2c3",
+            "scope": "read",
+        })
+    }
+
+    fn token(typ: &str, claims: &Value) -> String {
+        let mut header = Header::new(Algorithm::HS256);
+        header.typ = Some(typ.to_owned());
+
+        jsonwebtoken::encode(&header, claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn validator() -> JwtAccessTokenValidator {
+        JwtAccessTokenValidator::builder(DecodingKey::from_secret(SECRET))
+            .algorithm(Algorithm::HS256)
+            .issuer("https://auth.example.com")
+            .audience("https://api.example.com")
+            .build()
+    }
+
+    #[actix_web::test]
+    async fn test_access_token() {
+        let req = TestRequest::default().to_http_request();
+
+        for typ in ["at+jwt", "at+JWT", "application/at+jwt"] {
+            let claims = validator()
+                .validate(&req, &token(typ, &valid_claims()))
+                .await
+                .unwrap();
+            assert_eq!(claims.sub, "alice");
+            assert_eq!(claims.client_id, "app");
+            assert_eq!(claims.aud, vec!["https://api.example.com"]);
+            assert_eq!(claims.extra["scope"], "read");
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_unexpected_type() {
+        let req = TestRequest::default().to_http_request();
+
+        let err = validator()
+            .validate(&req, &token("JWT", &valid_claims()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+    }
+
+    #[actix_web::test]
+    async fn test_missing_claims() {
+        let req = TestRequest::default().to_http_request();
+
+        for claim in ["iss", "aud", "exp", "iat", "sub", "client_id", "jti"] {
+            let mut claims = valid_claims();
+            claims.as_object_mut().unwrap().remove(claim);
+
+            let err = validator()
+                .validate(&req, &token("at+jwt", &claims))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, ValidationError::InvalidToken(_)), "{}", claim);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_claims() {
+        let req = TestRequest::default().to_http_request();
+
+        let mut claims = valid_claims();
+        claims["iss"] = "https://evil.example.com".into();
+        let err = validator()
+            .validate(&req, &token("at+jwt", &claims))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+
+        let mut claims = valid_claims();
+        claims["aud"] = "https://other.example.com".into();
+        let err = validator()
+            .validate(&req, &token("at+jwt", &claims))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+
+        let mut claims = valid_claims();
+        claims["exp"] = (now() - 3600).into();
+        let err = validator()
+            .validate(&req, &token("at+jwt", &claims))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ValidationError::Expired));
+    }
+
+    #[actix_web::test]
+    async fn test_issued_in_future() {
+        let req = TestRequest::default().to_http_request();
+
+        // within the leeway
+        let mut claims = valid_claims();
+        claims["iat"] = (now() + 30).into();
+        assert!(validator()
+            .validate(&req, &token("at+jwt", &claims))
+            .await
+            .is_ok());
+
+        let mut claims = valid_claims();
+        claims["iat"] = (now() + 3600).into();
+        let err = validator()
+            .validate(&req, &token("at+jwt", &claims))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+    }
+}
@@ -0,0 +1,91 @@
+use actix_web::HttpRequest;
+use futures_util::future::{self, LocalBoxFuture};
+
+use super::{ClaimsCheck, ClaimsPolicy, ValidationError};
+
+/// Claims that identify the intended recipients of a token.
+pub trait AudienceClaims {
+    /// Returns the audiences the token is intended for.
+    fn audience(&self) -> &[String];
+}
+
+/// Validator that rejects tokens intended for other recipients.
+pub type AudienceValidator<V> = ClaimsCheck<V, ExpectedAudience>;
+
+/// Policy accepting tokens intended for at least one of the expected audiences.
+///
+/// This prevents tokens issued for other services from being accepted (a "confused deputy").
+/// Tokens without an audience, and all tokens if no audience is expected, are rejected.
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::validators::ExpectedAudience;
+///
+/// let policy = ExpectedAudience::new(["https://api.example.com"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedAudience {
+    audiences: Vec<String>,
+}
+
+impl ExpectedAudience {
+    /// Accepts tokens intended for any of `audiences`.
+    pub fn new<I, T>(audiences: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        ExpectedAudience {
+            audiences: audiences.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl<C: AudienceClaims> ClaimsPolicy<C> for ExpectedAudience {
+    fn check(
+        &self,
+        _req: &HttpRequest,
+        _token: &str,
+        claims: &C,
+    ) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+        let expected = claims
+            .audience()
+            .iter()
+            .any(|aud| self.audiences.contains(aud));
+
+        if !expected {
+            return Box::pin(future::err(ValidationError::invalid(
+                "The access token is not intended for this audience",
+            )));
+        }
+
+        Box::pin(future::ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+    use crate::validators::{test_util::Parsed, TokenValidator as _};
+
+    #[actix_web::test]
+    async fn test_audience() {
+        let req = TestRequest::default().to_http_request();
+        let validator = AudienceValidator::new(Parsed, ExpectedAudience::new(["api", "web"]));
+
+        assert!(validator.validate(&req, "aud=api").await.is_ok());
+        assert!(validator.validate(&req, "aud=other,web").await.is_ok());
+
+        let res = validator.validate(&req, "aud=other").await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+
+        let res = validator.validate(&req, "").await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+
+        let validator = AudienceValidator::new(Parsed, ExpectedAudience::new(Vec::<String>::new()));
+        let res = validator.validate(&req, "aud=api").await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+    }
+}
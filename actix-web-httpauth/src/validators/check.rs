@@ -0,0 +1,112 @@
+use std::{fmt, sync::Arc};
+
+use actix_web::HttpRequest;
+use futures_util::future::LocalBoxFuture;
+
+use super::{TokenValidator, ValidationError};
+
+/// Policy applied to the claims of tokens accepted by another validator, see [`ClaimsCheck`].
+pub trait ClaimsPolicy<C> {
+    /// Checks the `claims` of `token`, which was sent along with `req`.
+    fn check(
+        &self,
+        req: &HttpRequest,
+        token: &str,
+        claims: &C,
+    ) -> LocalBoxFuture<'static, Result<(), ValidationError>>;
+}
+
+/// Validator that applies a [`ClaimsPolicy`] to the claims of tokens accepted by another
+/// validator.
+///
+/// Tokens rejected by the inner validator are rejected without consulting the policy. Policies
+/// provided by this crate, such as [`ClockSkewTolerance`](super::ClockSkewTolerance), can be
+/// combined by nesting checks.
+///
+/// # Examples
+/// ```
+/// # use actix_web::HttpRequest;
+/// # use futures_util::future::{self, LocalBoxFuture};
+/// # use actix_web_httpauth::validators::{ExpiringClaims, TokenValidator, ValidationError};
+/// # struct Claims;
+/// # impl ExpiringClaims for Claims {
+/// #     fn expires_at(&self) -> Option<u64> { None }
+/// # }
+/// # struct StaticToken;
+/// # impl TokenValidator for StaticToken {
+/// #     type Claims = Claims;
+/// #     fn validate(&self, _: &HttpRequest, _: &str) -> LocalBoxFuture<'static, Result<Claims, ValidationError>> {
+/// #         Box::pin(future::ok(Claims))
+/// #     }
+/// # }
+/// use actix_web_httpauth::{
+///     middleware::HttpAuthentication,
+///     validators::{ClaimsCheck, ClockSkewTolerance},
+/// };
+///
+/// let validator = ClaimsCheck::new(StaticToken, ClockSkewTolerance::seconds(60));
+///
+/// let middleware = HttpAuthentication::bearer_validator(validator);
+/// ```
+pub struct ClaimsCheck<V, P> {
+    inner: V,
+    policy: Arc<P>,
+}
+
+impl<V, P> ClaimsCheck<V, P> {
+    /// Wraps `inner`, applying `policy` to the claims of the tokens it accepts.
+    pub fn new(inner: V, policy: P) -> Self {
+        ClaimsCheck {
+            inner,
+            policy: Arc::new(policy),
+        }
+    }
+
+    /// Returns the policy applied by this validator.
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+}
+
+impl<V, P> TokenValidator for ClaimsCheck<V, P>
+where
+    V: TokenValidator,
+    P: ClaimsPolicy<V::Claims> + 'static,
+{
+    type Claims = V::Claims;
+
+    fn validate(
+        &self,
+        req: &HttpRequest,
+        token: &str,
+    ) -> LocalBoxFuture<'static, Result<Self::Claims, ValidationError>> {
+        let fut = self.inner.validate(req, token);
+        let policy = Arc::clone(&self.policy);
+        let req = req.clone();
+        let token = token.to_owned();
+
+        Box::pin(async move {
+            let claims = fut.await?;
+            policy.check(&req, &token, &claims).await?;
+            Ok(claims)
+        })
+    }
+}
+
+impl<V: Clone, P> Clone for ClaimsCheck<V, P> {
+    fn clone(&self) -> Self {
+        ClaimsCheck {
+            inner: self.inner.clone(),
+            policy: Arc::clone(&self.policy),
+        }
+    }
+}
+
+impl<V: fmt::Debug, P: fmt::Debug> fmt::Debug for ClaimsCheck<V, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClaimsCheck")
+            .field("inner", &self.inner)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
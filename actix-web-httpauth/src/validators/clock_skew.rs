@@ -0,0 +1,89 @@
+use actix_web::HttpRequest;
+use futures_util::future::{self, LocalBoxFuture};
+
+use super::{ClaimsCheck, ClaimsPolicy, ValidationError};
+use crate::utils::unix_timestamp;
+
+/// Default minimum remaining lifetime of accepted tokens.
+const DEFAULT_TOLERANCE: u64 = 30;
+
+/// Claims that carry an expiration time.
+pub trait ExpiringClaims {
+    /// Returns the expiration time, in seconds since the Unix epoch, if the claims have one.
+    fn expires_at(&self) -> Option<u64>;
+}
+
+/// Validator that rejects tokens which are about to expire.
+pub type ClockSkewTolerantValidator<V> = ClaimsCheck<V, ClockSkewTolerance>;
+
+/// Policy rejecting tokens that expire within a tolerance, 30 seconds by default.
+///
+/// Such tokens are rejected as [expired](ValidationError::Expired), so that they do not run out
+/// while the request is being processed, e.g. on slow networks or when clocks are skewed. Tokens
+/// without an expiration time are accepted.
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::validators::ClockSkewTolerance;
+///
+/// let policy = ClockSkewTolerance::seconds(60);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSkewTolerance {
+    tolerance: u64,
+}
+
+impl ClockSkewTolerance {
+    /// Rejects tokens expiring within `tolerance` seconds.
+    pub fn seconds(tolerance: u64) -> Self {
+        ClockSkewTolerance { tolerance }
+    }
+}
+
+impl Default for ClockSkewTolerance {
+    fn default() -> Self {
+        Self::seconds(DEFAULT_TOLERANCE)
+    }
+}
+
+impl<C: ExpiringClaims> ClaimsPolicy<C> for ClockSkewTolerance {
+    fn check(
+        &self,
+        _req: &HttpRequest,
+        _token: &str,
+        claims: &C,
+    ) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+        let expired = claims
+            .expires_at()
+            .is_some_and(|exp| exp <= unix_timestamp().saturating_add(self.tolerance));
+
+        if expired {
+            return Box::pin(future::err(ValidationError::Expired));
+        }
+
+        Box::pin(future::ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+    use crate::validators::{test_util::Parsed, TokenValidator as _};
+
+    #[actix_web::test]
+    async fn test_clock_skew_tolerance() {
+        let req = TestRequest::default().to_http_request();
+        let validator = ClockSkewTolerantValidator::new(Parsed, ClockSkewTolerance::seconds(60));
+
+        assert!(validator.validate(&req, "exp=3600").await.is_ok());
+        assert!(validator.validate(&req, "").await.is_ok());
+
+        let err = validator.validate(&req, "exp=10").await.unwrap_err();
+        assert!(matches!(err, ValidationError::Expired));
+
+        let err = validator.validate(&req, "exp=0").await.unwrap_err();
+        assert!(matches!(err, ValidationError::Expired));
+    }
+}
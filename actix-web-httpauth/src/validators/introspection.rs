@@ -0,0 +1,378 @@
+use std::{
+    fmt,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use actix_web::{
+    error::PayloadError,
+    http::{header::ACCEPT, StatusCode},
+    HttpRequest,
+};
+use awc::error::SendRequestError;
+use futures_util::future::LocalBoxFuture;
+use lru::LruCache;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sha2::{Digest as _, Sha256};
+use url::Url;
+
+use super::{ExpiringClaims, IssuerClaims, SubjectClaims, TokenValidator, ValidationError};
+
+/// Default number of active tokens kept in the cache.
+const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+/// Maximum size of an introspection response.
+const MAX_RESPONSE_SIZE: usize = 64 * 1024;
+
+/// Claims returned by a token introspection endpoint, defined in [RFC 7662 §2.2].
+///
+/// Claims that are not defined by the RFC are kept in [`extra`](Self::extra).
+///
+/// [RFC 7662 §2.2]: https://tools.ietf.org/html/rfc7662#section-2.2
+#[cfg_attr(docsrs, doc(cfg(feature = "introspection")))]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct IntrospectionClaims {
+    /// Whether the token is currently active.
+    pub active: bool,
+
+    /// Space-separated list of scopes associated with the token.
+    #[serde(default)]
+    pub scope: Option<String>,
+
+    /// Client identifier of the client that requested the token.
+    #[serde(default)]
+    pub client_id: Option<String>,
+
+    /// Human-readable identifier of the resource owner who authorized the token.
+    #[serde(default)]
+    pub username: Option<String>,
+
+    /// Subject of the token.
+    #[serde(default)]
+    pub sub: Option<String>,
+
+    /// Issuer of the token.
+    #[serde(default)]
+    pub iss: Option<String>,
+
+    /// Expiration time, in seconds since the Unix epoch.
+    #[serde(default)]
+    pub exp: Option<u64>,
+
+    /// Claims not defined by RFC 7662.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl ExpiringClaims for IntrospectionClaims {
+    fn expires_at(&self) -> Option<u64> {
+        self.exp
+    }
+}
+
+impl IssuerClaims for IntrospectionClaims {
+    fn issuer(&self) -> Option<&str> {
+        self.iss.as_deref()
+    }
+}
+
+impl SubjectClaims for IntrospectionClaims {
+    fn subject(&self) -> Option<&str> {
+        self.sub.as_deref()
+    }
+}
+
+/// Errors that can occur while calling a token introspection endpoint.
+#[cfg_attr(docsrs, doc(cfg(feature = "introspection")))]
+#[derive(Debug)]
+pub enum IntrospectionError {
+    /// Request to the introspection endpoint failed.
+    Request(SendRequestError),
+
+    /// Introspection endpoint responded with a non-success status code.
+    Status(StatusCode),
+
+    /// Response body could not be read.
+    Payload(PayloadError),
+
+    /// Response body is not a valid introspection response.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for IntrospectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntrospectionError::Request(err) => write!(f, "Introspection request failed: {}", err),
+            IntrospectionError::Status(status) => {
+                write!(f, "Introspection endpoint responded with {}", status)
+            }
+            IntrospectionError::Payload(err) => {
+                write!(f, "Introspection response could not be read: {}", err)
+            }
+            IntrospectionError::Json(err) => {
+                write!(f, "Introspection response is malformed: {}", err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IntrospectionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IntrospectionError::Request(err) => Some(err),
+            IntrospectionError::Status(_) => None,
+            IntrospectionError::Payload(err) => Some(err),
+            IntrospectionError::Json(err) => Some(err),
+        }
+    }
+}
+
+type TokenHash = [u8; 32];
+
+thread_local! {
+    /// Client used for introspection requests; `awc::Client` cannot be shared between threads.
+    static CLIENT: awc::Client = awc::Client::default();
+}
+
+/// Validator for opaque tokens using an OAuth 2.0 token introspection endpoint ([RFC 7662]).
+///
+/// Tokens are POSTed to the introspection endpoint, authenticating with the client credentials
+/// using HTTP Basic authentication. Tokens reported as inactive are rejected; failures to reach
+/// the endpoint result in `500 Internal Server Error` responses.
+///
+/// Active tokens with an `exp` claim are kept in an in-memory LRU cache, keyed by their hash,
+/// until they expire. Tokens revoked before then may therefore be accepted until their expiry.
+///
+/// Requests are sent using one [`awc::Client`] per worker thread, shared by all introspection
+/// validators, so that connections to the endpoint are reused. HTTPS introspection endpoints
+/// require one of the TLS features of [`awc`] to be enabled.
+///
+/// # Examples
+/// ```no_run
+/// use actix_web::{App, HttpServer};
+/// use actix_web_httpauth::{middleware::HttpAuthentication, validators::IntrospectionValidator};
+///
+/// #[actix_web::main]
+/// async fn main() -> std::io::Result<()> {
+///     let validator = IntrospectionValidator::new(
+///         "https://auth.example.com/introspect".parse().unwrap(),
+///         "my-api".to_owned(),
+///         "my-api-secret".to_owned(),
+///     );
+///
+///     HttpServer::new(move || {
+///         App::new().wrap(HttpAuthentication::bearer_validator(validator.clone()))
+///     })
+///     .bind(("127.0.0.1", 8080))?
+///     .run()
+///     .await
+/// }
+/// ```
+///
+/// [RFC 7662]: https://tools.ietf.org/html/rfc7662
+#[cfg_attr(docsrs, doc(cfg(feature = "introspection")))]
+#[derive(Clone)]
+pub struct IntrospectionValidator {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    introspection_url: Url,
+    client_id: String,
+    client_secret: String,
+    cache: Mutex<LruCache<TokenHash, (IntrospectionClaims, Instant)>>,
+}
+
+impl IntrospectionValidator {
+    /// Constructs a validator calling `introspection_url` with the given client credentials.
+    pub fn new(introspection_url: Url, client_id: String, client_secret: String) -> Self {
+        let capacity = NonZeroUsize::new(DEFAULT_CACHE_CAPACITY).unwrap();
+
+        IntrospectionValidator {
+            inner: Arc::new(Inner {
+                introspection_url,
+                client_id,
+                client_secret,
+                cache: Mutex::new(LruCache::new(capacity)),
+            }),
+        }
+    }
+
+    /// Introspects `token`, returning its claims if it is active.
+    async fn lookup(&self, token: &str) -> Result<IntrospectionClaims, ValidationError> {
+        let hash: TokenHash = Sha256::digest(token.as_bytes()).into();
+
+        if let Some(claims) = self.cached(&hash) {
+            return Ok(claims);
+        }
+
+        let claims = self
+            .introspect(token)
+            .await
+            .map_err(ValidationError::internal)?;
+
+        if !claims.active {
+            return Err(ValidationError::invalid("The access token is not active"));
+        }
+
+        if let Some(ttl) = claims.exp.and_then(time_until) {
+            self.inner
+                .cache
+                .lock()
+                .expect("introspection cache lock poisoned")
+                .put(hash, (claims.clone(), Instant::now() + ttl));
+        }
+
+        Ok(claims)
+    }
+
+    async fn introspect(&self, token: &str) -> Result<IntrospectionClaims, IntrospectionError> {
+        let inner = &self.inner;
+
+        let mut res = CLIENT
+            .with(awc::Client::clone)
+            .post(inner.introspection_url.as_str())
+            .basic_auth(&inner.client_id, &inner.client_secret)
+            .insert_header((ACCEPT, "application/json"))
+            .send_form(&[("token", token), ("token_type_hint", "access_token")])
+            .await
+            .map_err(IntrospectionError::Request)?;
+
+        if !res.status().is_success() {
+            return Err(IntrospectionError::Status(res.status()));
+        }
+
+        let body = res
+            .body()
+            .limit(MAX_RESPONSE_SIZE)
+            .await
+            .map_err(IntrospectionError::Payload)?;
+
+        serde_json::from_slice(&body).map_err(IntrospectionError::Json)
+    }
+
+    fn cached(&self, hash: &TokenHash) -> Option<IntrospectionClaims> {
+        let mut cache = self
+            .inner
+            .cache
+            .lock()
+            .expect("introspection cache lock poisoned");
+
+        match cache.get(hash) {
+            Some((claims, expires_at)) if Instant::now() < *expires_at => Some(claims.clone()),
+            Some(_) => {
+                cache.pop(hash);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+/// Returns the time remaining until `exp`, in seconds since the Unix epoch, if it is in the future.
+fn time_until(exp: u64) -> Option<Duration> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    Duration::from_secs(exp)
+        .checked_sub(now)
+        .filter(|ttl| !ttl.is_zero())
+}
+
+impl TokenValidator for IntrospectionValidator {
+    type Claims = IntrospectionClaims;
+
+    fn validate(
+        &self,
+        _req: &HttpRequest,
+        token: &str,
+    ) -> LocalBoxFuture<'static, Result<Self::Claims, ValidationError>> {
+        let this = self.clone();
+        let token = token.to_owned();
+
+        Box::pin(async move { this.lookup(&token).await })
+    }
+}
+
+impl fmt::Debug for IntrospectionValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntrospectionValidator")
+            .field("introspection_url", &self.inner.introspection_url.as_str())
+            .field("client_id", &self.inner.client_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use actix_web::{test::TestRequest, web, App, HttpResponse};
+
+    use super::*;
+    use crate::extractors::basic::BasicAuth;
+
+    #[derive(Deserialize)]
+    struct Form {
+        token: String,
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    #[actix_web::test]
+    async fn test_introspection() {
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let srv = {
+            let calls = Arc::clone(&calls);
+
+            actix_test::start(move || {
+                let calls = Arc::clone(&calls);
+
+                App::new().route(
+                    "/introspect",
+                    web::post().to(move |auth: BasicAuth, form: web::Form<Form>| {
+                        calls.fetch_add(1, Ordering::SeqCst);
+
+                        let exp = now() + 600;
+                        let res = match (auth.user_id(), auth.password(), form.token.as_str()) {
+                            ("api", Some("secret"), "good") => HttpResponse::Ok().json(
+                                serde_json::json!({ "active": true, "sub": "alice", "exp": exp }),
+                            ),
+                            ("api", Some("secret"), _) => {
+                                HttpResponse::Ok().json(serde_json::json!({ "active": false }))
+                            }
+                            _ => HttpResponse::Unauthorized().finish(),
+                        };
+
+                        async move { res }
+                    }),
+                )
+            })
+        };
+
+        let req = TestRequest::default().to_http_request();
+        let url: Url = srv.url("/introspect").parse().unwrap();
+        let validator =
+            IntrospectionValidator::new(url.clone(), "api".to_owned(), "secret".to_owned());
+
+        let claims = validator.validate(&req, "good").await.unwrap();
+        assert_eq!(claims.sub.as_deref(), Some("alice"));
+
+        // served from the cache
+        validator.validate(&req, "good").await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let err = validator.validate(&req, "bad").await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+
+        let validator = IntrospectionValidator::new(url, "api".to_owned(), "wrong".to_owned());
+        let err = validator.validate(&req, "good").await.unwrap_err();
+        assert!(matches!(err, ValidationError::Internal(_)));
+    }
+}
@@ -0,0 +1,89 @@
+use actix_web::HttpRequest;
+use futures_util::future::{self, LocalBoxFuture};
+
+use super::{ClaimsCheck, ClaimsPolicy, ValidationError};
+use crate::utils::unix_timestamp;
+
+/// Claims that carry an issuance time.
+pub trait IssuedAtClaims {
+    /// Returns the issuance time, in seconds since the Unix epoch, if the claims have one.
+    fn issued_at(&self) -> Option<u64>;
+}
+
+/// Validator that rejects tokens issued in the future.
+pub type StrictIatValidator<V> = ClaimsCheck<V, IssuedAtTolerance>;
+
+/// Policy rejecting tokens issued later than a tolerance from now, 0 seconds by default.
+///
+/// Such tokens were either issued by a server with a skewed clock or forged. Tokens without an
+/// issuance time are accepted. Unlike [`ClockSkewTolerance`](super::ClockSkewTolerance), which
+/// rejects tokens that are about to expire, this only checks the start of the token's lifetime;
+/// both can be combined.
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::validators::IssuedAtTolerance;
+///
+/// let policy = IssuedAtTolerance::seconds(5);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IssuedAtTolerance {
+    tolerance: u64,
+}
+
+impl IssuedAtTolerance {
+    /// Accepts tokens issued at most `tolerance` seconds in the future.
+    pub fn seconds(tolerance: u64) -> Self {
+        IssuedAtTolerance { tolerance }
+    }
+
+    /// Returns an error if `iat` is later than the tolerance from now.
+    pub(crate) fn check_issued_at(&self, iat: Option<u64>) -> Result<(), ValidationError> {
+        match iat {
+            Some(iat) if iat > unix_timestamp().saturating_add(self.tolerance) => Err(
+                ValidationError::invalid("The access token was issued in the future"),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<C: IssuedAtClaims> ClaimsPolicy<C> for IssuedAtTolerance {
+    fn check(
+        &self,
+        _req: &HttpRequest,
+        _token: &str,
+        claims: &C,
+    ) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+        Box::pin(future::ready(self.check_issued_at(claims.issued_at())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+    use crate::validators::{test_util::Parsed, TokenValidator as _};
+
+    #[actix_web::test]
+    async fn test_strict_iat() {
+        let req = TestRequest::default().to_http_request();
+        let validator = StrictIatValidator::new(Parsed, IssuedAtTolerance::default());
+
+        assert!(validator.validate(&req, "iat=-60").await.is_ok());
+        assert!(validator.validate(&req, "").await.is_ok());
+
+        let err = validator.validate(&req, "iat=60").await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+    }
+
+    #[actix_web::test]
+    async fn test_strict_iat_tolerance() {
+        let req = TestRequest::default().to_http_request();
+        let validator = StrictIatValidator::new(Parsed, IssuedAtTolerance::seconds(60));
+
+        assert!(validator.validate(&req, "iat=30").await.is_ok());
+        assert!(validator.validate(&req, "iat=3600").await.is_err());
+    }
+}
@@ -0,0 +1,105 @@
+use actix_web::HttpRequest;
+use futures_util::future::{self, LocalBoxFuture};
+
+use super::{ClaimsCheck, ClaimsPolicy, ValidationError};
+
+/// Claims that identify the issuer of a token.
+pub trait IssuerClaims {
+    /// Returns the issuer of the token, if the claims have one.
+    fn issuer(&self) -> Option<&str>;
+}
+
+/// Validator that rejects tokens from unexpected issuers.
+pub type IssuerValidator<V> = ClaimsCheck<V, TrustedIssuers>;
+
+/// Policy accepting tokens issued by one of the trusted issuers, usually the URLs of
+/// authorization servers.
+///
+/// This prevents validly signed tokens from other environments, e.g. a test identity provider
+/// sharing keys with production, from being accepted. Issuers are compared exactly. Tokens without
+/// an issuer, and all tokens if no issuer is trusted, are rejected.
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::validators::TrustedIssuers;
+///
+/// let policy = TrustedIssuers::new(["https://auth.example.com"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustedIssuers {
+    issuers: Vec<String>,
+}
+
+impl TrustedIssuers {
+    /// Accepts tokens issued by any of `issuers`.
+    pub fn new<I, T>(issuers: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        TrustedIssuers {
+            issuers: issuers.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl<C: IssuerClaims> ClaimsPolicy<C> for TrustedIssuers {
+    fn check(
+        &self,
+        _req: &HttpRequest,
+        _token: &str,
+        claims: &C,
+    ) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+        let trusted = claims
+            .issuer()
+            .is_some_and(|iss| self.issuers.iter().any(|trusted| trusted == iss));
+
+        if !trusted {
+            return Box::pin(future::err(ValidationError::invalid(
+                "The access token was not issued by a trusted issuer",
+            )));
+        }
+
+        Box::pin(future::ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+    use crate::validators::{test_util::Parsed, TokenValidator as _};
+
+    #[actix_web::test]
+    async fn test_issuer() {
+        let req = TestRequest::default().to_http_request();
+        let validator = IssuerValidator::new(
+            Parsed,
+            TrustedIssuers::new(["https://auth.example.com", "https://login.example.com"]),
+        );
+
+        let res = validator
+            .validate(&req, "iss=https://auth.example.com")
+            .await;
+        assert!(res.is_ok());
+        let res = validator
+            .validate(&req, "iss=https://login.example.com")
+            .await;
+        assert!(res.is_ok());
+
+        let res = validator
+            .validate(&req, "iss=https://test.example.com")
+            .await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+
+        let res = validator.validate(&req, "").await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+
+        let validator = IssuerValidator::new(Parsed, TrustedIssuers::new(Vec::<String>::new()));
+        let res = validator
+            .validate(&req, "iss=https://auth.example.com")
+            .await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+    }
+}
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+
+use super::{AudienceClaims, ExpiringClaims, IssuedAtClaims, IssuerClaims, SubjectClaims};
+
+/// The registered claims of a JSON Web Token, defined in [RFC 7519 §4.1].
+///
+/// Claims that are not registered by the RFC are kept in [`extra`](Self::extra).
+///
+/// [RFC 7519 §4.1]: https://tools.ietf.org/html/rfc7519#section-4.1
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RegisteredClaims {
+    /// Issuer of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+
+    /// Subject of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+
+    /// Intended recipients of the token.
+    ///
+    /// Tokens may contain either a single audience string or an array of them.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_audience",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub aud: Vec<String>,
+
+    /// Expiration time, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+
+    /// Time before which the token must not be accepted, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>,
+
+    /// Time at which the token was issued, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<u64>,
+
+    /// Unique identifier of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+
+    /// Claims not registered by RFC 7519.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl AudienceClaims for RegisteredClaims {
+    fn audience(&self) -> &[String] {
+        &self.aud
+    }
+}
+
+impl ExpiringClaims for RegisteredClaims {
+    fn expires_at(&self) -> Option<u64> {
+        self.exp
+    }
+}
+
+impl IssuedAtClaims for RegisteredClaims {
+    fn issued_at(&self) -> Option<u64> {
+        self.iat
+    }
+}
+
+impl IssuerClaims for RegisteredClaims {
+    fn issuer(&self) -> Option<&str> {
+        self.iss.as_deref()
+    }
+}
+
+impl SubjectClaims for RegisteredClaims {
+    fn subject(&self) -> Option<&str> {
+        self.sub.as_deref()
+    }
+}
+
+pub(super) fn deserialize_audience<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Audience {
+        Single(String),
+        Multiple(Vec<String>),
+    }
+
+    Ok(match Audience::deserialize(deserializer)? {
+        Audience::Single(aud) => vec![aud],
+        Audience::Multiple(aud) => aud,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_audience() {
+        let claims: RegisteredClaims =
+            serde_json::from_str(r#"{"sub":"alice","aud":"api","scope":"read"}"#).unwrap();
+        assert_eq!(claims.sub.as_deref(), Some("alice"));
+        assert_eq!(claims.aud, vec!["api"]);
+        assert_eq!(claims.extra["scope"], "read");
+
+        let claims: RegisteredClaims = serde_json::from_str(r#"{"aud":["api","web"]}"#).unwrap();
+        assert_eq!(claims.aud, vec!["api", "web"]);
+
+        let claims: RegisteredClaims = serde_json::from_str("{}").unwrap();
+        assert!(claims.aud.is_empty());
+    }
+}
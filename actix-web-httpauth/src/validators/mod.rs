@@ -0,0 +1,216 @@
+//! Reusable validators for bearer tokens.
+//!
+//! A [`TokenValidator`] checks a token extracted from the `Authorization` header and produces the
+//! claims associated with it. Validators can be plugged into the authentication middleware using
+//! [`HttpAuthentication::bearer_validator`], which stores successfully validated tokens, along
+//! with their claims, in the request extensions as a [`ValidatedToken`].
+//!
+//! [`HttpAuthentication::bearer_validator`]: crate::middleware::HttpAuthentication::bearer_validator
+
+use std::{borrow::Cow, error::Error, fmt};
+
+use actix_web::{error, http::StatusCode, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+
+use crate::{
+    extractors::{bearer, AuthenticationError},
+    headers::www_authenticate::bearer::Bearer,
+};
+
+#[cfg(feature = "jwt")]
+mod access_token;
+mod audience;
+mod check;
+mod clock_skew;
+#[cfg(feature = "introspection")]
+mod introspection;
+mod issued_at;
+mod issuer;
+#[cfg(feature = "jwk")]
+mod jwk;
+#[cfg(feature = "jwt")]
+mod jwt;
+#[cfg(feature = "nonce")]
+mod nonce;
+#[cfg(feature = "one-time-tokens")]
+mod one_time;
+#[cfg(feature = "pat")]
+mod pat;
+#[cfg(feature = "pop")]
+mod pop;
+#[cfg(any(feature = "nonce", feature = "one-time-tokens", feature = "scram"))]
+pub(crate) mod replay;
+mod subject;
+#[cfg(test)]
+pub(crate) mod test_util;
+mod token_binding;
+
+#[cfg(feature = "jwt")]
+pub use self::access_token::{
+    JwtAccessTokenValidator, JwtAccessTokenValidatorBuilder, Rfc9068Claims,
+};
+pub use self::audience::{AudienceClaims, AudienceValidator, ExpectedAudience};
+pub use self::check::{ClaimsCheck, ClaimsPolicy};
+pub use self::clock_skew::{ClockSkewTolerance, ClockSkewTolerantValidator, ExpiringClaims};
+#[cfg(feature = "introspection")]
+pub use self::introspection::{IntrospectionClaims, IntrospectionError, IntrospectionValidator};
+pub use self::issued_at::{IssuedAtClaims, IssuedAtTolerance, StrictIatValidator};
+pub use self::issuer::{IssuerClaims, IssuerValidator, TrustedIssuers};
+#[cfg(feature = "jwk")]
+pub use self::jwk::{JwkBearerValidator, JwkError};
+#[cfg(feature = "jwt")]
+pub use self::jwt::RegisteredClaims;
+#[cfg(feature = "nonce")]
+pub use self::nonce::{NonceClaims, NonceValidator, SessionNonce};
+#[cfg(feature = "one-time-tokens")]
+pub use self::one_time::{OneTimeUse, OtpBearerValidator, TokenIdClaims};
+#[cfg(feature = "pat")]
+pub use self::pat::{DatabasePATValidator, UserClaims};
+#[cfg(feature = "pop")]
+pub use self::pop::{ConfirmationClaims, PoPValidator, ProofOfPossession, X_POP_SIGNATURE};
+pub use self::subject::{InMemorySubjects, SubjectClaims, SubjectLookup, SubjectValidator};
+pub use self::token_binding::{ChannelBinding, TokenBindingValidator};
+
+/// Asynchronous validation of bearer tokens.
+pub trait TokenValidator {
+    /// Claims associated with a successfully validated token.
+    type Claims: 'static;
+
+    /// Validates `token`, which was sent along with `req`.
+    fn validate(
+        &self,
+        req: &HttpRequest,
+        token: &str,
+    ) -> LocalBoxFuture<'static, Result<Self::Claims, ValidationError>>;
+}
+
+/// A bearer token accepted by a [`TokenValidator`], along with its claims.
+///
+/// Inserted into the request extensions by the middleware, so that handlers can use the claims for
+/// authorization decisions and still forward the raw token to other services. Can be extracted
+/// using [`ReqData`](actix_web::web::ReqData) if the claims implement [`Clone`].
+///
+/// # Examples
+/// ```
+/// use actix_web::web::ReqData;
+/// use actix_web_httpauth::validators::ValidatedToken;
+///
+/// async fn index(token: ReqData<ValidatedToken<String>>) -> String {
+///     format!("Hello, {}! Your token is {}.", token.claims, token.raw_token)
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ValidatedToken<C> {
+    /// Token as sent by the client.
+    pub raw_token: String,
+
+    /// Claims produced by the validator.
+    pub claims: C,
+}
+
+/// Reasons for a [`TokenValidator`] to reject a token.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// Token is malformed, its signature could not be verified, or it was otherwise rejected.
+    InvalidToken(Cow<'static, str>),
+
+    /// Token has expired.
+    Expired,
+
+    /// Token could not be validated due to an internal error, such as an unreachable database.
+    ///
+    /// This results in a `500 Internal Server Error` response, rather than a challenge.
+    Internal(Box<dyn Error>),
+}
+
+impl ValidationError {
+    /// Creates an [`InvalidToken`](Self::InvalidToken) error with the provided description.
+    pub fn invalid<T>(desc: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        ValidationError::InvalidToken(desc.into())
+    }
+
+    /// Creates an [`Internal`](Self::Internal) error from the underlying cause.
+    pub fn internal<E>(err: E) -> Self
+    where
+        E: Error + 'static,
+    {
+        ValidationError::Internal(Box::new(err))
+    }
+
+    /// Converts this error into a bearer [`AuthenticationError`] for the given challenge.
+    ///
+    /// [`Internal`](Self::Internal) errors are given the `500 Internal Server Error` status code.
+    pub fn into_authentication_error(self, challenge: Bearer) -> AuthenticationError<Bearer> {
+        if let ValidationError::Internal(_) = self {
+            let mut err = AuthenticationError::new(challenge);
+            *err.status_code_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            return err;
+        }
+
+        AuthenticationError::new(challenge)
+            .with_error(bearer::Error::InvalidToken)
+            .with_error_description(self.to_string())
+    }
+
+    /// Converts this error into a response error, using the [`bearer::Config`] registered as app
+    /// data (if any) to form the challenge.
+    pub(crate) fn into_response_error(self, req: &HttpRequest) -> actix_web::Error {
+        if let ValidationError::Internal(err) = self {
+            log::error!("Token validation failed: {}", err);
+            return error::ErrorInternalServerError("Token validation failed");
+        }
+
+        let challenge = req
+            .app_data::<bearer::Config>()
+            .map(|config| config.as_ref().clone())
+            .unwrap_or_default();
+
+        self.into_authentication_error(challenge).into()
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::InvalidToken(desc) => f.write_str(desc),
+            ValidationError::Expired => f.write_str("The access token expired"),
+            ValidationError::Internal(err) => fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+impl Error for ValidationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ValidationError::Internal(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header::WWW_AUTHENTICATE, test::TestRequest};
+
+    use super::*;
+
+    #[test]
+    fn test_error_challenge() {
+        let req = TestRequest::default()
+            .app_data(bearer::Config::default().realm("example"))
+            .to_http_request();
+
+        let err = ValidationError::Expired.into_response_error(&req);
+        let res = err.error_response();
+
+        assert_eq!(res.status(), 401);
+        assert_eq!(
+            res.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer realm=\"example\" error=\"invalid_token\" error_description=\"The access token expired\""
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use actix_session::SessionExt as _;
+use actix_web::HttpRequest;
+use futures_util::future::{self, LocalBoxFuture};
+
+use super::{replay::ReplayCache, ClaimsCheck, ClaimsPolicy, ExpiringClaims, ValidationError};
+use crate::utils::constant_time_eq;
+
+/// Default session key holding the expected nonce.
+const DEFAULT_SESSION_KEY: &str = "nonce";
+
+/// Claims that carry a nonce, e.g. OpenID Connect ID tokens.
+#[cfg_attr(docsrs, doc(cfg(feature = "nonce")))]
+pub trait NonceClaims {
+    /// Returns the nonce of the token, if the claims have one.
+    fn nonce(&self) -> Option<&str>;
+}
+
+#[cfg(feature = "jwt")]
+impl NonceClaims for super::RegisteredClaims {
+    fn nonce(&self) -> Option<&str> {
+        self.extra.get("nonce").and_then(|nonce| nonce.as_str())
+    }
+}
+
+/// Validator that rejects replayed tokens using the nonce stored in the user's session.
+#[cfg_attr(docsrs, doc(cfg(feature = "nonce")))]
+pub type NonceValidator<V> = ClaimsCheck<V, SessionNonce>;
+
+/// Policy accepting tokens whose `nonce` claim matches the nonce stored in the user's session,
+/// under the `nonce` key by default, when the authentication flow was started.
+///
+/// Each nonce is accepted only once: it is removed from the session and remembered as used until
+/// the token expires, so that a token cannot be replayed, not even with another session. Tokens
+/// without a nonce or an expiration time, and all tokens if the session has no nonce, are
+/// rejected.
+///
+/// Used nonces are kept in memory. To share them between workers, construct the validator once,
+/// outside of the `HttpServer::new` closure, and clone it. Requires the
+/// [`SessionMiddleware`](actix_session::SessionMiddleware) to be registered.
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::validators::SessionNonce;
+///
+/// let policy = SessionNonce::default().session_key("oidc_nonce");
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "nonce")))]
+#[derive(Debug, Clone)]
+pub struct SessionNonce {
+    session_key: String,
+    used: ReplayCache,
+}
+
+impl SessionNonce {
+    /// Sets the session key holding the expected nonce.
+    ///
+    /// Defaults to `nonce`.
+    pub fn session_key(mut self, key: impl Into<String>) -> Self {
+        self.session_key = key.into();
+        self
+    }
+
+    fn check_nonce<C>(&self, req: &HttpRequest, claims: &C) -> Result<(), ValidationError>
+    where
+        C: NonceClaims + ExpiringClaims,
+    {
+        let session = req.get_session();
+        let expected = session.get::<String>(&self.session_key).ok().flatten();
+
+        let (nonce, exp) = match (claims.nonce(), expected.as_deref(), claims.expires_at()) {
+            (Some(nonce), Some(expected), Some(exp))
+                if constant_time_eq(nonce.as_bytes(), expected.as_bytes()) =>
+            {
+                (nonce, exp)
+            }
+            _ => {
+                return Err(ValidationError::invalid(
+                    "The access token nonce does not match the session",
+                ))
+            }
+        };
+
+        session.remove(&self.session_key);
+
+        if !self.used.insert(nonce, exp) {
+            return Err(ValidationError::invalid(
+                "The access token nonce has already been used",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for SessionNonce {
+    fn default() -> Self {
+        SessionNonce {
+            session_key: DEFAULT_SESSION_KEY.to_owned(),
+            used: ReplayCache::default(),
+        }
+    }
+}
+
+impl<C: NonceClaims + ExpiringClaims> ClaimsPolicy<C> for SessionNonce {
+    fn check(
+        &self,
+        req: &HttpRequest,
+        _token: &str,
+        claims: &C,
+    ) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+        Box::pin(future::ready(self.check_nonce(req, claims)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{dev::Service as _, http::StatusCode, test, web, App, HttpResponse};
+
+    use super::*;
+    use crate::{middleware::HttpAuthentication, validators::test_util::Parsed};
+
+    #[actix_web::test]
+    async fn test_nonce() {
+        let validator = NonceValidator::new(Parsed, SessionNonce::default());
+
+        let srv = test::init_service(
+            App::new()
+                .wrap(HttpAuthentication::bearer_validator(validator))
+                .wrap_fn(|req, srv| {
+                    if let Some(nonce) = req.headers().get("x-nonce") {
+                        let nonce = nonce.to_str().unwrap().to_owned();
+                        req.get_session().insert("nonce", nonce).unwrap();
+                    }
+                    srv.call(req)
+                })
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let call = |nonce: Option<&str>, token: &str| {
+            let mut req = test::TestRequest::with_uri("/")
+                .insert_header(("Authorization", format!("Bearer {}", token)));
+            if let Some(nonce) = nonce {
+                req = req.insert_header(("x-nonce", nonce));
+            }
+            test::call_service(&srv, req.to_request())
+        };
+
+        let res = call(Some("n1"), "nonce=n2;exp=60").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = call(Some("n1"), "nonce=n1;exp=60").await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // replayed token
+        let res = call(Some("n1"), "nonce=n1;exp=60").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        // no expiration time
+        let res = call(Some("n3"), "nonce=n3").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = call(None, "nonce=n4;exp=60").await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+}
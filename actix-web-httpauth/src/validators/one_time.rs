@@ -0,0 +1,134 @@
+use actix_web::HttpRequest;
+use futures_util::future::{self, LocalBoxFuture};
+
+use super::{replay::ReplayCache, ClaimsCheck, ClaimsPolicy, ExpiringClaims, ValidationError};
+
+/// Claims that carry a unique token identifier.
+#[cfg_attr(docsrs, doc(cfg(feature = "one-time-tokens")))]
+pub trait TokenIdClaims {
+    /// Returns the unique identifier of the token, if the claims have one.
+    fn token_id(&self) -> Option<&str>;
+}
+
+#[cfg(feature = "jwt")]
+impl TokenIdClaims for super::RegisteredClaims {
+    fn token_id(&self) -> Option<&str> {
+        self.jti.as_deref()
+    }
+}
+
+#[cfg(feature = "jwt")]
+impl TokenIdClaims for super::Rfc9068Claims {
+    fn token_id(&self) -> Option<&str> {
+        Some(&self.jti)
+    }
+}
+
+/// Validator that accepts each token only once.
+#[cfg_attr(docsrs, doc(cfg(feature = "one-time-tokens")))]
+pub type OtpBearerValidator<V> = ClaimsCheck<V, OneTimeUse>;
+
+/// Policy accepting each token only once, identified by its `jti` claim.
+///
+/// This prevents replay of ephemeral tokens within their validity window. Tokens without an
+/// identifier or without an expiration time are rejected.
+///
+/// Identifiers are kept in memory until their token expires, plus the configured leeway. To share
+/// them between workers, construct the validator once, outside of the `HttpServer::new` closure,
+/// and clone it.
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::validators::OneTimeUse;
+///
+/// let policy = OneTimeUse::default().leeway_seconds(30);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "one-time-tokens")))]
+#[derive(Debug, Clone, Default)]
+pub struct OneTimeUse {
+    leeway: u64,
+    used: ReplayCache,
+}
+
+impl OneTimeUse {
+    /// Sets the number of seconds identifiers are kept after their token expires.
+    ///
+    /// Should be at least the clock skew tolerated by the wrapped validator, so that tokens
+    /// accepted after their expiration time cannot be replayed. Defaults to 0.
+    pub fn leeway_seconds(mut self, leeway: u64) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    fn check_token_id<C>(&self, claims: &C) -> Result<(), ValidationError>
+    where
+        C: TokenIdClaims + ExpiringClaims,
+    {
+        let (jti, exp) = match (claims.token_id(), claims.expires_at()) {
+            (Some(jti), Some(exp)) => (jti, exp),
+            _ => {
+                return Err(ValidationError::invalid(
+                    "The access token must have an identifier and an expiration time",
+                ))
+            }
+        };
+
+        if !self.used.insert(jti, exp.saturating_add(self.leeway)) {
+            return Err(ValidationError::invalid(
+                "The access token has already been used",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: TokenIdClaims + ExpiringClaims> ClaimsPolicy<C> for OneTimeUse {
+    fn check(
+        &self,
+        _req: &HttpRequest,
+        _token: &str,
+        claims: &C,
+    ) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+        Box::pin(future::ready(self.check_token_id(claims)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+    use crate::validators::{test_util::Parsed, TokenValidator as _};
+
+    #[actix_web::test]
+    async fn test_one_time() {
+        let req = TestRequest::default().to_http_request();
+        let validator = OtpBearerValidator::new(Parsed, OneTimeUse::default());
+
+        let res = validator.validate(&req, "jti=a;exp=60").await;
+        assert!(res.is_ok());
+        let res = validator.validate(&req, "jti=b;exp=60").await;
+        assert!(res.is_ok());
+
+        let res = validator.validate(&req, "jti=a;exp=60").await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+
+        let res = validator.validate(&req, "exp=60").await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+        let res = validator.validate(&req, "jti=c").await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+    }
+
+    #[actix_web::test]
+    async fn test_leeway() {
+        let req = TestRequest::default().to_http_request();
+        let validator = OtpBearerValidator::new(Parsed, OneTimeUse::default().leeway_seconds(60));
+
+        // expired, but still remembered within the leeway
+        let res = validator.validate(&req, "jti=a;exp=-10").await;
+        assert!(res.is_ok());
+        let res = validator.validate(&req, "jti=a;exp=-10").await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+    }
+}
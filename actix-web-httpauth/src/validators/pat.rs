@@ -0,0 +1,233 @@
+use std::{
+    fmt,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use actix_web::HttpRequest;
+use futures_util::future::LocalBoxFuture;
+use lru::LruCache;
+use sha2::{Digest as _, Sha256};
+use sqlx::PgPool;
+
+use super::{TokenValidator, ValidationError};
+
+/// Default number of validated tokens kept in the cache.
+const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+/// Default duration for which a validated token is cached.
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+const QUERY: &str =
+    "SELECT user_id::text, scopes FROM personal_access_tokens WHERE token_hash = $1";
+
+/// Claims associated with a personal access token.
+#[cfg_attr(docsrs, doc(cfg(feature = "pat")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserClaims {
+    /// ID of the user that the token belongs to.
+    pub user_id: String,
+
+    /// Scopes granted to the token.
+    pub scopes: Vec<String>,
+}
+
+type TokenHash = [u8; 32];
+
+/// Validator for opaque personal access tokens (PATs) stored in a PostgreSQL database.
+///
+/// Tokens are looked up by their SHA-256 hash in a `personal_access_tokens` table, which must
+/// provide at least the following columns:
+///
+/// | Column       | Type       | Description                          |
+/// |--------------|------------|--------------------------------------|
+/// | `token_hash` | `BYTEA`    | SHA-256 hash of the token.           |
+/// | `user_id`    | any        | ID of the user owning the token.     |
+/// | `scopes`     | `TEXT[]`   | Scopes granted to the token.         |
+///
+/// Successfully validated tokens are kept in an in-memory LRU cache, keyed by their hash, for a
+/// short time (one minute by default) to avoid hitting the database on every request. Revoked
+/// tokens may therefore be accepted until their cache entry expires.
+///
+/// # Examples
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use actix_web::{App, HttpServer};
+/// use actix_web_httpauth::{middleware::HttpAuthentication, validators::DatabasePATValidator};
+/// use sqlx::PgPool;
+///
+/// #[actix_web::main]
+/// async fn main() -> std::io::Result<()> {
+///     let pool = PgPool::connect("postgres://localhost/app").await.unwrap();
+///     let validator = DatabasePATValidator::new(Arc::new(pool));
+///
+///     HttpServer::new(move || {
+///         App::new().wrap(HttpAuthentication::bearer_validator(validator.clone()))
+///     })
+///     .bind(("127.0.0.1", 8080))?
+///     .run()
+///     .await
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "pat")))]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone)]
+pub struct DatabasePATValidator {
+    pool: Arc<PgPool>,
+    cache: Arc<Mutex<LruCache<TokenHash, (UserClaims, Instant)>>>,
+    cache_ttl: Duration,
+}
+
+impl DatabasePATValidator {
+    /// Constructs a validator that looks up tokens using `pool`.
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        let capacity = NonZeroUsize::new(DEFAULT_CACHE_CAPACITY).unwrap();
+
+        DatabasePATValidator {
+            pool,
+            cache: Arc::new(Mutex::new(LruCache::new(capacity))),
+            cache_ttl: DEFAULT_CACHE_TTL,
+        }
+    }
+
+    /// Sets the maximum number of validated tokens kept in the cache.
+    ///
+    /// Defaults to 1024.
+    pub fn cache_capacity(self, capacity: NonZeroUsize) -> Self {
+        self.cache
+            .lock()
+            .expect("PAT cache lock poisoned")
+            .resize(capacity);
+        self
+    }
+
+    /// Sets the duration for which a validated token is cached.
+    ///
+    /// Defaults to one minute. A zero duration disables caching.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Looks up `token`, returning the associated user ID and scopes.
+    async fn lookup(&self, token: &str) -> Result<UserClaims, ValidationError> {
+        let hash: TokenHash = Sha256::digest(token.as_bytes()).into();
+
+        if let Some(claims) = self.cached(&hash) {
+            return Ok(claims);
+        }
+
+        let row: Option<(String, Vec<String>)> = sqlx::query_as(QUERY)
+            .bind(&hash[..])
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(ValidationError::internal)?;
+
+        let (user_id, scopes) =
+            row.ok_or_else(|| ValidationError::invalid("The access token is invalid"))?;
+        let claims = UserClaims { user_id, scopes };
+
+        if !self.cache_ttl.is_zero() {
+            self.cache
+                .lock()
+                .expect("PAT cache lock poisoned")
+                .put(hash, (claims.clone(), Instant::now()));
+        }
+
+        Ok(claims)
+    }
+
+    fn cached(&self, hash: &TokenHash) -> Option<UserClaims> {
+        let mut cache = self.cache.lock().expect("PAT cache lock poisoned");
+
+        match cache.get(hash) {
+            Some((claims, cached_at)) if cached_at.elapsed() < self.cache_ttl => {
+                Some(claims.clone())
+            }
+            Some(_) => {
+                cache.pop(hash);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+impl TokenValidator for DatabasePATValidator {
+    type Claims = UserClaims;
+
+    fn validate(
+        &self,
+        _req: &HttpRequest,
+        token: &str,
+    ) -> LocalBoxFuture<'static, Result<Self::Claims, ValidationError>> {
+        let this = self.clone();
+        let token = token.to_owned();
+
+        Box::pin(async move { this.lookup(&token).await })
+    }
+}
+
+impl fmt::Debug for DatabasePATValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DatabasePATValidator")
+            .field("cache_ttl", &self.cache_ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    async fn validator() -> DatabasePATValidator {
+        // closed without ever connecting, so that lookups fail immediately
+        let pool = PgPool::connect_lazy("postgres://localhost/app").unwrap();
+        pool.close().await;
+        DatabasePATValidator::new(Arc::new(pool))
+    }
+
+    fn seed(validator: &DatabasePATValidator, token: &str, cached_at: Instant) -> UserClaims {
+        let claims = UserClaims {
+            user_id: "42".to_owned(),
+            scopes: vec!["repo".to_owned()],
+        };
+
+        let hash = Sha256::digest(token.as_bytes()).into();
+        validator
+            .cache
+            .lock()
+            .unwrap()
+            .put(hash, (claims.clone(), cached_at));
+
+        claims
+    }
+
+    #[actix_web::test]
+    async fn test_cached_token() {
+        let req = TestRequest::default().to_http_request();
+        let validator = validator().await;
+        let claims = seed(&validator, "pat_123", Instant::now());
+
+        assert_eq!(validator.validate(&req, "pat_123").await.unwrap(), claims);
+    }
+
+    #[actix_web::test]
+    async fn test_database_error() {
+        let req = TestRequest::default().to_http_request();
+        let validator = validator().await.cache_ttl(Duration::from_secs(1));
+        seed(
+            &validator,
+            "pat_123",
+            Instant::now() - Duration::from_secs(2),
+        );
+
+        let err = validator.validate(&req, "pat_123").await.unwrap_err();
+        assert!(matches!(err, ValidationError::Internal(_)));
+        assert!(validator.cache.lock().unwrap().is_empty());
+    }
+}
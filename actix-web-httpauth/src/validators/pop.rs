@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
+
+use actix_web::{http::header::HeaderName, HttpRequest};
+use futures_util::future::{self, LocalBoxFuture};
+use josekit::{jwk::Jwk, jwt};
+use serde_json::Value;
+use sha2::{Digest as _, Sha256};
+
+use super::{ClaimsCheck, ClaimsPolicy, ValidationError};
+use crate::extractors::dpop::{request_url, strip_query, verifier};
+
+/// Name of the request header carrying the proof-of-possession signature.
+pub const X_POP_SIGNATURE: HeaderName = HeaderName::from_static("x-pop-signature");
+
+/// Default maximum difference between the issue time of a proof and the current time.
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Claims that may bind a token to a key using the confirmation (`cnf`) claim of [RFC 7800].
+///
+/// [RFC 7800]: https://tools.ietf.org/html/rfc7800
+#[cfg_attr(docsrs, doc(cfg(feature = "pop")))]
+pub trait ConfirmationClaims {
+    /// Returns the `cnf` claim of the token, if the claims have one.
+    fn confirmation(&self) -> Option<&Value>;
+}
+
+#[cfg(feature = "jwt")]
+impl ConfirmationClaims for super::RegisteredClaims {
+    fn confirmation(&self) -> Option<&Value> {
+        self.extra.get("cnf")
+    }
+}
+
+/// Validator that requires clients to prove possession of the key their token is bound to.
+#[cfg_attr(docsrs, doc(cfg(feature = "pop")))]
+pub type PoPValidator<V> = ClaimsCheck<V, ProofOfPossession>;
+
+/// Policy requiring clients to prove possession of the key their token is bound to.
+///
+/// The key is resolved from the token's `cnf` claim ([RFC 7800 §3]): either the public key
+/// embedded as `cnf.jwk`, or the key registered with the policy under the ID in `cnf.kid`. The
+/// request must then carry an `X-PoP-Signature` header containing a JWT signed with that key, with
+/// the claims:
+///
+/// - `htm` and `htu`: the method and URL (without query and fragment) of the request;
+/// - `iat`: the time the proof was created, within one minute of the current time by default;
+/// - `ath`: the base64url-encoded SHA-256 hash of the access token.
+///
+/// Tokens that are not bound to a key, bound to an unknown key, or sent without a valid proof are
+/// rejected. Detecting replayed proofs is left to the application.
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::validators::ProofOfPossession;
+/// use josekit::jwk::Jwk;
+///
+/// let key = Jwk::from_bytes(br#"{
+///     "kty": "EC",
+///     "crv": "P-256",
+///     "x": "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+///     "y": "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"
+/// }"#).unwrap();
+///
+/// let policy = ProofOfPossession::default().key("client-key-1", key);
+/// ```
+///
+/// [RFC 7800 §3]: https://tools.ietf.org/html/rfc7800#section-3
+#[cfg_attr(docsrs, doc(cfg(feature = "pop")))]
+#[derive(Debug, Clone)]
+pub struct ProofOfPossession {
+    keys: HashMap<String, Jwk>,
+    max_age: Duration,
+}
+
+impl ProofOfPossession {
+    /// Registers the public key used by tokens whose `cnf.kid` claim is `kid`.
+    pub fn key(mut self, kid: impl Into<String>, jwk: Jwk) -> Self {
+        self.keys.insert(kid.into(), jwk);
+        self
+    }
+
+    /// Sets the maximum difference between the issue time of a proof and the current time.
+    ///
+    /// Defaults to one minute.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    fn check_proof(
+        &self,
+        req: &HttpRequest,
+        token: &str,
+        cnf: Option<&Value>,
+    ) -> Result<(), ValidationError> {
+        let jwk = confirmation_key(cnf, &self.keys)?;
+        let proof = req
+            .headers()
+            .get(X_POP_SIGNATURE)
+            .and_then(|proof| proof.to_str().ok())
+            .ok_or_else(|| ValidationError::invalid("The proof of possession is missing"))?;
+
+        let header = jwt::decode_header(proof)
+            .map_err(|_| ValidationError::invalid("The proof of possession is malformed"))?;
+        let alg = header.claim("alg").and_then(|alg| alg.as_str());
+        let verifier = verifier(alg, &jwk).ok_or_else(|| {
+            ValidationError::invalid("The proof of possession algorithm is unsupported")
+        })?;
+
+        let (payload, _) = jwt::decode_with_verifier(proof, &*verifier).map_err(|_| {
+            ValidationError::invalid("The proof of possession signature is invalid")
+        })?;
+
+        let htm = payload.claim("htm").and_then(|htm| htm.as_str());
+        let htu = payload.claim("htu").and_then(|htu| htu.as_str());
+        if htm != Some(req.method().as_str()) || htu.map(strip_query) != Some(&*request_url(req)) {
+            return Err(ValidationError::invalid(
+                "The proof of possession does not match the request",
+            ));
+        }
+
+        let iat = payload
+            .issued_at()
+            .ok_or_else(|| ValidationError::invalid("The proof of possession has no issue time"))?;
+        let now = SystemTime::now();
+        let age = now
+            .duration_since(iat)
+            .or_else(|_| iat.duration_since(now))
+            .unwrap_or_default();
+        if age > self.max_age {
+            return Err(ValidationError::invalid(
+                "The proof of possession is not fresh",
+            ));
+        }
+
+        let ath = base64::encode_config(Sha256::digest(token.as_bytes()), base64::URL_SAFE_NO_PAD);
+        if payload.claim("ath").and_then(|ath| ath.as_str()) != Some(ath.as_str()) {
+            return Err(ValidationError::invalid(
+                "The proof of possession does not match the access token",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ProofOfPossession {
+    fn default() -> Self {
+        ProofOfPossession {
+            keys: HashMap::new(),
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+}
+
+impl<C: ConfirmationClaims> ClaimsPolicy<C> for ProofOfPossession {
+    fn check(
+        &self,
+        req: &HttpRequest,
+        token: &str,
+        claims: &C,
+    ) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+        Box::pin(future::ready(self.check_proof(
+            req,
+            token,
+            claims.confirmation(),
+        )))
+    }
+}
+
+/// Resolves the key a token is bound to from its `cnf` claim.
+fn confirmation_key(
+    cnf: Option<&Value>,
+    keys: &HashMap<String, Jwk>,
+) -> Result<Jwk, ValidationError> {
+    let cnf = cnf
+        .and_then(Value::as_object)
+        .ok_or_else(|| ValidationError::invalid("The access token is not bound to a key"))?;
+
+    if let Some(jwk) = cnf.get("jwk") {
+        let jwk = jwk
+            .as_object()
+            .and_then(|jwk| Jwk::from_map(jwk.clone()).ok())
+            .filter(|jwk| jwk.parameter("d").is_none())
+            .ok_or_else(|| ValidationError::invalid("The access token key is invalid"))?;
+        return Ok(jwk);
+    }
+
+    cnf.get("kid")
+        .and_then(Value::as_str)
+        .and_then(|kid| keys.get(kid))
+        .cloned()
+        .ok_or_else(|| ValidationError::invalid("The access token key is unknown"))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header::HOST, test::TestRequest};
+    use josekit::{
+        jwk::alg::ec::EcKeyPair,
+        jws::{JwsHeader, ES256},
+        jwt::JwtPayload,
+    };
+    use serde_json::json;
+
+    use super::*;
+    use crate::validators::{
+        test_util::{Fixed, TestClaims},
+        TokenValidator as _,
+    };
+
+    /// Accepts all tokens, bound to a key with the confirmation claim `cnf`.
+    fn confirmed(cnf: Value) -> Fixed {
+        Fixed(TestClaims {
+            cnf: Some(cnf),
+            ..TestClaims::default()
+        })
+    }
+
+    const TOKEN: &str = "Kz~8mXK1EalYznwH-LC-1fBAo.4Ljp~zsPE_NeO.gxU";
+
+    fn proof(key_pair: &EcKeyPair, htm: &str) -> String {
+        let ath = base64::encode_config(Sha256::digest(TOKEN.as_bytes()), base64::URL_SAFE_NO_PAD);
+
+        let mut payload = JwtPayload::new();
+        payload.set_issued_at(&SystemTime::now());
+        payload.set_claim("htm", Some(json!(htm))).unwrap();
+        payload
+            .set_claim("htu", Some(json!("http://example.com/resource")))
+            .unwrap();
+        payload.set_claim("ath", Some(json!(ath))).unwrap();
+
+        let signer = ES256.signer_from_jwk(&key_pair.to_jwk_key_pair()).unwrap();
+        jwt::encode_with_signer(&payload, &JwsHeader::new(), &signer).unwrap()
+    }
+
+    fn request(proof: Option<&str>) -> HttpRequest {
+        let mut req = TestRequest::get()
+            .uri("/resource?page=2")
+            .insert_header((HOST, "example.com"));
+        if let Some(proof) = proof {
+            req = req.insert_header((X_POP_SIGNATURE, proof));
+        }
+        req.to_http_request()
+    }
+
+    #[actix_web::test]
+    async fn test_embedded_key() {
+        let key_pair = ES256.generate_key_pair().unwrap();
+        let cnf = json!({ "jwk": Value::Object(key_pair.to_jwk_public_key().into()) });
+        let validator = PoPValidator::new(confirmed(cnf), ProofOfPossession::default());
+
+        let req = request(Some(&proof(&key_pair, "GET")));
+        assert!(validator.validate(&req, TOKEN).await.is_ok());
+
+        // wrong method
+        let req = request(Some(&proof(&key_pair, "POST")));
+        let err = validator.validate(&req, TOKEN).await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+
+        // wrong token
+        let req = request(Some(&proof(&key_pair, "GET")));
+        let err = validator.validate(&req, "other-token").await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+
+        // signed with another key
+        let other = ES256.generate_key_pair().unwrap();
+        let req = request(Some(&proof(&other, "GET")));
+        let err = validator.validate(&req, TOKEN).await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+
+        // missing proof
+        let err = validator.validate(&request(None), TOKEN).await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+    }
+
+    #[actix_web::test]
+    async fn test_key_id() {
+        let key_pair = ES256.generate_key_pair().unwrap();
+        let validator = PoPValidator::new(
+            confirmed(json!({ "kid": "client-key-1" })),
+            ProofOfPossession::default().key("client-key-1", key_pair.to_jwk_public_key()),
+        );
+
+        let req = request(Some(&proof(&key_pair, "GET")));
+        assert!(validator.validate(&req, TOKEN).await.is_ok());
+
+        // unknown key
+        let validator = PoPValidator::new(
+            confirmed(json!({ "kid": "client-key-1" })),
+            ProofOfPossession::default(),
+        );
+        let err = validator.validate(&req, TOKEN).await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+
+        // unbound token
+        let validator = PoPValidator::new(confirmed(json!({})), ProofOfPossession::default());
+        let err = validator.validate(&req, TOKEN).await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use crate::utils::unix_timestamp;
+
+/// Minimum number of seconds between two sweeps of expired identifiers.
+const SWEEP_INTERVAL: u64 = 60;
+
+/// Identifiers of used tokens, such as nonces or `jti` claims, each kept until it expires.
+///
+/// Expired identifiers are swept when new ones are inserted, at most once per minute, so that
+/// memory use is bounded by the number of tokens used within their lifetime. Clones share the same
+/// identifiers.
+#[derive(Clone, Default)]
+pub(crate) struct ReplayCache {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Default)]
+struct Inner {
+    used: HashMap<String, u64>,
+    last_sweep: u64,
+}
+
+impl ReplayCache {
+    /// Marks `id` as used until `expires_at`, in seconds since the Unix epoch.
+    ///
+    /// Returns `false` if `id` is already in use.
+    pub(crate) fn insert(&self, id: &str, expires_at: u64) -> bool {
+        let now = unix_timestamp();
+        let mut inner = self.inner.lock().unwrap();
+
+        if now.saturating_sub(inner.last_sweep) >= SWEEP_INTERVAL {
+            inner.used.retain(|_, exp| *exp > now);
+            inner.last_sweep = now;
+        }
+
+        match inner.used.get(id) {
+            Some(&exp) if exp > now => false,
+            _ => {
+                inner.used.insert(id.to_owned(), expires_at);
+                true
+            }
+        }
+    }
+
+    /// Returns the number of identifiers kept, including expired ones not yet swept.
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.inner.lock().unwrap().used.len()
+    }
+}
+
+impl fmt::Debug for ReplayCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReplayCache").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay() {
+        let cache = ReplayCache::default();
+        let now = unix_timestamp();
+
+        assert!(cache.insert("a", now + 60));
+        assert!(cache.insert("b", now + 60));
+        assert!(!cache.insert("a", now + 60));
+
+        // expired identifiers may be reused
+        assert!(cache.insert("c", now));
+        assert!(cache.insert("c", now + 60));
+    }
+
+    #[test]
+    fn test_sweep() {
+        let cache = ReplayCache::default();
+        let now = unix_timestamp();
+
+        assert!(cache.insert("a", now));
+        assert!(cache.insert("b", now + 60));
+        assert_eq!(cache.len(), 2);
+
+        cache.inner.lock().unwrap().last_sweep = now - SWEEP_INTERVAL;
+        assert!(cache.insert("c", now + 60));
+
+        let inner = cache.inner.lock().unwrap();
+        assert!(!inner.used.contains_key("a"));
+        assert!(inner.used.contains_key("b"));
+        assert!(inner.used.contains_key("c"));
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    future::Future,
+    sync::{Arc, RwLock},
+};
+
+use actix_web::HttpRequest;
+use futures_util::future::{self, LocalBoxFuture, Ready};
+
+use super::{ClaimsCheck, ClaimsPolicy, ValidationError};
+
+/// Claims that identify the subject of a token, usually a user.
+pub trait SubjectClaims {
+    /// Returns the subject of the token, if the claims have one.
+    fn subject(&self) -> Option<&str>;
+}
+
+/// Validator that rejects tokens whose subject no longer exists.
+pub type SubjectValidator<V, S> = ClaimsCheck<V, SubjectLookup<S>>;
+
+/// Policy accepting tokens for which a lookup function, called with their subject, resolves to
+/// `true`.
+///
+/// This way, tokens issued to deleted or suspended users stop being accepted before they expire.
+/// Tokens without a subject are rejected.
+///
+/// # Examples
+/// ```
+/// # async fn is_active_user(_id: &str) -> bool { true }
+/// use actix_web_httpauth::validators::SubjectLookup;
+///
+/// let policy = SubjectLookup::new(|sub: String| async move { is_active_user(&sub).await });
+/// ```
+pub struct SubjectLookup<S> {
+    lookup: S,
+}
+
+impl<S> SubjectLookup<S> {
+    /// Accepts tokens for which `lookup` resolves to `true` when called with their subject.
+    pub fn new(lookup: S) -> Self {
+        SubjectLookup { lookup }
+    }
+}
+
+impl<C, S, F> ClaimsPolicy<C> for SubjectLookup<S>
+where
+    C: SubjectClaims,
+    S: Fn(String) -> F,
+    F: Future<Output = bool> + 'static,
+{
+    fn check(
+        &self,
+        _req: &HttpRequest,
+        _token: &str,
+        claims: &C,
+    ) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+        let known = claims.subject().map(|sub| (self.lookup)(sub.to_owned()));
+
+        Box::pin(async move {
+            let known = match known {
+                Some(known) => known.await,
+                None => false,
+            };
+
+            if !known {
+                return Err(ValidationError::invalid(
+                    "The subject of the access token is unknown or disabled",
+                ));
+            }
+
+            Ok(())
+        })
+    }
+}
+
+impl<S> fmt::Debug for SubjectLookup<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubjectLookup").finish_non_exhaustive()
+    }
+}
+
+/// In-memory set of known subjects, for use with [`SubjectLookup`] in tests and examples.
+///
+/// Clones share the same set, so subjects can be removed while the policy is in use.
+///
+/// # Examples
+/// ```
+/// use actix_web_httpauth::validators::{InMemorySubjects, SubjectLookup};
+///
+/// let subjects = InMemorySubjects::new(["alice", "bob"]);
+/// let policy = SubjectLookup::new(subjects.lookup());
+///
+/// // tokens issued to bob are rejected from now on
+/// subjects.remove("bob");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InMemorySubjects {
+    subjects: Arc<RwLock<HashSet<String>>>,
+}
+
+impl InMemorySubjects {
+    /// Creates a set containing `subjects`.
+    pub fn new<I, T>(subjects: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let subjects = subjects.into_iter().map(Into::into).collect();
+
+        InMemorySubjects {
+            subjects: Arc::new(RwLock::new(subjects)),
+        }
+    }
+
+    /// Adds a subject to the set.
+    pub fn insert(&self, subject: impl Into<String>) {
+        self.subjects.write().unwrap().insert(subject.into());
+    }
+
+    /// Removes a subject from the set, returning whether it was present.
+    pub fn remove(&self, subject: &str) -> bool {
+        self.subjects.write().unwrap().remove(subject)
+    }
+
+    /// Returns whether the set contains `subject`.
+    pub fn contains(&self, subject: &str) -> bool {
+        self.subjects.read().unwrap().contains(subject)
+    }
+
+    /// Returns a lookup function for [`SubjectLookup`], backed by this set.
+    pub fn lookup(&self) -> impl Fn(String) -> Ready<bool> + 'static {
+        let subjects = self.clone();
+        move |sub| future::ready(subjects.contains(&sub))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+    use crate::validators::{test_util::Parsed, TokenValidator as _};
+
+    #[actix_web::test]
+    async fn test_subject() {
+        let req = TestRequest::default().to_http_request();
+        let subjects = InMemorySubjects::new(["alice", "bob"]);
+        let validator = SubjectValidator::new(Parsed, SubjectLookup::new(subjects.lookup()));
+
+        let res = validator.validate(&req, "sub=alice").await;
+        assert!(res.is_ok());
+        let res = validator.validate(&req, "sub=bob").await;
+        assert!(res.is_ok());
+
+        let res = validator.validate(&req, "sub=mallory").await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+
+        let res = validator.validate(&req, "").await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+
+        assert!(subjects.remove("bob"));
+        let res = validator.validate(&req, "sub=bob").await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+
+        subjects.insert("mallory");
+        let res = validator.validate(&req, "sub=mallory").await;
+        assert!(res.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_async_lookup() {
+        let req = TestRequest::default().to_http_request();
+        let validator = SubjectValidator::new(
+            Parsed,
+            SubjectLookup::new(|sub: String| async move { sub.starts_with("user-") }),
+        );
+
+        let res = validator.validate(&req, "sub=user-1").await;
+        assert!(res.is_ok());
+        let res = validator.validate(&req, "sub=admin").await;
+        assert!(matches!(res, Err(ValidationError::InvalidToken(_))));
+    }
+}
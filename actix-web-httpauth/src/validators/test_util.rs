@@ -0,0 +1,132 @@
+//! Validators shared by the tests of the claims policies.
+
+use actix_web::HttpRequest;
+use futures_util::future::{self, LocalBoxFuture};
+
+use super::{
+    AudienceClaims, ExpiringClaims, IssuedAtClaims, IssuerClaims, SubjectClaims, TokenValidator,
+    ValidationError,
+};
+
+/// Claims produced by [`Parsed`] and [`Fixed`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TestClaims {
+    pub(crate) exp: Option<u64>,
+    pub(crate) iat: Option<u64>,
+    pub(crate) aud: Vec<String>,
+    pub(crate) iss: Option<String>,
+    pub(crate) sub: Option<String>,
+    #[cfg(feature = "nonce")]
+    pub(crate) nonce: Option<String>,
+    #[cfg(feature = "one-time-tokens")]
+    pub(crate) jti: Option<String>,
+    #[cfg(feature = "pop")]
+    pub(crate) cnf: Option<serde_json::Value>,
+}
+
+/// Accepts tokens made up of `claim=value` pairs separated by `;`, e.g. `aud=api,web;exp=60`.
+///
+/// Times are given in seconds relative to now and may be negative; audiences are separated by `,`.
+pub(crate) struct Parsed;
+
+impl TokenValidator for Parsed {
+    type Claims = TestClaims;
+
+    fn validate(
+        &self,
+        _req: &HttpRequest,
+        token: &str,
+    ) -> LocalBoxFuture<'static, Result<TestClaims, ValidationError>> {
+        let time = |secs: &str| {
+            let secs = secs.parse::<i64>().unwrap();
+            (crate::utils::unix_timestamp() as i64 + secs) as u64
+        };
+
+        let mut claims = TestClaims::default();
+
+        for (name, value) in token.split(';').filter_map(|pair| pair.split_once('=')) {
+            match name {
+                "exp" => claims.exp = Some(time(value)),
+                "iat" => claims.iat = Some(time(value)),
+                "aud" => claims.aud = value.split(',').map(ToOwned::to_owned).collect(),
+                "iss" => claims.iss = Some(value.to_owned()),
+                "sub" => claims.sub = Some(value.to_owned()),
+                #[cfg(feature = "nonce")]
+                "nonce" => claims.nonce = Some(value.to_owned()),
+                #[cfg(feature = "one-time-tokens")]
+                "jti" => claims.jti = Some(value.to_owned()),
+                _ => panic!("unknown claim {}", name),
+            }
+        }
+
+        Box::pin(future::ok(claims))
+    }
+}
+
+/// Accepts all tokens, producing the same claims for each.
+#[cfg(feature = "pop")]
+pub(crate) struct Fixed(pub(crate) TestClaims);
+
+#[cfg(feature = "pop")]
+impl TokenValidator for Fixed {
+    type Claims = TestClaims;
+
+    fn validate(
+        &self,
+        _req: &HttpRequest,
+        _token: &str,
+    ) -> LocalBoxFuture<'static, Result<TestClaims, ValidationError>> {
+        Box::pin(future::ok(self.0.clone()))
+    }
+}
+
+impl AudienceClaims for TestClaims {
+    fn audience(&self) -> &[String] {
+        &self.aud
+    }
+}
+
+impl ExpiringClaims for TestClaims {
+    fn expires_at(&self) -> Option<u64> {
+        self.exp
+    }
+}
+
+impl IssuedAtClaims for TestClaims {
+    fn issued_at(&self) -> Option<u64> {
+        self.iat
+    }
+}
+
+impl IssuerClaims for TestClaims {
+    fn issuer(&self) -> Option<&str> {
+        self.iss.as_deref()
+    }
+}
+
+impl SubjectClaims for TestClaims {
+    fn subject(&self) -> Option<&str> {
+        self.sub.as_deref()
+    }
+}
+
+#[cfg(feature = "nonce")]
+impl super::NonceClaims for TestClaims {
+    fn nonce(&self) -> Option<&str> {
+        self.nonce.as_deref()
+    }
+}
+
+#[cfg(feature = "one-time-tokens")]
+impl super::TokenIdClaims for TestClaims {
+    fn token_id(&self) -> Option<&str> {
+        self.jti.as_deref()
+    }
+}
+
+#[cfg(feature = "pop")]
+impl super::ConfirmationClaims for TestClaims {
+    fn confirmation(&self) -> Option<&serde_json::Value> {
+        self.cnf.as_ref()
+    }
+}
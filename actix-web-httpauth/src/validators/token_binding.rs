@@ -0,0 +1,172 @@
+use actix_web::{http::header::HeaderName, HttpMessage as _, HttpRequest};
+use futures_util::future::{self, LocalBoxFuture};
+
+use super::{TokenValidator, ValidationError};
+use crate::utils::constant_time_eq;
+
+/// Name of the header carrying the Token Binding message, defined in [RFC 8473 §2].
+///
+/// [RFC 8473 §2]: https://tools.ietf.org/html/rfc8473#section-2
+const SEC_TOKEN_BINDING: HeaderName = HeaderName::from_static("sec-token-binding");
+
+/// Token Binding data of the TLS connection a request was received on.
+///
+/// Contains the Token Binding message that the client is expected to send in the
+/// `Sec-Token-Binding` header, as established by the TLS layer during the handshake (see
+/// [RFC 8471]). It must be inserted into the request extensions before [`TokenBindingValidator`]
+/// runs, e.g. by a middleware wrapping the authentication middleware.
+///
+/// [RFC 8471]: https://tools.ietf.org/html/rfc8471
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelBinding(pub Vec<u8>);
+
+/// Validator that binds bearer tokens to the TLS connection they are sent over.
+///
+/// If a request contains a `Sec-Token-Binding` header, its (base64url-encoded) value is checked
+/// against the [`ChannelBinding`] stored in the request extensions before the token is passed on
+/// to the inner validator. Requests whose binding is malformed, missing from the extensions or
+/// does not match are rejected with `401 Unauthorized`. Requests without the header are passed on
+/// to the inner validator unchanged.
+///
+/// Note that this validator does not inspect the TLS session itself; the TLS layer is responsible
+/// for verifying the Token Binding signatures and injecting the resulting [`ChannelBinding`].
+///
+/// # Examples
+/// ```
+/// # use actix_web_httpauth::validators::{TokenBindingValidator, TokenValidator, ValidationError};
+/// # use actix_web::HttpRequest;
+/// # use futures_util::future::{self, LocalBoxFuture};
+/// # struct StaticToken;
+/// # impl TokenValidator for StaticToken {
+/// #     type Claims = ();
+/// #     fn validate(&self, _: &HttpRequest, _: &str) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+/// #         Box::pin(future::ok(()))
+/// #     }
+/// # }
+/// use actix_web_httpauth::middleware::HttpAuthentication;
+///
+/// let validator = TokenBindingValidator::new(StaticToken);
+/// let middleware = HttpAuthentication::bearer_validator(validator);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TokenBindingValidator<V> {
+    inner: V,
+}
+
+impl<V> TokenBindingValidator<V> {
+    /// Wraps `inner`, which validates tokens after their binding has been checked.
+    pub fn new(inner: V) -> Self {
+        TokenBindingValidator { inner }
+    }
+
+    /// Returns a reference to the inner validator.
+    pub fn get_ref(&self) -> &V {
+        &self.inner
+    }
+}
+
+impl<V: TokenValidator> TokenValidator for TokenBindingValidator<V> {
+    type Claims = V::Claims;
+
+    fn validate(
+        &self,
+        req: &HttpRequest,
+        token: &str,
+    ) -> LocalBoxFuture<'static, Result<Self::Claims, ValidationError>> {
+        if let Err(err) = verify_binding(req) {
+            return Box::pin(future::err(err));
+        }
+
+        self.inner.validate(req, token)
+    }
+}
+
+/// Checks the `Sec-Token-Binding` header of `req`, if any, against its [`ChannelBinding`].
+fn verify_binding(req: &HttpRequest) -> Result<(), ValidationError> {
+    let header = match req.headers().get(SEC_TOKEN_BINDING) {
+        Some(header) => header,
+        None => return Ok(()),
+    };
+
+    let provided = header
+        .to_str()
+        .ok()
+        .and_then(|value| base64::decode_config(value.trim(), base64::URL_SAFE_NO_PAD).ok())
+        .ok_or_else(|| ValidationError::invalid("The token binding is malformed"))?;
+
+    let extensions = req.extensions();
+    let expected = extensions
+        .get::<ChannelBinding>()
+        .ok_or_else(|| ValidationError::invalid("The token binding could not be verified"))?;
+
+    if !constant_time_eq(&provided, &expected.0) {
+        return Err(ValidationError::invalid(
+            "The token binding does not match the connection",
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    struct StaticToken;
+
+    impl TokenValidator for StaticToken {
+        type Claims = &'static str;
+
+        fn validate(
+            &self,
+            _req: &HttpRequest,
+            _token: &str,
+        ) -> LocalBoxFuture<'static, Result<Self::Claims, ValidationError>> {
+            Box::pin(future::ok("alice"))
+        }
+    }
+
+    fn request(binding: Option<&str>, channel: Option<&[u8]>) -> HttpRequest {
+        let mut req = TestRequest::default();
+        if let Some(binding) = binding {
+            req = req.insert_header((SEC_TOKEN_BINDING, binding));
+        }
+
+        let req = req.to_http_request();
+        if let Some(channel) = channel {
+            req.extensions_mut()
+                .insert(ChannelBinding(channel.to_vec()));
+        }
+        req
+    }
+
+    #[actix_web::test]
+    async fn test_token_binding() {
+        let validator = TokenBindingValidator::new(StaticToken);
+
+        // no binding header
+        let req = request(None, None);
+        assert_eq!(validator.validate(&req, "token").await.unwrap(), "alice");
+
+        // matching binding
+        let req = request(Some("AIkAAgBB"), Some(b"\x00\x89\x00\x02\x00\x41"));
+        assert_eq!(validator.validate(&req, "token").await.unwrap(), "alice");
+
+        // mismatched binding
+        let req = request(Some("AIkAAgBB"), Some(b"\x00\x89\x00\x02\x00\x42"));
+        let err = validator.validate(&req, "token").await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+
+        // missing channel binding data
+        let req = request(Some("AIkAAgBB"), None);
+        let err = validator.validate(&req, "token").await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+
+        // malformed binding
+        let req = request(Some("not base64!"), Some(b"\x00"));
+        let err = validator.validate(&req, "token").await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidToken(_)));
+    }
+}
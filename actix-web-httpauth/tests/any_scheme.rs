@@ -0,0 +1,113 @@
+//! Combining validators for several authentication schemes with `HttpAuthentication::any`.
+
+use actix_web::{
+    dev::{Service as _, ServiceRequest},
+    http::{
+        header::{AUTHORIZATION, WWW_AUTHENTICATE},
+        StatusCode,
+    },
+    test, web, App, Error, HttpMessage as _, HttpResponse,
+};
+use actix_web_httpauth::{
+    extractors::{
+        basic::{self, BasicAuth},
+        bearer::{self, BearerAuth},
+        AuthenticationError,
+    },
+    middleware::{boxed_basic, boxed_bearer, HttpAuthentication},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Scheme(&'static str);
+
+async fn basic_validator(
+    req: ServiceRequest,
+    credentials: BasicAuth,
+) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    if credentials.user_id() == "admin" && credentials.password() == Some("secret") {
+        req.extensions_mut().insert(Scheme("basic"));
+        Ok(req)
+    } else {
+        let config = req.app_data::<basic::Config>().cloned().unwrap_or_default();
+        Err((AuthenticationError::from(config).into(), req))
+    }
+}
+
+async fn bearer_validator(
+    req: ServiceRequest,
+    credentials: BearerAuth,
+) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    if credentials.token() == "mF_9.B5f-4.1JqM" {
+        req.extensions_mut().insert(Scheme("bearer"));
+        Ok(req)
+    } else {
+        let config = req
+            .app_data::<bearer::Config>()
+            .cloned()
+            .unwrap_or_default();
+        Err((AuthenticationError::from(config).into(), req))
+    }
+}
+
+async fn index(scheme: web::ReqData<Scheme>) -> HttpResponse {
+    HttpResponse::Ok().body(scheme.0)
+}
+
+#[actix_web::test]
+async fn basic_and_bearer() {
+    let srv = test::init_service(
+        App::new()
+            .app_data(basic::Config::default().realm("example"))
+            .app_data(bearer::Config::default().realm("example"))
+            .wrap(HttpAuthentication::any(vec![
+                boxed_bearer(bearer_validator),
+                boxed_basic(basic_validator),
+            ]))
+            .route("/", web::get().to(index)),
+    )
+    .await;
+
+    // admin:secret
+    let req = test::TestRequest::with_uri("/")
+        .insert_header((AUTHORIZATION, "Basic YWRtaW46c2VjcmV0"))
+        .to_request();
+    let res = srv.call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(test::read_body(res).await, "basic");
+
+    let req = test::TestRequest::with_uri("/")
+        .insert_header((AUTHORIZATION, "Bearer mF_9.B5f-4.1JqM"))
+        .to_request();
+    let res = srv.call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(test::read_body(res).await, "bearer");
+}
+
+#[actix_web::test]
+async fn all_rejected() {
+    let srv = test::init_service(
+        App::new()
+            .app_data(basic::Config::default().realm("example"))
+            .app_data(bearer::Config::default().realm("example"))
+            .wrap(HttpAuthentication::any(vec![
+                boxed_bearer(bearer_validator),
+                boxed_basic(basic_validator),
+            ]))
+            .route("/", web::get().to(index)),
+    )
+    .await;
+
+    let challenges = r#"Bearer realm="example", Basic realm="example""#;
+
+    let req = test::TestRequest::with_uri("/").to_request();
+    let res = srv.call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(res.headers().get(WWW_AUTHENTICATE).unwrap(), challenges);
+
+    let req = test::TestRequest::with_uri("/")
+        .insert_header((AUTHORIZATION, "Bearer invalid"))
+        .to_request();
+    let res = srv.call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(res.headers().get(WWW_AUTHENTICATE).unwrap(), challenges);
+}
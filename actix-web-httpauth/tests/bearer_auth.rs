@@ -0,0 +1,100 @@
+//! Handling of malformed `Authorization` headers by the `Bearer` scheme, see [RFC 6750 §2.1].
+//!
+//! [RFC 6750 §2.1]: https://tools.ietf.org/html/rfc6750#section-2.1
+
+use actix_web::{
+    http::{
+        header::{HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE},
+        StatusCode,
+    },
+    test::TestRequest,
+    FromRequest as _, ResponseError as _,
+};
+use actix_web_httpauth::{
+    extractors::bearer::BearerAuth,
+    headers::authorization::{Bearer, ParseError, Scheme as _},
+};
+
+async fn extract(value: HeaderValue) -> Result<BearerAuth, StatusCode> {
+    let (req, mut payload) = TestRequest::default()
+        .insert_header((AUTHORIZATION, value))
+        .to_http_parts();
+
+    BearerAuth::from_request(&req, &mut payload)
+        .await
+        .map_err(|err| {
+            assert!(err
+                .error_response()
+                .headers()
+                .contains_key(WWW_AUTHENTICATE));
+            err.status_code()
+        })
+}
+
+#[actix_web::test]
+async fn missing_header() {
+    let (req, mut payload) = TestRequest::default().to_http_parts();
+    let err = BearerAuth::from_request(&req, &mut payload)
+        .await
+        .unwrap_err();
+    assert_eq!(err.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn empty_header() {
+    let value = HeaderValue::from_static("");
+    assert!(matches!(Bearer::parse(&value), Err(ParseError::Invalid)));
+    assert_eq!(extract(value).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn wrong_scheme() {
+    let value = HeaderValue::from_static("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==");
+    assert!(matches!(
+        Bearer::parse(&value),
+        Err(ParseError::MissingScheme)
+    ));
+    assert_eq!(extract(value).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn missing_token() {
+    for value in ["Bearer", "Bearer "] {
+        let value = HeaderValue::from_static(value);
+        assert!(matches!(Bearer::parse(&value), Err(ParseError::Invalid)));
+        assert_eq!(extract(value).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[actix_web::test]
+async fn whitespace_token() {
+    let value = HeaderValue::from_static("Bearer  \t  ");
+    assert!(matches!(Bearer::parse(&value), Err(ParseError::Invalid)));
+    assert_eq!(extract(value).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn binary_token() {
+    let value = HeaderValue::from_bytes(b"Bearer \xde\xad\xbe\xef").unwrap();
+    assert!(matches!(
+        Bearer::parse(&value),
+        Err(ParseError::ToStrError(_))
+    ));
+    assert_eq!(extract(value).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn valid_token() {
+    let value = HeaderValue::from_static("Bearer mF_9.B5f-4.1JqM");
+    assert_eq!(Bearer::parse(&value).unwrap().token(), "mF_9.B5f-4.1JqM");
+    assert_eq!(extract(value).await.unwrap().token(), "mF_9.B5f-4.1JqM");
+}
+
+#[actix_web::test]
+async fn case_insensitive_scheme() {
+    for value in ["bearer mF_9.B5f-4.1JqM", "BEARER mF_9.B5f-4.1JqM"] {
+        let value = HeaderValue::from_static(value);
+        assert_eq!(Bearer::parse(&value).unwrap().token(), "mF_9.B5f-4.1JqM");
+        assert_eq!(extract(value).await.unwrap().token(), "mF_9.B5f-4.1JqM");
+    }
+}
@@ -0,0 +1,148 @@
+//! Localization of the `error_description` of `Bearer` challenges using the `Accept-Language`
+//! header.
+
+use actix_web::{
+    dev::{Service as _, ServiceRequest},
+    http::{
+        header::{AcceptLanguage, Header as _, ACCEPT_LANGUAGE, AUTHORIZATION, WWW_AUTHENTICATE},
+        StatusCode,
+    },
+    test, web, App, Error, HttpRequest, HttpResponse,
+};
+use actix_web_httpauth::{
+    extractors::{
+        bearer::{self, BearerAuth},
+        AuthenticationError,
+    },
+    middleware::HttpAuthentication,
+    validators::{TokenValidator, ValidationError},
+};
+use futures_util::future::{self, LocalBoxFuture};
+
+struct StaticToken;
+
+impl TokenValidator for StaticToken {
+    type Claims = ();
+
+    fn validate(
+        &self,
+        _req: &HttpRequest,
+        token: &str,
+    ) -> LocalBoxFuture<'static, Result<(), ValidationError>> {
+        let res = if token == "mF_9.B5f-4.1JqM" {
+            Ok(())
+        } else {
+            Err(ValidationError::invalid("The access token is invalid"))
+        };
+
+        Box::pin(future::ready(res))
+    }
+}
+
+fn config() -> bearer::Config {
+    bearer::Config::default()
+        .realm("example")
+        .error_description_locale("fr", "Le jeton d'accès est invalide")
+}
+
+#[actix_web::test]
+async fn extractor() {
+    let srv =
+        test::init_service(App::new().app_data(config()).route(
+            "/",
+            web::get().to(|auth: BearerAuth| async move {
+                HttpResponse::Ok().body(auth.token().to_owned())
+            }),
+        ))
+        .await;
+
+    // no error information is sent when credentials are missing
+    let req = test::TestRequest::with_uri("/")
+        .insert_header((ACCEPT_LANGUAGE, "fr"))
+        .to_request();
+    let res = srv.call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        res.headers().get(WWW_AUTHENTICATE).unwrap(),
+        "Bearer realm=\"example\""
+    );
+}
+
+#[actix_web::test]
+async fn validator() {
+    let srv = test::init_service(
+        App::new()
+            .app_data(config())
+            .wrap(HttpAuthentication::bearer_validator(StaticToken))
+            .route("/", web::get().to(HttpResponse::Ok)),
+    )
+    .await;
+
+    // the validator's description is more specific than the localized ones
+    for lang in ["fr", "de"] {
+        let req = test::TestRequest::with_uri("/")
+            .insert_header((AUTHORIZATION, "Bearer invalid"))
+            .insert_header((ACCEPT_LANGUAGE, lang))
+            .to_request();
+        let res = srv.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            res.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer realm=\"example\" error=\"invalid_token\" error_description=\"The access token is invalid\""
+        );
+    }
+}
+
+#[actix_web::test]
+async fn manual() {
+    async fn validator(
+        req: ServiceRequest,
+        credentials: BearerAuth,
+    ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+        if credentials.token() == "mF_9.B5f-4.1JqM" {
+            return Ok(req);
+        }
+
+        let config = req
+            .app_data::<bearer::Config>()
+            .cloned()
+            .unwrap_or_default();
+        let mut err = AuthenticationError::from(config).with_error(bearer::Error::InvalidToken);
+
+        if let Ok(accept_language) = AcceptLanguage::parse(&req) {
+            err.challenge_mut().localize(&accept_language);
+        }
+
+        Err((err.into(), req))
+    }
+
+    let srv = test::init_service(
+        App::new()
+            .app_data(config())
+            .wrap(HttpAuthentication::bearer(validator))
+            .route("/", web::get().to(HttpResponse::Ok)),
+    )
+    .await;
+
+    let req = test::TestRequest::with_uri("/")
+        .insert_header((AUTHORIZATION, "Bearer invalid"))
+        .insert_header((ACCEPT_LANGUAGE, "fr"))
+        .to_request();
+    let res = srv.call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        res.headers().get(WWW_AUTHENTICATE).unwrap(),
+        "Bearer realm=\"example\" error=\"invalid_token\" error_description=\"Le jeton d'accès est invalide\""
+    );
+
+    let req = test::TestRequest::with_uri("/")
+        .insert_header((AUTHORIZATION, "Bearer invalid"))
+        .insert_header((ACCEPT_LANGUAGE, "de"))
+        .to_request();
+    let res = srv.call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        res.headers().get(WWW_AUTHENTICATE).unwrap(),
+        "Bearer realm=\"example\" error=\"invalid_token\""
+    );
+}